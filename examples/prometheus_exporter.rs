@@ -0,0 +1,62 @@
+//! Serves statistics of a cgroup subtree in the Prometheus text exposition format.
+//!
+//! Usage: `prometheus_exporter [CGROUP_NAME] [ADDRESS]`
+//!
+//! `CGROUP_NAME` is the name of the root of the subtree (e.g. `students`), defaulting to the root
+//! cgroup. `ADDRESS` defaults to `127.0.0.1:9753`. Metrics are served on any path, e.g.
+//! `curl http://127.0.0.1:9753/metrics`.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    path::PathBuf,
+};
+
+use controlgroup::v1::{prometheus::Exporter, UnifiedRepr};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let name = PathBuf::from(args.next().unwrap_or_default());
+    let address = args.next().unwrap_or_else(|| "127.0.0.1:9753".to_string());
+
+    let cgroups = UnifiedRepr::discover(name)?;
+    let listener = TcpListener::bind(&address)?;
+    println!("Listening on http://{}/metrics", address);
+
+    for stream in listener.incoming() {
+        if let Err(e) = handle(stream?, &cgroups) {
+            eprintln!("{}", e);
+        }
+    }
+
+    Ok(())
+}
+
+fn handle(mut stream: TcpStream, cgroups: &UnifiedRepr) -> Result<(), Box<dyn std::error::Error>> {
+    // Skips the request line and headers.
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+
+    let mut exporter = Exporter::new();
+    let (status, content_type, body) = match exporter.add_recursive(cgroups) {
+        Ok(()) => ("200 OK", "text/plain; version=0.0.4", exporter.to_string()),
+        Err(e) => (
+            "500 Internal Server Error",
+            "text/plain",
+            format!("{}\n", e),
+        ),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
@@ -0,0 +1,423 @@
+//! Command-line interface to cgroup v1 hierarchies, built on `controlgroup` crate.
+//!
+//! Run `cgctl help` for usage. Values of options are validated with the parsers of the crate
+//! before anything is written to the cgroup file system.
+
+use std::{
+    collections::HashSet,
+    error::Error as StdError,
+    fmt, fs,
+    path::{Path, PathBuf},
+    process::{self, Command},
+    str::FromStr,
+    time::Duration,
+};
+
+use controlgroup::{
+    v1::{
+        mount::MountInfo, prometheus::Exporter, CommandExt, DeleteOptions, Drain, Resources,
+        SubsystemKind, UnifiedRepr,
+    },
+    Max,
+};
+
+const USAGE: &str = "\
+Usage: cgctl [-s SUBSYSTEMS] COMMAND NAME [OPTIONS]
+
+Commands:
+    create NAME [RESOURCE OPTIONS]      Create cgroups and apply resource limits
+    apply NAME [RESOURCE OPTIONS]       Apply resource limits to existing cgroups
+    exec NAME -- PROGRAM [ARGS...]      Execute a program in cgroups
+    show NAME                           Print the current resource limits in JSON
+    stat NAME                           Print statistics in Prometheus text format
+    tree NAME                           List cgroups in the subtree with number of processes
+    freeze NAME [--timeout SECS]        Freeze cgroups and wait until frozen (default 10 secs)
+    thaw NAME                           Thaw cgroups
+    delete NAME [--recursive] [--kill]  Delete cgroups (and descendants, killing tasks)
+    help                                Print this message
+
+Options:
+    -s, --subsystems LIST    Comma-separated subsystems to operate on (e.g. cpu,memory).
+                             Defaults to all mounted subsystems.
+
+Resource options:
+    --spec FILE              Read resource limits from a JSON file; other options override it
+    --cpu-shares N           cpu.shares
+    --cpu-quota US           cpu.cfs_quota_us (-1 for no limit)
+    --cpu-period US          cpu.cfs_period_us
+    --cpus IDS               cpuset.cpus (e.g. 0-3,7)
+    --mems IDS               cpuset.mems
+    --memory-limit BYTES     memory.limit_in_bytes (suffix K, M, or G allowed; -1 for no limit)
+    --memory-swappiness N    memory.swappiness
+    --blkio-weight N         blkio.weight
+    --pids-max N             pids.max (`max` for no limit)
+    --classid ID             net_cls.classid (e.g. 10:1)
+    --device-allow RULE      devices.allow (e.g. \"c 1:3 mr\"), can be repeated
+    --device-deny RULE       devices.deny, can be repeated
+";
+
+fn main() {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(code) => process::exit(code),
+        Err(e) => {
+            eprintln!("cgctl: {}", e);
+            process::exit(if e.is::<UsageError>() { 2 } else { 1 });
+        }
+    }
+}
+
+type BoxResult<T> = Result<T, Box<dyn StdError>>;
+
+#[derive(Debug)]
+struct UsageError(String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\n\n{}", self.0, USAGE)
+    }
+}
+
+impl StdError for UsageError {}
+
+macro_rules! usage {
+    ($( $arg: tt )*) => {
+        return Err(Box::new(UsageError(format!($( $arg )*))))
+    };
+}
+
+fn run(mut args: Vec<String>) -> BoxResult<i32> {
+    let subsystems = match args.first().map(String::as_str) {
+        Some("-s") | Some("--subsystems") => {
+            if args.len() < 2 {
+                usage!("missing value for {}", args[0]);
+            }
+            let list = args.remove(1);
+            args.remove(0);
+            Some(parse_subsystems(&list)?)
+        }
+        _ => None,
+    };
+
+    if args.is_empty() {
+        usage!("missing command");
+    }
+    let command = args.remove(0);
+    if command == "help" || command == "-h" || command == "--help" {
+        print!("{}", USAGE);
+        return Ok(0);
+    }
+
+    if args.is_empty() {
+        usage!("missing cgroup name");
+    }
+    let name = PathBuf::from(args.remove(0));
+    let mut cgroups = unified_repr(name, subsystems.as_ref())?;
+
+    match command.as_str() {
+        "create" => {
+            let resources = parse_resources(&args)?;
+            // The guard deletes the created cgroups if applying the resources failed.
+            let mut guard = cgroups.create_guarded()?;
+            guard.apply_transactional(&resources)?;
+            guard.keep();
+        }
+        "apply" => {
+            let resources = parse_resources(&args)?;
+            cgroups.apply_transactional(&resources)?;
+        }
+        "exec" => {
+            let program = match args.iter().position(|a| a == "--") {
+                Some(i) => &args[i + 1..],
+                None => &args[..],
+            };
+            if program.is_empty() {
+                usage!("missing program to execute");
+            }
+
+            let status = Command::new(&program[0])
+                .args(&program[1..])
+                .cgroups_unified_repr(&mut cgroups)
+                .status()?;
+            return Ok(status.code().unwrap_or(1));
+        }
+        "show" => {
+            no_options(&args)?;
+            println!("{}", serde_json::to_string_pretty(&cgroups.resources()?)?);
+        }
+        "stat" => {
+            no_options(&args)?;
+            let mut exporter = Exporter::new();
+            exporter.add(&cgroups)?;
+            print!("{}", exporter);
+        }
+        "tree" => {
+            no_options(&args)?;
+            let depth = |c: &UnifiedRepr| c.name().map_or(0, |n| n.components().count());
+            let base = depth(&cgroups);
+            for cgroup in cgroups.walk() {
+                let cgroup = cgroup?;
+                let procs = cgroup.procs()?.values().map(Vec::len).max().unwrap_or(0);
+                println!(
+                    "{:indent$}{} ({} procs)",
+                    "",
+                    Path::new("/")
+                        .join(cgroup.name().unwrap_or_else(|| Path::new("")))
+                        .display(),
+                    procs,
+                    indent = 2 * (depth(&cgroup) - base)
+                );
+            }
+        }
+        "freeze" => {
+            let mut timeout = Duration::from_secs(10);
+            let mut options = args.iter();
+            while let Some(option) = options.next() {
+                match option.as_str() {
+                    "--timeout" => timeout = Duration::from_secs(value(option, options.next())?),
+                    _ => usage!("unknown option: {}", option),
+                }
+            }
+            freezer(&mut cgroups)?.freeze_and_wait(timeout)?;
+        }
+        "thaw" => {
+            no_options(&args)?;
+            freezer(&mut cgroups)?.thaw()?;
+        }
+        "delete" => {
+            let (mut recursive, mut kill) = (false, false);
+            for option in &args {
+                match option.as_str() {
+                    "--recursive" => recursive = true,
+                    "--kill" => kill = true,
+                    _ => usage!("unknown option: {}", option),
+                }
+            }
+
+            if recursive {
+                let drain = if kill { Drain::Kill } else { Drain::Parent };
+                cgroups.delete_recursive(&DeleteOptions {
+                    drain,
+                    ..DeleteOptions::default()
+                })?;
+            } else if kill {
+                usage!("--kill requires --recursive");
+            } else {
+                cgroups.delete()?;
+            }
+        }
+        _ => usage!("unknown command: {}", command),
+    }
+
+    Ok(0)
+}
+
+// Defines cgroups in the mounted hierarchies. Co-mounted subsystems are created only once.
+fn unified_repr(name: PathBuf, subsystems: Option<&Vec<SubsystemKind>>) -> BoxResult<UnifiedRepr> {
+    let mount_info = MountInfo::load()?;
+    let subsystems = match subsystems {
+        Some(subsystems) => subsystems,
+        None => return Ok(UnifiedRepr::with_mount_info(name, &mount_info)),
+    };
+
+    let mut paths = Vec::new();
+    let mut skip = Vec::new();
+    let mut mount_points = HashSet::new();
+    for &kind in subsystems {
+        let hierarchy = match mount_info.hierarchy(kind) {
+            Some(hierarchy) => hierarchy,
+            None => return Err(format!("subsystem {} is not mounted", kind).into()),
+        };
+        if !mount_points.insert(hierarchy.mount_point()) {
+            skip.push(kind);
+        }
+        paths.push((kind, mount_info.cgroup_path(kind, name.clone()).unwrap()));
+    }
+
+    let mut cgroups = UnifiedRepr::with_custom_name_subsystems(paths);
+    cgroups.skip_create(&skip);
+    Ok(cgroups)
+}
+
+fn freezer(cgroups: &mut UnifiedRepr) -> BoxResult<&mut controlgroup::v1::freezer::Subsystem> {
+    match cgroups.freezer_mut() {
+        Some(freezer) => Ok(freezer),
+        None => Err("freezer subsystem is not available".into()),
+    }
+}
+
+fn parse_subsystems(list: &str) -> BoxResult<Vec<SubsystemKind>> {
+    list.split(',')
+        .map(|s| parse_value("--subsystems", s))
+        .collect()
+}
+
+fn parse_resources(args: &[String]) -> BoxResult<Resources> {
+    // `--spec` is read first, so that other options override it.
+    let mut resources = match args.iter().position(|a| a == "--spec") {
+        Some(i) => {
+            let path = value::<String>("--spec", args.get(i + 1))?;
+            serde_json::from_str(&fs::read_to_string(&path)?)
+                .map_err(|e| format!("invalid spec file {}: {}", path, e))?
+        }
+        None => Resources::default(),
+    };
+
+    let mut options = args.iter();
+    while let Some(option) = options.next() {
+        let val = options.next();
+        match option.as_str() {
+            "--spec" => {}
+            "--cpu-shares" => resources.cpu.shares = Some(value(option, val)?),
+            "--cpu-quota" => resources.cpu.cfs_quota_us = Some(value(option, val)?),
+            "--cpu-period" => resources.cpu.cfs_period_us = Some(value(option, val)?),
+            "--cpus" => resources.cpuset.cpus = Some(value(option, val)?),
+            "--mems" => resources.cpuset.mems = Some(value(option, val)?),
+            "--memory-limit" => {
+                resources.memory.limit_in_bytes = Some(bytes(option, val)?);
+            }
+            "--memory-swappiness" => resources.memory.swappiness = Some(value(option, val)?),
+            "--blkio-weight" => resources.blkio.weight = Some(value(option, val)?),
+            "--pids-max" => resources.pids.max = Some(value::<Max>(option, val)?),
+            "--classid" => resources.net_cls.classid = Some(value(option, val)?),
+            "--device-allow" => resources.devices.allow.push(value(option, val)?),
+            "--device-deny" => resources.devices.deny.push(value(option, val)?),
+            _ => usage!("unknown option: {}", option),
+        }
+    }
+
+    Ok(resources)
+}
+
+fn no_options(args: &[String]) -> BoxResult<()> {
+    match args.first() {
+        Some(option) => usage!("unknown option: {}", option),
+        None => Ok(()),
+    }
+}
+
+fn value<T>(option: &str, val: Option<&String>) -> BoxResult<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    match val {
+        Some(val) => parse_value(option, val),
+        None => usage!("missing value for {}", option),
+    }
+}
+
+fn parse_value<T>(option: &str, val: &str) -> BoxResult<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    val.parse()
+        .map_err(|e| format!("invalid value for {}: {:?} ({})", option, val, e).into())
+}
+
+// Parses a number of bytes with an optional binary suffix, e.g. `512M`.
+fn bytes(option: &str, val: Option<&String>) -> BoxResult<i64> {
+    let val = value::<String>(option, val)?;
+    let (num, shift) = match val.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&val[..val.len() - 1], 10),
+        Some('M') => (&val[..val.len() - 1], 20),
+        Some('G') => (&val[..val.len() - 1], 30),
+        _ => (&val[..], 0),
+    };
+
+    let num: i64 = parse_value(option, num)?;
+    if num < 0 && shift != 0 {
+        return Err(format!("invalid value for {}: {:?}", option, val).into());
+    }
+
+    num.checked_mul(1 << shift)
+        .ok_or_else(|| format!("invalid value for {}: {:?} (too large)", option, val).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_resources() {
+        let resources = parse_resources(&args(&[
+            "--cpu-shares",
+            "512",
+            "--cpus",
+            "0-1,3",
+            "--memory-limit",
+            "64M",
+            "--pids-max",
+            "max",
+            "--device-deny",
+            "a",
+            "--device-allow",
+            "c 1:3 mr",
+        ]))
+        .unwrap();
+
+        let mut expected = Resources::default();
+        expected.cpu.shares = Some(512);
+        expected.cpuset.cpus = Some([0, 1, 3].iter().copied().collect());
+        expected.memory.limit_in_bytes = Some(64 * 1024 * 1024);
+        expected.pids.max = Some(Max::Max);
+        expected.devices.deny = vec!["a".parse().unwrap()];
+        expected.devices.allow = vec!["c 1:3 mr".parse().unwrap()];
+
+        assert_eq!(resources, expected);
+    }
+
+    #[test]
+    fn test_parse_resources_spec() {
+        let path = std::env::temp_dir().join("controlgroup_rs-cgctl-spec.json");
+        fs::write(
+            &path,
+            r#"{"memory":{"limit_in_bytes":1000},"pids":{"max":"max"}}"#,
+        )
+        .unwrap();
+        let spec = path.to_str().unwrap();
+
+        let resources = parse_resources(&args(&["--spec", spec, "--pids-max", "42"])).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let mut expected = Resources::default();
+        expected.memory.limit_in_bytes = Some(1000);
+        expected.pids.max = Some(Max::Limit(42));
+
+        assert_eq!(resources, expected);
+    }
+
+    #[test]
+    fn err_parse_resources() {
+        assert!(parse_resources(&args(&["--cpu-shares", "-1"])).is_err());
+        assert!(parse_resources(&args(&["--cpus", "0-"])).is_err());
+        assert!(parse_resources(&args(&["--pids-max", "unlimited"])).is_err());
+        assert!(parse_resources(&args(&["--cpu-shares"])).is_err());
+        assert!(parse_resources(&args(&["--unknown", "0"])).is_err());
+    }
+
+    #[test]
+    fn test_bytes() {
+        let b = |s: &str| bytes("--memory-limit", Some(&s.to_string())).ok();
+        assert_eq!(b("4096"), Some(4096));
+        assert_eq!(b("-1"), Some(-1));
+        assert_eq!(b("4k"), Some(4096));
+        assert_eq!(b("2G"), Some(2 << 30));
+        assert_eq!(b("-1M"), None);
+        assert_eq!(b("M"), None);
+        assert_eq!(b("9999999999G"), None);
+    }
+
+    #[test]
+    fn test_parse_subsystems() {
+        assert_eq!(
+            parse_subsystems("cpu,memory").unwrap(),
+            vec![SubsystemKind::Cpu, SubsystemKind::Memory]
+        );
+        assert!(parse_subsystems("cpu,mem").is_err());
+    }
+}
@@ -0,0 +1,13 @@
+//! Release agent that forwards the path to an emptied cgroup to
+//! `controlgroup::v1::release_agent::DEFAULT_SOCKET_PATH`.
+//!
+//! See `controlgroup::v1::release_agent` module for more information.
+
+use controlgroup::v1::release_agent;
+
+fn main() {
+    if let Err(e) = release_agent::run(release_agent::DEFAULT_SOCKET_PATH) {
+        eprintln!("controlgroup-release-agent: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -1,4 +1,8 @@
-use std::{error::Error as StdError, fmt};
+use std::{
+    error::Error as StdError,
+    fmt, io,
+    path::{Path, PathBuf},
+};
 
 /// Result type returned from this crate.
 pub type Result<T> = std::result::Result<T, Error>;
@@ -7,13 +11,28 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// lower-level source of this error can be obtained via [`kind`] and [`source`] methods,
 /// respectively.
 ///
+/// If the error occurred while reading or writing a cgroup file, the path to the file and the
+/// value attempted to be written can be obtained via [`path`] and [`attempted_value`] methods.
+/// The cause reported by the system (kernel) can be examined via [`errno`] method, or
+/// [`is_not_found`], [`is_permission_denied`], [`is_busy`], and [`is_invalid_value`] methods for
+/// typical cases.
+///
 /// [`Result::Err`]: https://doc.rust-lang.org/std/result/enum.Result.html#variant.Err
 /// [`kind`]: #method.kind
 /// [`source`]: https://doc.rust-lang.org/nightly/std/error/trait.Error.html#method.source
+/// [`path`]: #method.path
+/// [`attempted_value`]: #method.attempted_value
+/// [`errno`]: #method.errno
+/// [`is_not_found`]: #method.is_not_found
+/// [`is_permission_denied`]: #method.is_permission_denied
+/// [`is_busy`]: #method.is_busy
+/// [`is_invalid_value`]: #method.is_invalid_value
 #[derive(Debug)]
 pub struct Error {
     kind: ErrorKind,
     source: Option<Box<dyn StdError + Sync + Send + 'static>>,
+    path: Option<PathBuf>,
+    value: Option<String>,
 }
 
 /// Kinds of errors that can occur while operating on cgroups.
@@ -24,7 +43,9 @@ pub enum ErrorKind {
 
     /// Failed to parse a content of a cgroup file into a value.
     ///
-    /// In a future version, there will be some information attached to this variant.
+    /// The path to the file can be obtained via [`Error::path`] method.
+    ///
+    /// [`Error::path`]: struct.Error.html#method.path
     Parse,
 
     /// You passed an invalid argument.
@@ -54,6 +75,9 @@ pub enum ErrorKind {
     /// [`Error`]: struct.Error.html
     /// [`Error::source`]: https://doc.rust-lang.org/nightly/std/error/trait.Error.html#method.source
     InvalidOperation,
+
+    /// An operation did not complete within the given time.
+    Timeout,
 }
 
 impl StdError for Error {
@@ -72,8 +96,15 @@ impl fmt::Display for Error {
             ErrorKind::Parse => "Unable to parse a content of a cgroup file",
             ErrorKind::InvalidArgument => "Invalid argument",
             ErrorKind::InvalidOperation => "Invalid operation",
+            ErrorKind::Timeout => "Operation timed out",
         })?;
 
+        match (&self.path, &self.value) {
+            (Some(path), Some(value)) => write!(f, " (writing {:?} to {})", value, path.display())?,
+            (Some(path), None) => write!(f, " ({})", path.display())?,
+            _ => {}
+        }
+
         if let Some(ref source) = self.source {
             write!(f, ": {}", source)?;
         }
@@ -84,7 +115,12 @@ impl fmt::Display for Error {
 
 impl Error {
     pub(crate) fn new(kind: ErrorKind) -> Self {
-        Self { kind, source: None }
+        Self {
+            kind,
+            source: None,
+            path: None,
+            value: None,
+        }
     }
 
     pub(crate) fn with_source<E>(kind: ErrorKind, source: E) -> Self
@@ -92,11 +128,27 @@ impl Error {
         E: StdError + Sync + Send + 'static,
     {
         Self {
-            kind,
             source: Some(Box::new(source)),
+            ..Self::new(kind)
         }
     }
 
+    // Records the file on which the error occurred, unless already recorded.
+    pub(crate) fn at(mut self, path: impl Into<PathBuf>) -> Self {
+        if self.path.is_none() {
+            self.path = Some(path.into());
+        }
+        self
+    }
+
+    // Records the value attempted to be written, unless already recorded.
+    pub(crate) fn writing(mut self, value: impl Into<String>) -> Self {
+        if self.value.is_none() {
+            self.value = Some(value.into());
+        }
+        self
+    }
+
     pub(crate) fn parse<E>(source: E) -> Self
     where
         E: StdError + Sync + Send + 'static,
@@ -108,6 +160,61 @@ impl Error {
     pub fn kind(&self) -> ErrorKind {
         self.kind
     }
+
+    /// Returns the path to the cgroup file on which this error occurred, if any.
+    #[allow(clippy::option_as_ref_deref)] // `Option::as_deref` requires Rust 1.40
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_ref().map(PathBuf::as_path)
+    }
+
+    /// Returns the name of the cgroup file on which this error occurred (e.g. `cpu.shares`), if
+    /// any.
+    pub fn file_name(&self) -> Option<&str> {
+        self.path()
+            .and_then(Path::file_name)
+            .and_then(|n| n.to_str())
+    }
+
+    /// Returns the value that was attempted to be written to the cgroup file, if this error
+    /// occurred while writing.
+    #[allow(clippy::option_as_ref_deref)] // `Option::as_deref` requires Rust 1.40
+    pub fn attempted_value(&self) -> Option<&str> {
+        self.value.as_ref().map(String::as_str)
+    }
+
+    /// Returns the error number reported by the system, if this error was caused by a system
+    /// call.
+    pub fn errno(&self) -> Option<i32> {
+        self.io_error().and_then(io::Error::raw_os_error)
+    }
+
+    /// Returns whether this error was caused by a missing file or directory (`ENOENT`), e.g.
+    /// because the subsystem is not mounted, the cgroup does not exist, or the kernel does not
+    /// support the file.
+    pub fn is_not_found(&self) -> bool {
+        self.io_error().map(io::Error::kind) == Some(io::ErrorKind::NotFound)
+    }
+
+    /// Returns whether this error was caused by lack of permission (`EACCES` or `EPERM`), e.g.
+    /// because the cgroup is not delegated to the current user.
+    pub fn is_permission_denied(&self) -> bool {
+        self.io_error().map(io::Error::kind) == Some(io::ErrorKind::PermissionDenied)
+    }
+
+    /// Returns whether this error was caused by a busy resource (`EBUSY`), e.g. because the cgroup
+    /// still has tasks or child cgroups.
+    pub fn is_busy(&self) -> bool {
+        self.errno() == Some(libc::EBUSY)
+    }
+
+    /// Returns whether this error was caused by the kernel rejecting a written value (`EINVAL`).
+    pub fn is_invalid_value(&self) -> bool {
+        self.errno() == Some(libc::EINVAL)
+    }
+
+    fn io_error(&self) -> Option<&io::Error> {
+        self.source.as_ref().and_then(|s| s.downcast_ref())
+    }
 }
 
 impl From<std::io::Error> for Error {
@@ -129,3 +236,39 @@ fn test_error_impl_sync_send() {
     let _: &dyn Sync = &_e;
     let _: &dyn Send = &_e;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_context() {
+        let e = Error::from(io::Error::from_raw_os_error(libc::EBUSY))
+            .at("/sys/fs/cgroup/cpu/students/cpu.shares")
+            .writing("1024");
+
+        assert_eq!(e.kind(), ErrorKind::Io);
+        assert_eq!(
+            e.path(),
+            Some(Path::new("/sys/fs/cgroup/cpu/students/cpu.shares"))
+        );
+        assert_eq!(e.file_name(), Some("cpu.shares"));
+        assert_eq!(e.attempted_value(), Some("1024"));
+        assert_eq!(e.errno(), Some(libc::EBUSY));
+        assert!(e.is_busy());
+        assert!(!e.is_not_found() && !e.is_permission_denied() && !e.is_invalid_value());
+        assert!(e
+            .to_string()
+            .starts_with("Unable to do an I/O operation on a cgroup file system (writing \"1024\" to /sys/fs/cgroup/cpu/students/cpu.shares): "));
+
+        let e = Error::new(ErrorKind::Parse).at("/sys/fs/cgroup/cpu/cpu.stat");
+        assert_eq!(e.file_name(), Some("cpu.stat"));
+        assert_eq!(e.attempted_value(), None);
+        assert_eq!(e.errno(), None);
+        assert!(!e.is_not_found());
+
+        let e = Error::from(io::Error::from(io::ErrorKind::NotFound));
+        assert!(e.is_not_found());
+        assert_eq!(e.path(), None);
+    }
+}
@@ -0,0 +1,36 @@
+use std::{
+    fs::{File, OpenOptions},
+    path::PathBuf,
+};
+
+use crate::{v1::backend::Backend, Error, Result};
+
+// Opens a cgroup file and parses its content, recording the path in the error on failure.
+pub fn read<T>(
+    backend: &dyn Backend,
+    path: PathBuf,
+    parser: impl FnOnce(File) -> Result<T>,
+) -> Result<T> {
+    backend
+        .open(&path, OpenOptions::new().read(true))
+        .map_err(Error::from)
+        .and_then(parser)
+        .map_err(|e| e.at(path))
+}
+
+// Writes a value to a cgroup file, recording the path and the value in the error on failure.
+pub fn write(backend: &dyn Backend, path: PathBuf, val: impl AsRef<[u8]>) -> Result<()> {
+    let val = val.as_ref();
+    backend.write(&path, val).map_err(|e| {
+        Error::from(e)
+            .at(path)
+            .writing(String::from_utf8_lossy(val))
+    })
+}
+
+// Opens a cgroup file with the given options, recording the path in the error on failure.
+pub fn open(backend: &dyn Backend, path: PathBuf, options: &OpenOptions) -> Result<File> {
+    backend
+        .open(&path, options)
+        .map_err(|e| Error::from(e).at(path))
+}
@@ -11,11 +11,12 @@
 )]
 // Clippy's suggestion causes many compile error
 #![allow(clippy::string_lit_as_bytes)]
-#![doc(html_root_url = "https://docs.rs/controlgroup/0.3.0")]
+#![doc(html_root_url = "https://docs.rs/controlgroup/0.4.0")]
 
 //! Native Rust crate for cgroup operations.
 //!
-//! Currently this crate supports only cgroup v1 hierarchy, implemented in [`v1`] module.
+//! This crate supports cgroup v1 hierarchies, implemented in [`v1`] module, and cgroup v2 (unified)
+//! hierarchy, implemented in [`v2`] module.
 //!
 //! ## Examples for v1 hierarchy
 //!
@@ -192,16 +193,49 @@
 //! # }
 //! ```
 //!
+//! ## Optional features
+//!
+//! - `serde`: Implements `Serialize` and `Deserialize` of [serde] for resource limits and
+//!   statistics types, such as [`v1::Resources`] and [`v1::memory::Stat`]. Types that implement
+//!   `FromStr` and `Display` (e.g. [`v1::cpuset::IdSet`], [`Device`], [`Max`]) are
+//!   (de)serialized in their string forms as in cgroup files (e.g. `"0-3,7"`, `"8:0"`, `"max"`).
+//! - `tokio`: Provides asynchronous handlers [`v1::AsyncCgroup`] and [`v1::AsyncUnifiedRepr`]
+//!   for [Tokio] runtime, whose operations are executed on threads for blocking operations, and
+//!   [`v1::AsyncEventListener`] that waits for eventfd notifications (e.g. OOM) on the reactor.
+//!   Note that this feature requires a newer Rust than the MSRV of this crate, as Tokio does.
+//! - `cli`: Builds `cgctl` binary, a command-line interface to operations of [`v1::UnifiedRepr`]
+//!   such as creating cgroups, applying resource limits, and executing a program in cgroups. Run
+//!   `cgctl help` for usage.
+//! - `oci`: Provides [`v1::oci`] module, which converts `linux.resources` of the OCI runtime
+//!   specification (e.g. read from `config.json` of a bundle) into [`v1::Resources`] and back.
+//!
 //! [`v1`]: v1/index.html
+//! [`v2`]: v2/index.html
 //! [`v1::Builder`]: v1/builder/struct.Builder.html
+//! [`v1::oci`]: v1/oci/index.html
 //! [`v1::CommandExt`]: v1/trait.CommandExt.html
 //! [`std::process::Command`]: https://doc.rust-lang.org/std/process/struct.Command.html
+//!
+//! [serde]: https://serde.rs
+//! [`v1::Resources`]: v1/struct.Resources.html
+//! [`v1::memory::Stat`]: v1/memory/struct.Stat.html
+//! [`v1::cpuset::IdSet`]: v1/cpuset/struct.IdSet.html
+//! [`Device`]: struct.Device.html
+//! [`Max`]: enum.Max.html
+//!
+//! [Tokio]: https://tokio.rs
+//! [`v1::UnifiedRepr`]: v1/struct.UnifiedRepr.html
+//! [`v1::AsyncCgroup`]: v1/struct.AsyncCgroup.html
+//! [`v1::AsyncUnifiedRepr`]: v1/struct.AsyncUnifiedRepr.html
+//! [`v1::AsyncEventListener`]: v1/struct.AsyncEventListener.html
 
 #[macro_use]
 mod macros;
 mod error;
+mod file;
 mod parse;
 pub mod v1;
+pub mod v2;
 
 use std::{
     fmt::{self, Display},
@@ -226,6 +260,11 @@ pub use error::{Error, ErrorKind, Result};
 /// [`u32`]: https://doc.rust-lang.org/std/primitive.u32.html
 /// [`&std::process::Child`]: https://doc.rust-lang.org/std/process/struct.Child.html
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct Pid(u32); // Max PID is 2^15 on 32-bit systems, 2^22 on 64-bit systems
                      // FIXME: ^ also true for thread IDs?
 
@@ -302,6 +341,7 @@ pub enum Max {
     Limit(u32), // only `u32` is used for the integer type of `Max` in this crate
 }
 
+#[allow(clippy::derivable_impls)] // `#[default]` on enum variants requires Rust 1.62
 impl Default for Max {
     fn default() -> Self {
         Self::Max
@@ -500,6 +540,9 @@ impl Display for DeviceNumber {
     }
 }
 
+#[cfg(feature = "serde")]
+impl_serde_str!(Max, Device, DeviceNumber);
+
 /// Yields a pair of a references, each of which points to a key and a value.
 ///
 /// This trait is used to convert a reference to a pair `&(K, V)` into a pair of references
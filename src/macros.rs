@@ -20,6 +20,33 @@ macro_rules! bail_parse {
     };
 }
 
+// Implements `Serialize` and `Deserialize` for types with `Display` and `FromStr`, so that the types
+// are (de)serialized in the same string form as in cgroup files.
+#[cfg(feature = "serde")]
+macro_rules! impl_serde_str {
+    ($( $ty: ty ),* $(, )?) => { $(
+        impl serde::Serialize for $ty {
+            fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $ty {
+            fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                String::deserialize(deserializer)?
+                    .parse()
+                    .map_err(serde::de::Error::custom)
+            }
+        }
+    )* };
+}
+
 #[cfg(test)]
 macro_rules! gen_cgroup_name {
     () => {
@@ -56,7 +83,61 @@ mod tests {
     fn test_gen_cgroup_name() {
         assert_eq!(
             gen_cgroup_name!(),
-            std::path::PathBuf::from("controlgroup_rs-macros-58")
+            std::path::PathBuf::from("controlgroup_rs-macros-85")
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_impl_serde_str() {
+        use crate::{v1, Max};
+
+        let mut resources = v1::Resources::default();
+        resources.cpuset.cpus = Some([0, 1, 2, 3, 7].iter().copied().collect());
+        resources.pids.max = Some(Max::Max);
+        resources.blkio.read_bps_device = hashmap! { ([8, 0].into(), 1000) };
+        resources.hugetlb.limits = hashmap! {
+            (v1::hugetlb::HugepageSize::Mb2, v1::hugetlb::Limit::Pages(4)),
+        };
+
+        let json = serde_json::to_value(&resources).unwrap();
+        assert_eq!(json["cpuset"]["cpus"], "0-3,7");
+        assert_eq!(json["pids"]["max"], "max");
+        assert_eq!(json["blkio"]["read_bps_device"]["8:0"], 1000);
+        assert_eq!(json["hugetlb"]["limits"]["2MB"]["Pages"], 4);
+
+        assert_eq!(
+            serde_json::from_value::<v1::Resources>(json).unwrap(),
+            resources
+        );
+        assert!(serde_json::from_str::<Max>("\"foo\"").is_err());
+        assert!(serde_json::from_str::<v1::hugetlb::HugepageSize>("\"Mb2\"").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_default() {
+        use crate::{v1, v2};
+
+        let resources =
+            serde_json::from_str::<v1::Resources>(r#"{"memory":{"limit_in_bytes":1000}}"#).unwrap();
+        let mut expected = v1::Resources::default();
+        expected.memory.limit_in_bytes = Some(1000);
+        assert_eq!(resources, expected);
+
+        let json = serde_json::to_string(&resources).unwrap();
+        assert_eq!(
+            serde_json::from_str::<v1::Resources>(&json).unwrap(),
+            resources
+        );
+
+        assert_eq!(
+            serde_json::from_str::<v1::Resources>("{}").unwrap(),
+            v1::Resources::default()
+        );
+        assert_eq!(
+            serde_json::from_str::<v2::Resources>(r#"{"pids":{}}"#).unwrap(),
+            v2::Resources::default()
         );
     }
 
@@ -92,7 +92,10 @@ mod tests {
 
     #[test]
     fn test_parse_vec() {
-        assert_eq!(parse_vec::<i32, _>("".as_bytes()).unwrap(), vec![]);
+        assert_eq!(
+            parse_vec::<i32, _>("".as_bytes()).unwrap(),
+            Vec::<i32>::new()
+        );
         assert_eq!(parse_vec::<i32, _>("0".as_bytes()).unwrap(), vec![0]);
         assert_eq!(
             parse_vec::<i32, _>("0 1 2 3".as_bytes()).unwrap(),
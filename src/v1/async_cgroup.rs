@@ -0,0 +1,579 @@
+use std::{
+    collections::HashMap,
+    io,
+    os::unix::io::AsRawFd,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use tokio::{io::unix::AsyncFd, task};
+
+use crate::{
+    v1::{
+        self,
+        memory::{self, PressureLevel, PressureMode},
+        Cgroup, DeleteOptions, EventListener, SubsystemKind, UnifiedRepr,
+    },
+    Error, ErrorKind, Pid, Result,
+};
+
+/// Asynchronous handler of a cgroup, available with `tokio` feature.
+///
+/// Cgroup file systems do not support non-blocking I/O, so each operation of `AsyncCgroup` is
+/// executed on a thread for blocking operations by [`tokio::task::spawn_blocking`], and the
+/// returned future resolves when the operation completes. The wrapped cgroup is shared among
+/// clones of an `AsyncCgroup`, and operations on it are serialized.
+///
+/// The common operations in [`Cgroup`] trait are provided as `async fn`s. Other operations, such
+/// as subsystem-specific getters and setters, can be executed via [`run`] method.
+///
+/// Note that methods of this type must be called within a Tokio runtime.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn f() -> controlgroup::Result<()> {
+/// use std::path::PathBuf;
+/// use controlgroup::v1::{cpu, AsyncCgroup, Cgroup, CgroupPath, Resources, SubsystemKind};
+///
+/// let cgroup = AsyncCgroup::new(cpu::Subsystem::new(
+///     CgroupPath::new(SubsystemKind::Cpu, PathBuf::from("students/charlie"))));
+/// cgroup.create().await?;
+///
+/// let mut resources = Resources::default();
+/// resources.cpu.shares = Some(1000);
+/// cgroup.apply(resources).await?;
+///
+/// let shares = cgroup.run(|c| c.shares()).await?;
+/// assert_eq!(shares, 1000);
+///
+/// cgroup.delete().await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`tokio::task::spawn_blocking`]: https://docs.rs/tokio/1/tokio/task/fn.spawn_blocking.html
+/// [`Cgroup`]: trait.Cgroup.html
+/// [`run`]: #method.run
+#[derive(Debug)]
+pub struct AsyncCgroup<C> {
+    cgroup: Shared<C>,
+}
+
+/// Asynchronous handler of a set of cgroups in [`UnifiedRepr`], available with `tokio` feature.
+///
+/// As [`AsyncCgroup`], each operation is executed on a thread for blocking operations, and the
+/// wrapped `UnifiedRepr` is shared among clones. Listeners of memory events can be registered
+/// by `notify_*` methods, as [`AsyncCgroup<memory::Subsystem>`]. Other operations can be executed
+/// via [`run`] method.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn f() -> controlgroup::Result<()> {
+/// use std::path::PathBuf;
+/// use controlgroup::v1::{AsyncUnifiedRepr, Resources, UnifiedRepr};
+///
+/// let cgroups = AsyncUnifiedRepr::new(UnifiedRepr::new(PathBuf::from("students/charlie")));
+/// cgroups.create().await?;
+///
+/// let mut resources = Resources::default();
+/// resources.pids.max = Some(42.into());
+/// cgroups.apply(resources).await?;
+///
+/// let max = cgroups.run(|c| c.pids().unwrap().max()).await?;
+/// assert_eq!(max, 42.into());
+///
+/// cgroups.delete().await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`UnifiedRepr`]: struct.UnifiedRepr.html
+/// [`AsyncCgroup`]: struct.AsyncCgroup.html
+/// [`AsyncCgroup<memory::Subsystem>`]: struct.AsyncCgroup.html#method.notify_oom
+/// [`run`]: #method.run
+#[derive(Debug, Clone)]
+pub struct AsyncUnifiedRepr {
+    repr: Shared<UnifiedRepr>,
+}
+
+/// Asynchronous listener of events notified by a cgroup through an eventfd, available with
+/// `tokio` feature.
+///
+/// This is an asynchronous version of [`EventListener`]. The eventfd is registered to the reactor
+/// of the Tokio runtime, so waiting for an event does not occupy any thread.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn f() -> controlgroup::Result<()> {
+/// use std::path::PathBuf;
+/// use controlgroup::v1::{memory, AsyncCgroup, Cgroup, CgroupPath, SubsystemKind};
+///
+/// let cgroup = AsyncCgroup::new(memory::Subsystem::new(
+///     CgroupPath::new(SubsystemKind::Memory, PathBuf::from("students/charlie"))));
+/// cgroup.create().await?;
+///
+/// let listener = cgroup.notify_oom().await?;
+///
+/// // Waits until the cgroup hits OOM.
+/// let count = listener.wait().await?;
+/// println!("OOM occurred {} time(s)", count);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`EventListener`]: struct.EventListener.html
+#[derive(Debug)]
+pub struct AsyncEventListener {
+    eventfd: AsyncFd<EventListener>,
+}
+
+#[derive(Debug)]
+struct Shared<T>(Arc<Mutex<T>>);
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T: Send + 'static> Shared<T> {
+    fn new(inner: T) -> Self {
+        Self(Arc::new(Mutex::new(inner)))
+    }
+
+    async fn run<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut T) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let inner = Arc::clone(&self.0);
+        let join = task::spawn_blocking(move || {
+            // A panic in a previous operation does not break the invariant of a handler.
+            let mut inner = inner.lock().unwrap_or_else(PoisonError::into_inner);
+            f(&mut inner)
+        });
+
+        match join.await {
+            Ok(result) => result,
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(e) => Err(Error::with_source(ErrorKind::Io, e)),
+        }
+    }
+}
+
+impl<C> Clone for AsyncCgroup<C> {
+    fn clone(&self) -> Self {
+        Self {
+            cgroup: self.cgroup.clone(),
+        }
+    }
+}
+
+macro_rules! gen_async {
+    ($field: ident, $method: ident $( , $arg: ident: $ty: ty )* => $ret: ty, $link: literal) => {
+        with_doc! { concat!(
+            "Executes [`", stringify!($method), "`] asynchronously.\n\n",
+            "[`", stringify!($method), "`]: ", $link, stringify!($method)),
+            pub async fn $method(&self $( , $arg: $ty )*) -> Result<$ret> {
+                self.$field.run(move |c| c.$method($( $arg ),*)).await
+            }
+        }
+    };
+}
+
+macro_rules! gen_async_common {
+    ($field: ident, $link: literal, $tasks: ty) => {
+        gen_async!($field, create => (), $link);
+
+        with_doc! { concat!("Executes [`apply`] asynchronously.\n\n[`apply`]: ", $link, "apply"),
+            pub async fn apply(&self, resources: v1::Resources) -> Result<()> {
+                self.$field.run(move |c| c.apply(&resources)).await
+            }
+        }
+
+        gen_async!($field, resources => v1::Resources, $link);
+        gen_async!($field, delete => (), $link);
+
+        with_doc! { concat!(
+            "Executes [`delete_recursive`] asynchronously.\n\n",
+            "[`delete_recursive`]: ", $link, "delete_recursive"),
+            pub async fn delete_recursive(&self, options: DeleteOptions) -> Result<()> {
+                self.$field.run(move |c| c.delete_recursive(&options)).await
+            }
+        }
+
+        gen_async!($field, tasks => $tasks, $link);
+        gen_async!($field, add_task, pid: Pid => (), $link);
+        gen_async!($field, remove_task, pid: Pid => (), $link);
+        gen_async!($field, procs => $tasks, $link);
+        gen_async!($field, add_proc, pid: Pid => (), $link);
+        gen_async!($field, remove_proc, pid: Pid => (), $link);
+    };
+}
+
+impl<C: Cgroup + Send + 'static> AsyncCgroup<C> {
+    /// Wraps a cgroup handler into an asynchronous one.
+    pub fn new(cgroup: C) -> Self {
+        Self {
+            cgroup: Shared::new(cgroup),
+        }
+    }
+
+    /// Executes `f` with the wrapped cgroup on a thread for blocking operations, and returns the
+    /// result.
+    ///
+    /// This is useful to execute subsystem-specific operations, e.g. getters and setters,
+    /// asynchronously.
+    ///
+    /// # Panics
+    ///
+    /// Resumes the panic if `f` panics.
+    pub async fn run<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut C) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        self.cgroup.run(f).await
+    }
+
+    gen_async_common!(cgroup, "trait.Cgroup.html#method.", Vec<Pid>);
+}
+
+impl AsyncCgroup<memory::Subsystem> {
+    /// Registers an asynchronous listener of OOM events. See [`memory::Subsystem::notify_oom`].
+    ///
+    /// [`memory::Subsystem::notify_oom`]: memory/struct.Subsystem.html#method.notify_oom
+    pub async fn notify_oom(&self) -> Result<AsyncEventListener> {
+        let listener = self.run(|c| c.notify_oom()).await?;
+        AsyncEventListener::new(listener)
+    }
+
+    /// Registers an asynchronous listener of memory pressure level notifications. See
+    /// [`memory::Subsystem::notify_pressure_level`].
+    ///
+    /// [`memory::Subsystem::notify_pressure_level`]: memory/struct.Subsystem.html#method.notify_pressure_level
+    pub async fn notify_pressure_level(
+        &self,
+        level: PressureLevel,
+        mode: PressureMode,
+    ) -> Result<AsyncEventListener> {
+        let listener = self
+            .run(move |c| c.notify_pressure_level(level, mode))
+            .await?;
+        AsyncEventListener::new(listener)
+    }
+
+    /// Registers an asynchronous listener of memory usage threshold crossing. See
+    /// [`memory::Subsystem::notify_usage_threshold`].
+    ///
+    /// [`memory::Subsystem::notify_usage_threshold`]: memory/struct.Subsystem.html#method.notify_usage_threshold
+    pub async fn notify_usage_threshold(&self, threshold: u64) -> Result<AsyncEventListener> {
+        let listener = self
+            .run(move |c| c.notify_usage_threshold(threshold))
+            .await?;
+        AsyncEventListener::new(listener)
+    }
+
+    /// Registers an asynchronous listener of memory and swap usage threshold crossing. See
+    /// [`memory::Subsystem::notify_memsw_usage_threshold`].
+    ///
+    /// [`memory::Subsystem::notify_memsw_usage_threshold`]: memory/struct.Subsystem.html#method.notify_memsw_usage_threshold
+    pub async fn notify_memsw_usage_threshold(&self, threshold: u64) -> Result<AsyncEventListener> {
+        let listener = self
+            .run(move |c| c.notify_memsw_usage_threshold(threshold))
+            .await?;
+        AsyncEventListener::new(listener)
+    }
+}
+
+impl AsyncUnifiedRepr {
+    /// Wraps a `UnifiedRepr` into an asynchronous one.
+    pub fn new(repr: UnifiedRepr) -> Self {
+        Self {
+            repr: Shared::new(repr),
+        }
+    }
+
+    /// Executes `f` with the wrapped `UnifiedRepr` on a thread for blocking operations, and
+    /// returns the result.
+    ///
+    /// This is useful to execute subsystem-specific operations, e.g. getters and setters,
+    /// asynchronously.
+    ///
+    /// # Panics
+    ///
+    /// Resumes the panic if `f` panics.
+    pub async fn run<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut UnifiedRepr) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        self.repr.run(f).await
+    }
+
+    gen_async_common!(
+        repr,
+        "struct.UnifiedRepr.html#method.",
+        HashMap<SubsystemKind, Vec<Pid>>
+    );
+
+    /// Registers an asynchronous listener of OOM events of the memory subsystem. See
+    /// [`memory::Subsystem::notify_oom`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`ErrorKind::InvalidOperation`] if the memory subsystem is not
+    /// included in the wrapped `UnifiedRepr`. See also [`memory::Subsystem::notify_oom`].
+    ///
+    /// [`memory::Subsystem::notify_oom`]: memory/struct.Subsystem.html#method.notify_oom
+    /// [`ErrorKind::InvalidOperation`]: ../enum.ErrorKind.html#variant.InvalidOperation
+    pub async fn notify_oom(&self) -> Result<AsyncEventListener> {
+        self.notify(|m| m.notify_oom()).await
+    }
+
+    /// Registers an asynchronous listener of memory pressure level notifications of the memory
+    /// subsystem. See [`memory::Subsystem::notify_pressure_level`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`ErrorKind::InvalidOperation`] if the memory subsystem is not
+    /// included in the wrapped `UnifiedRepr`. See also
+    /// [`memory::Subsystem::notify_pressure_level`].
+    ///
+    /// [`memory::Subsystem::notify_pressure_level`]: memory/struct.Subsystem.html#method.notify_pressure_level
+    /// [`ErrorKind::InvalidOperation`]: ../enum.ErrorKind.html#variant.InvalidOperation
+    pub async fn notify_pressure_level(
+        &self,
+        level: PressureLevel,
+        mode: PressureMode,
+    ) -> Result<AsyncEventListener> {
+        self.notify(move |m| m.notify_pressure_level(level, mode))
+            .await
+    }
+
+    /// Registers an asynchronous listener of memory usage threshold crossing of the memory
+    /// subsystem. See [`memory::Subsystem::notify_usage_threshold`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`ErrorKind::InvalidOperation`] if the memory subsystem is not
+    /// included in the wrapped `UnifiedRepr`. See also
+    /// [`memory::Subsystem::notify_usage_threshold`].
+    ///
+    /// [`memory::Subsystem::notify_usage_threshold`]: memory/struct.Subsystem.html#method.notify_usage_threshold
+    /// [`ErrorKind::InvalidOperation`]: ../enum.ErrorKind.html#variant.InvalidOperation
+    pub async fn notify_usage_threshold(&self, threshold: u64) -> Result<AsyncEventListener> {
+        self.notify(move |m| m.notify_usage_threshold(threshold))
+            .await
+    }
+
+    /// Registers an asynchronous listener of memory and swap usage threshold crossing of the
+    /// memory subsystem. See [`memory::Subsystem::notify_memsw_usage_threshold`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`ErrorKind::InvalidOperation`] if the memory subsystem is not
+    /// included in the wrapped `UnifiedRepr`. See also
+    /// [`memory::Subsystem::notify_memsw_usage_threshold`].
+    ///
+    /// [`memory::Subsystem::notify_memsw_usage_threshold`]: memory/struct.Subsystem.html#method.notify_memsw_usage_threshold
+    /// [`ErrorKind::InvalidOperation`]: ../enum.ErrorKind.html#variant.InvalidOperation
+    pub async fn notify_memsw_usage_threshold(&self, threshold: u64) -> Result<AsyncEventListener> {
+        self.notify(move |m| m.notify_memsw_usage_threshold(threshold))
+            .await
+    }
+
+    async fn notify<F>(&self, f: F) -> Result<AsyncEventListener>
+    where
+        F: FnOnce(&mut memory::Subsystem) -> Result<EventListener> + Send + 'static,
+    {
+        let listener = self
+            .run(|c| match c.memory_mut() {
+                Some(memory) => f(memory),
+                None => Err(Error::new(ErrorKind::InvalidOperation)),
+            })
+            .await?;
+        AsyncEventListener::new(listener)
+    }
+}
+
+impl AsyncEventListener {
+    /// Converts a blocking [`EventListener`] into an asynchronous one.
+    ///
+    /// The eventfd of `listener` is made non-blocking, so the listener returned by [`get_ref`] no
+    /// longer blocks in [`EventListener::wait`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to make the eventfd non-blocking or to register it to the
+    /// reactor, with kind [`ErrorKind::Io`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside a Tokio runtime with I/O enabled.
+    ///
+    /// [`EventListener`]: struct.EventListener.html
+    /// [`get_ref`]: #method.get_ref
+    /// [`EventListener::wait`]: struct.EventListener.html#method.wait
+    /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
+    pub fn new(listener: EventListener) -> Result<Self> {
+        let fd = listener.as_raw_fd();
+
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+        if flags == -1 || unsafe { libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) } == -1
+        {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        Ok(Self {
+            eventfd: AsyncFd::new(listener)?,
+        })
+    }
+
+    /// Waits until an event is notified, and returns the number of events notified since the
+    /// last call to `wait`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to read the eventfd, with kind [`ErrorKind::Io`].
+    ///
+    /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
+    pub async fn wait(&self) -> Result<u64> {
+        loop {
+            let mut guard = self.eventfd.readable().await?;
+            match guard.try_io(|eventfd| eventfd.get_ref().read()) {
+                Ok(result) => return Ok(result?),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Returns the underlying [`EventListener`].
+    ///
+    /// Its eventfd has been made non-blocking by [`new`], so [`EventListener::wait`] on it returns
+    /// an error with kind [`ErrorKind::Io`] (`io::ErrorKind::WouldBlock`) instead of blocking if no
+    /// event has been notified. Use [`wait`] or [`EventListener::try_wait`] instead.
+    ///
+    /// [`EventListener`]: struct.EventListener.html
+    /// [`new`]: #method.new
+    /// [`wait`]: #method.wait
+    /// [`EventListener::wait`]: struct.EventListener.html#method.wait
+    /// [`EventListener::try_wait`]: struct.EventListener.html#method.try_wait
+    /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
+    pub fn get_ref(&self) -> &EventListener {
+        self.eventfd.get_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::{cpu, CgroupPath};
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_async_cgroup() -> Result<()> {
+        block_on(async {
+            let cgroup = AsyncCgroup::new(cpu::Subsystem::new(CgroupPath::new(
+                SubsystemKind::Cpu,
+                gen_cgroup_name!(),
+            )));
+            cgroup.create().await?;
+
+            let mut resources = v1::Resources::default();
+            resources.cpu.shares = Some(1000);
+            cgroup.apply(resources).await?;
+
+            assert_eq!(cgroup.clone().run(|c| c.shares()).await?, 1000);
+            assert_eq!(cgroup.resources().await?.cpu.shares, Some(1000));
+            assert!(cgroup.tasks().await?.is_empty());
+
+            cgroup.delete().await
+        })
+    }
+
+    #[test]
+    fn test_async_unified_repr() -> Result<()> {
+        block_on(async {
+            let cgroups = AsyncUnifiedRepr::new(UnifiedRepr::with_subsystems(
+                gen_cgroup_name!(),
+                &[SubsystemKind::Cpu, SubsystemKind::Pids],
+            ));
+            cgroups.create().await?;
+
+            let mut resources = v1::Resources::default();
+            resources.pids.max = Some(42.into());
+            cgroups.apply(resources).await?;
+
+            assert_eq!(cgroups.run(|c| c.pids().unwrap().max()).await?, 42.into());
+            assert_eq!(cgroups.procs().await?.len(), 2);
+
+            cgroups.delete().await
+        })
+    }
+
+    #[test]
+    fn test_async_unified_repr_notify() -> Result<()> {
+        block_on(async {
+            let name = gen_cgroup_name!();
+
+            let cgroups = AsyncUnifiedRepr::new(UnifiedRepr::with_subsystems(
+                name.clone(),
+                &[SubsystemKind::Memory],
+            ));
+            cgroups.create().await?;
+
+            let listener = cgroups.notify_oom().await?;
+            assert_eq!(listener.get_ref().try_wait()?, None);
+            drop(listener);
+
+            let listener = cgroups.notify_usage_threshold(1 << 30).await?;
+            drop(listener);
+            cgroups.delete().await?;
+
+            let cgroups =
+                AsyncUnifiedRepr::new(UnifiedRepr::with_subsystems(name, &[SubsystemKind::Cpu]));
+            assert_eq!(
+                cgroups.notify_oom().await.unwrap_err().kind(),
+                ErrorKind::InvalidOperation
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_async_event_listener() -> Result<()> {
+        block_on(async {
+            let cgroup = AsyncCgroup::new(memory::Subsystem::new(CgroupPath::new(
+                SubsystemKind::Memory,
+                gen_cgroup_name!(),
+            )));
+            cgroup.create().await?;
+
+            let listener = cgroup.notify_oom().await?;
+
+            // notify an event manually
+            let buf = 3_u64.to_ne_bytes();
+            let fd = listener.get_ref().as_raw_fd();
+            assert_eq!(unsafe { libc::write(fd, buf.as_ptr() as *const _, 8) }, 8);
+
+            assert_eq!(listener.wait().await?, 3);
+            assert_eq!(listener.get_ref().try_wait()?, None);
+            // the underlying listener does not block
+            assert_eq!(listener.get_ref().wait().unwrap_err().kind(), ErrorKind::Io);
+
+            drop(listener);
+            cgroup.delete().await
+        })
+    }
+}
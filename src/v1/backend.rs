@@ -0,0 +1,830 @@
+//! Backends through which cgroup files are accessed.
+//!
+//! By default, cgroup handlers access the real cgroup file system via [`Sysfs`] backend. A custom
+//! [`Backend`] can be attached to a cgroup by [`CgroupPath::with_backend`]. All operations on the
+//! cgroup, and on the cgroups derived from it (e.g. its parent and children), then go through the
+//! backend. For a set of cgroups, use [`UnifiedRepr::with_backend`] or [`Builder::backend`].
+//!
+//! [`Fake`] backend emulates a cgroup file system in a directory of a normal file system, so that
+//! code using this crate can be tested without root privilege or a real kernel.
+//!
+//! # Examples
+//!
+//! ```
+//! # fn main() -> controlgroup::Result<()> {
+//! use std::{path::PathBuf, sync::Arc};
+//! use controlgroup::v1::{backend::Fake, cpu, Cgroup, CgroupPath, SubsystemKind};
+//!
+//! let root = std::env::temp_dir().join("controlgroup_backend_doc");
+//! let fake = Arc::new(Fake::new(root.clone())?);
+//! fake.mount(SubsystemKind::Cpu)?;
+//!
+//! let mut cgroup = cpu::Subsystem::new(
+//!     CgroupPath::new(SubsystemKind::Cpu, PathBuf::from("students/charlie"))
+//!         .with_backend(fake));
+//! assert!(cgroup.create().is_err()); // `students` does not exist
+//!
+//! cgroup.parent().unwrap().create()?;
+//! cgroup.create()?;
+//!
+//! cgroup.set_shares(1000)?;
+//! assert_eq!(cgroup.shares()?, 1000);
+//!
+//! cgroup.delete()?;
+//! cgroup.parent().unwrap().delete()?;
+//! # std::fs::remove_dir_all(root)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Sysfs`]: struct.Sysfs.html
+//! [`Backend`]: trait.Backend.html
+//! [`Fake`]: struct.Fake.html
+//! [`CgroupPath::with_backend`]: ../struct.CgroupPath.html#method.with_backend
+//! [`UnifiedRepr::with_backend`]: ../struct.UnifiedRepr.html#method.with_backend
+//! [`Builder::backend`]: ../struct.Builder.html#method.backend
+
+use std::{
+    ffi::OsString,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use crate::{
+    v1::{self, SubsystemKind},
+    Result,
+};
+
+/// File system operations used by cgroup handlers.
+///
+/// All paths passed to a backend are absolute paths in the cgroup file system, e.g.
+/// `/sys/fs/cgroup/cpu/students/charlie/cpu.shares`. Errors should be reported with the same
+/// `errno` as the kernel does, e.g. `EBUSY` for removing a cgroup that has tasks.
+pub trait Backend: fmt::Debug + Send + Sync {
+    /// Opens a file with the given options.
+    fn open(&self, path: &Path, options: &OpenOptions) -> io::Result<File>;
+
+    /// Writes `contents` to a file by a single write, replacing the previous content.
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Creates a cgroup directory.
+    fn create_dir(&self, path: &Path) -> io::Result<()>;
+
+    /// Removes a cgroup directory.
+    fn remove_dir(&self, path: &Path) -> io::Result<()>;
+
+    /// Returns the names of subdirectories, i.e. child cgroups, of a directory in arbitrary order.
+    fn list_dirs(&self, path: &Path) -> io::Result<Vec<OsString>>;
+
+    /// Returns whether a file or directory exists.
+    fn exists(&self, path: &Path) -> bool;
+}
+
+/// Backend accessing the real cgroup file system. This is the default backend.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Sysfs;
+
+impl Backend for Sysfs {
+    fn open(&self, path: &Path, options: &OpenOptions) -> io::Result<File> {
+        options.open(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(path, contents)
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir(path)
+    }
+
+    fn list_dirs(&self, path: &Path) -> io::Result<Vec<OsString>> {
+        let mut dirs = Vec::new();
+
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                dirs.push(entry.file_name());
+            }
+        }
+
+        Ok(dirs)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+}
+
+/// Backend emulating a cgroup file system in a directory.
+///
+/// A path in the cgroup file system is mapped into the directory, e.g.
+/// `/sys/fs/cgroup/cpu/students/cpu.shares` to `<root>/sys/fs/cgroup/cpu/students/cpu.shares`.
+/// Subsystems are mounted by [`mount`], and cgroups created by [`Backend::create_dir`] are
+/// populated with the files of the subsystem with their default values.
+///
+/// The following kernel semantics are emulated on writes:
+///
+/// - Writing a PID to `tasks` or `cgroup.procs` file moves the task out of all other cgroups in
+///   the hierarchy. Threads and processes are not distinguished, and PID 0 denotes the current
+///   process.
+/// - Writing to `devices.allow` or `devices.deny` file updates `devices.list` file. As the kernel
+///   does, specific entries are not listed while the cgroup allows all device access by default.
+/// - Writing `FROZEN` or `THAWED` to `freezer.state` file updates `freezer.self_freezing` file,
+///   and `freezer.parent_freezing` and `freezer.state` files of the cgroup and its descendants.
+///   Tasks are frozen immediately, i.e. a cgroup is never in `FREEZING` state.
+/// - Removing a cgroup that has child cgroups or tasks fails with `EBUSY`.
+///
+/// Writing to any other file just replaces its content without any validation. Statistics files
+/// can be set up by [`set_contents`]. Note that writes through a file opened by
+/// [`Cgroup::open_file_write`] bypass the emulation.
+///
+/// The directory is not removed when a `Fake` is dropped.
+///
+/// [`mount`]: #method.mount
+/// [`set_contents`]: #method.set_contents
+/// [`Backend::create_dir`]: trait.Backend.html#tymethod.create_dir
+/// [`Cgroup::open_file_write`]: ../trait.Cgroup.html#method.open_file_write
+#[derive(Debug)]
+pub struct Fake {
+    root: PathBuf,
+    mounts: Mutex<Vec<PathBuf>>, // also serializes emulated operations
+}
+
+impl Fake {
+    /// Creates a new `Fake` backend emulating a cgroup file system in `root` directory, creating
+    /// the directory if it does not exist. No subsystem is mounted yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to create the directory, with kind [`ErrorKind::Io`].
+    ///
+    /// [`ErrorKind::Io`]: ../../enum.ErrorKind.html#variant.Io
+    pub fn new(root: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&root)?;
+        Ok(Self {
+            root,
+            mounts: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Returns the directory in which the cgroup file system is emulated.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Mounts a subsystem at the standard location, e.g. `/sys/fs/cgroup/cpu` for
+    /// `SubsystemKind::Cpu`, and populates the root cgroup with the files of the subsystem.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`ErrorKind::Io`] if the subsystem is already mounted, or if
+    /// failed to create the root cgroup.
+    ///
+    /// [`ErrorKind::Io`]: ../../enum.ErrorKind.html#variant.Io
+    pub fn mount(&self, kind: SubsystemKind) -> Result<()> {
+        let mut mounts = self.lock();
+
+        let mount_point = Path::new(v1::CGROUPFS_MOUNT_POINT).join(kind.as_ref());
+        if mounts.contains(&mount_point) {
+            return Err(errno(libc::EBUSY).into());
+        }
+
+        let dir = self.real(&mount_point);
+        fs::create_dir_all(&dir)?;
+        populate(&dir, kind, None)?;
+
+        mounts.push(mount_point);
+        Ok(())
+    }
+
+    /// Replaces the content of a file without any emulation, creating the file if it does not
+    /// exist. This is useful to set up statistics files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to write to the file, with kind [`ErrorKind::Io`].
+    ///
+    /// [`ErrorKind::Io`]: ../../enum.ErrorKind.html#variant.Io
+    pub fn set_contents(&self, path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+        let _mounts = self.lock();
+        fs::write(self.real(path), contents).map_err(Into::into)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<PathBuf>> {
+        self.mounts.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn real(&self, path: &Path) -> PathBuf {
+        self.root.join(path.strip_prefix("/").unwrap_or(path))
+    }
+}
+
+impl Backend for Fake {
+    fn open(&self, path: &Path, options: &OpenOptions) -> io::Result<File> {
+        options.open(self.real(path))
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let mounts = self.lock();
+
+        let mut file = OpenOptions::new().write(true).open(self.real(path))?;
+        let dir = self.real(path.parent().unwrap_or(path));
+
+        match path.file_name().and_then(|n| n.to_str()) {
+            Some("tasks") | Some("cgroup.procs") => {
+                let mount_point = mounts
+                    .iter()
+                    .find(|m| path.starts_with(m))
+                    .ok_or_else(|| errno(libc::ENOENT))?;
+                move_task(&self.real(mount_point), &dir, contents)
+            }
+            Some("devices.allow") => update_devices(&dir, true, contents),
+            Some("devices.deny") => update_devices(&dir, false, contents),
+            Some("freezer.state") => {
+                let self_freezing = match contents_str(contents)? {
+                    "FROZEN" => "1\n",
+                    "THAWED" => "0\n",
+                    _ => return Err(errno(libc::EINVAL)),
+                };
+                fs::write(dir.join("freezer.self_freezing"), self_freezing)?;
+                refresh_freezer(&dir)
+            }
+            _ => {
+                file.set_len(0)?;
+                file.write_all(contents)
+            }
+        }
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        let mounts = self.lock();
+
+        let kind = mounts
+            .iter()
+            .find(|m| path.starts_with(m) && path != m.as_path())
+            .and_then(|m| m.file_name()?.to_str()?.parse::<SubsystemKind>().ok())
+            .ok_or_else(|| errno(libc::ENOENT))?;
+
+        let dir = self.real(path);
+        fs::create_dir(&dir)?;
+
+        let parent = dir.parent().unwrap_or(&dir);
+        if let Err(e) = populate(&dir, kind, Some(parent)) {
+            let _ = fs::remove_dir_all(&dir);
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        let mounts = self.lock();
+
+        if mounts.iter().any(|m| m == path) {
+            return Err(errno(libc::EBUSY));
+        }
+
+        let dir = self.real(path);
+        if !subdirs(&dir)?.is_empty() || !fs::read_to_string(dir.join("tasks"))?.trim().is_empty() {
+            return Err(errno(libc::EBUSY));
+        }
+
+        fs::remove_dir_all(dir)
+    }
+
+    fn list_dirs(&self, path: &Path) -> io::Result<Vec<OsString>> {
+        Sysfs.list_dirs(&self.real(path))
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.real(path).exists()
+    }
+}
+
+fn errno(code: i32) -> io::Error {
+    io::Error::from_raw_os_error(code)
+}
+
+fn contents_str(contents: &[u8]) -> io::Result<&str> {
+    std::str::from_utf8(contents)
+        .map(str::trim)
+        .map_err(|_| errno(libc::EINVAL))
+}
+
+fn subdirs(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    Ok(Sysfs
+        .list_dirs(dir)?
+        .into_iter()
+        .map(|name| dir.join(name))
+        .collect())
+}
+
+// Creates the files of a cgroup with their default values. Some values are inherited from the
+// parent cgroup.
+fn populate(dir: &Path, kind: SubsystemKind, parent: Option<&Path>) -> io::Result<()> {
+    use SubsystemKind::*;
+
+    let inherit = |name: &str, default: &str| match parent {
+        Some(parent) => fs::read_to_string(parent.join(name)),
+        None => Ok(default.to_string()),
+    };
+
+    let mut files = vec![
+        ("tasks", String::new()),
+        ("cgroup.procs", String::new()),
+        ("cgroup.clone_children", "0\n".to_string()),
+        ("notify_on_release", inherit("notify_on_release", "0\n")?),
+    ];
+    if parent.is_none() {
+        files.push(("release_agent", "\n".to_string()));
+        files.push(("cgroup.sane_behavior", "0\n".to_string()));
+    }
+
+    let defaults: &[(&str, &str)] = match kind {
+        Cpu => &[
+            ("cpu.shares", "1024\n"),
+            ("cpu.cfs_quota_us", "-1\n"),
+            ("cpu.cfs_period_us", "100000\n"),
+            ("cpu.rt_period_us", "1000000\n"),
+            ("cpu.rt_runtime_us", "0\n"),
+            (
+                "cpu.stat",
+                "nr_periods 0\nnr_throttled 0\nthrottled_time 0\n",
+            ),
+        ],
+        Cpuacct => &[
+            ("cpuacct.usage", "0\n"),
+            ("cpuacct.usage_user", "0\n"),
+            ("cpuacct.usage_sys", "0\n"),
+            ("cpuacct.stat", "user 0\nsystem 0\n"),
+        ],
+        Cpuset => &[
+            ("cpuset.cpus", "\n"),
+            ("cpuset.mems", "\n"),
+            ("cpuset.effective_cpus", "\n"),
+            ("cpuset.effective_mems", "\n"),
+            ("cpuset.cpu_exclusive", "0\n"),
+            ("cpuset.mem_exclusive", "0\n"),
+        ],
+        Memory => &[
+            ("memory.limit_in_bytes", "9223372036854771712\n"),
+            ("memory.soft_limit_in_bytes", "9223372036854771712\n"),
+            ("memory.usage_in_bytes", "0\n"),
+            ("memory.max_usage_in_bytes", "0\n"),
+            ("memory.failcnt", "0\n"),
+            ("memory.swappiness", "60\n"),
+            ("memory.use_hierarchy", "1\n"),
+            ("memory.move_charge_at_immigrate", "0\n"),
+            (
+                "memory.oom_control",
+                "oom_kill_disable 0\nunder_oom 0\noom_kill 0\n",
+            ),
+        ],
+        Pids => &[("pids.current", "0\n"), ("pids.events", "max 0\n")],
+        Devices => &[("devices.allow", ""), ("devices.deny", "")],
+        NetCls => &[("net_cls.classid", "0\n")],
+        _ => &[],
+    };
+    files.extend(defaults.iter().map(|&(name, val)| (name, val.to_string())));
+
+    match kind {
+        Pids if parent.is_some() => files.push(("pids.max", "max\n".to_string())),
+        Devices => files.push(("devices.list", inherit("devices.list", "a *:* rwm\n")?)),
+        Freezer => {
+            if let Some(parent) = parent {
+                let parent_freezing = freezing(parent)?;
+                files.push(("freezer.state", freezer_state(parent_freezing).to_string()));
+                files.push(("freezer.self_freezing", "0\n".to_string()));
+                files.push((
+                    "freezer.parent_freezing",
+                    format!("{}\n", parent_freezing as i32),
+                ));
+            }
+        }
+        _ => {}
+    }
+
+    for (name, val) in files {
+        fs::write(dir.join(name), val)?;
+    }
+
+    Ok(())
+}
+
+// Moves a task into the cgroup `dir`, removing it from all cgroups in the hierarchy.
+fn move_task(mount_point: &Path, dir: &Path, contents: &[u8]) -> io::Result<()> {
+    let pid = match contents_str(contents)?.parse::<u32>() {
+        Ok(0) => std::process::id(),
+        Ok(pid) => pid,
+        Err(_) => return Err(errno(libc::EINVAL)),
+    };
+
+    remove_task(mount_point, &pid.to_string())?;
+
+    for name in &["tasks", "cgroup.procs"] {
+        let mut file = OpenOptions::new().append(true).open(dir.join(name))?;
+        writeln!(file, "{}", pid)?;
+    }
+
+    Ok(())
+}
+
+fn remove_task(dir: &Path, pid: &str) -> io::Result<()> {
+    for name in &["tasks", "cgroup.procs"] {
+        let path = dir.join(name);
+        let tasks = fs::read_to_string(&path)?;
+        if tasks.lines().any(|t| t == pid) {
+            let tasks = tasks
+                .lines()
+                .filter(|&t| t != pid)
+                .map(|t| format!("{}\n", t))
+                .collect::<String>();
+            fs::write(path, tasks)?;
+        }
+    }
+
+    subdirs(dir)?
+        .into_iter()
+        .try_for_each(|child| remove_task(&child, pid))
+}
+
+// Updates `devices.list` of the cgroup `dir` on a write to `devices.allow` or `devices.deny`.
+fn update_devices(dir: &Path, allow: bool, contents: &[u8]) -> io::Result<()> {
+    const ALL: &str = "a *:*";
+
+    let mut entry = contents_str(contents)?.split_whitespace();
+    let device_type = entry.next().ok_or_else(|| errno(libc::EINVAL))?;
+    let device_number = entry.next().unwrap_or("*:*");
+    let access = entry.next().unwrap_or("rwm");
+
+    let valid = ["a", "b", "c"].contains(&device_type)
+        && device_number.contains(':')
+        && !access.is_empty()
+        && access.chars().all(|c| "rwm".contains(c))
+        && entry.next().is_none();
+    if !valid {
+        return Err(errno(libc::EINVAL));
+    }
+
+    let path = dir.join("devices.list");
+    let mut list = fs::read_to_string(&path)?
+        .lines()
+        .filter_map(|line| {
+            let sep = line.rfind(' ')?;
+            Some((line[..sep].to_string(), line[sep + 1..].to_string()))
+        })
+        .collect::<Vec<_>>();
+
+    if device_type == "a" {
+        list = if allow {
+            vec![(ALL.to_string(), "rwm".to_string())]
+        } else {
+            Vec::new()
+        };
+    } else if !list.iter().any(|(device, _)| device == ALL) {
+        let device = format!("{} {}", device_type, device_number);
+        let pos = list.iter().position(|(d, _)| *d == device);
+        let old = pos.map_or("", |i| list[i].1.as_str());
+
+        let new = "rwm"
+            .chars()
+            .filter(|&c| {
+                if allow {
+                    old.contains(c) || access.contains(c)
+                } else {
+                    old.contains(c) && !access.contains(c)
+                }
+            })
+            .collect::<String>();
+
+        match pos {
+            Some(i) if new.is_empty() => {
+                list.remove(i);
+            }
+            Some(i) => list[i].1 = new,
+            None if !new.is_empty() => list.push((device, new)),
+            None => {}
+        }
+    }
+
+    let list = list
+        .into_iter()
+        .map(|(device, access)| format!("{} {}\n", device, access))
+        .collect::<String>();
+    fs::write(path, list)
+}
+
+fn freezer_state(freezing: bool) -> &'static str {
+    if freezing {
+        "FROZEN\n"
+    } else {
+        "THAWED\n"
+    }
+}
+
+// Returns whether the cgroup `dir` is frozen, either by itself or by its ancestor. The root cgroup
+// is never frozen.
+fn freezing(dir: &Path) -> io::Result<bool> {
+    let flag = |name: &str| match fs::read_to_string(dir.join(name)) {
+        Ok(flag) => Ok(flag.trim() == "1"),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    };
+
+    Ok(flag("freezer.self_freezing")? || flag("freezer.parent_freezing")?)
+}
+
+// Recomputes `freezer.state` of the cgroup `dir`, and propagates it to the descendants.
+fn refresh_freezer(dir: &Path) -> io::Result<()> {
+    let freezing = freezing(dir)?;
+    fs::write(dir.join("freezer.state"), freezer_state(freezing))?;
+
+    for child in subdirs(dir)? {
+        fs::write(
+            child.join("freezer.parent_freezing"),
+            format!("{}\n", freezing as i32),
+        )?;
+        refresh_freezer(&child)?;
+    }
+
+    Ok(())
+}
+
+/// Test fixture of a `Fake` backend in a temporary directory, which is removed when dropped.
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct TempFake(std::sync::Arc<Fake>);
+
+#[cfg(test)]
+impl TempFake {
+    /// Creates a `Fake` in `name` directory under the temporary directory, removing leftovers of
+    /// a previous run, and mounts `kinds`.
+    pub(crate) fn new(name: impl AsRef<Path>, kinds: &[SubsystemKind]) -> Result<Self> {
+        let root = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&root);
+
+        let fake = Fake::new(root)?;
+        for &kind in kinds {
+            fake.mount(kind)?;
+        }
+        Ok(Self(std::sync::Arc::new(fake)))
+    }
+
+    /// Returns the shared `Fake`, to be attached to a `CgroupPath`.
+    pub(crate) fn backend(&self) -> std::sync::Arc<Fake> {
+        self.0.clone()
+    }
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TempFake {
+    type Target = Fake;
+
+    fn deref(&self) -> &Fake {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempFake {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(self.0.root());
+    }
+}
+
+/// Test backend on which a freezer cgroup gets stuck in `FREEZING` state, and optionally fails to
+/// be thawed.
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct StuckFreezer {
+    pub(crate) fake: std::sync::Arc<Fake>,
+    pub(crate) fail_thaw: bool,
+}
+
+#[cfg(test)]
+impl Backend for StuckFreezer {
+    fn open(&self, path: &Path, options: &OpenOptions) -> io::Result<File> {
+        self.fake.open(path, options)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let state = path.ends_with("freezer.state");
+        if state && self.fail_thaw && contents == b"THAWED" {
+            return Err(errno(libc::EIO));
+        }
+
+        self.fake.write(path, contents)?;
+        if state && contents == b"FROZEN" {
+            fs::write(self.fake.real(path), "FREEZING\n")?;
+        }
+        Ok(())
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        self.fake.create_dir(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        self.fake.remove_dir(path)
+    }
+
+    fn list_dirs(&self, path: &Path) -> io::Result<Vec<OsString>> {
+        self.fake.list_dirs(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.fake.exists(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::{cpu, devices, freezer, Cgroup, CgroupPath};
+
+    #[test]
+    fn test_fake_create_delete() -> Result<()> {
+        let fake = TempFake::new(gen_cgroup_name!(), &[SubsystemKind::Cpu])?;
+        assert_eq!(
+            fake.mount(SubsystemKind::Cpu).unwrap_err().errno(),
+            Some(libc::EBUSY)
+        );
+
+        let path = CgroupPath::new(SubsystemKind::Cpu, PathBuf::from("a/b"));
+        let mut child = cpu::Subsystem::new(path.with_backend(fake.backend()));
+        let mut parent = *child.parent().unwrap();
+        let root = child.root_cgroup();
+
+        assert!(child.create().unwrap_err().is_not_found());
+        parent.create()?;
+        child.create()?;
+        assert!(child.file_exists("cpu.shares"));
+        assert!(!child.file_exists("release_agent"));
+        assert!(root.file_exists("release_agent"));
+        assert!(fake.root().join("sys/fs/cgroup/cpu/a/b").is_dir());
+
+        assert_eq!(child.shares()?, 1024);
+        child.set_shares(1000)?;
+        assert_eq!(child.shares()?, 1000);
+        assert_eq!(child.stat()?.nr_periods, 0);
+
+        let children = root.children()?;
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].cgroup_path(), parent.cgroup_path());
+        assert!(parent.delete().unwrap_err().is_busy());
+
+        child.delete()?;
+        parent.delete()?;
+        assert!(!fake.exists(&parent.path()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fake_tasks_procs() -> Result<()> {
+        let fake = TempFake::new(gen_cgroup_name!(), &[SubsystemKind::Cpu])?;
+
+        let mut a = cpu::Subsystem::new(
+            CgroupPath::new(SubsystemKind::Cpu, PathBuf::from("a")).with_backend(fake.backend()),
+        );
+        let mut b = cpu::Subsystem::new(
+            CgroupPath::new(SubsystemKind::Cpu, PathBuf::from("b")).with_backend(fake.backend()),
+        );
+        a.create()?;
+        b.create()?;
+
+        a.add_task(42)?;
+        assert_eq!(a.tasks()?, vec![42.into()]);
+        assert_eq!(a.procs()?, vec![42.into()]);
+
+        b.add_proc(42)?;
+        b.add_task(43)?;
+        assert!(a.tasks()?.is_empty());
+        assert!(a.procs()?.is_empty());
+        assert_eq!(b.tasks()?, vec![42.into(), 43.into()]);
+
+        assert!(b.delete().unwrap_err().is_busy());
+        assert!(b.add_task(0).is_ok());
+        assert!(b.tasks()?.contains(&std::process::id().into()));
+
+        let err = fake.write(&b.path().join("tasks"), b"foo").unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+
+        for task in b.tasks()? {
+            b.remove_task(task)?;
+        }
+        assert!(b.tasks()?.is_empty());
+        assert_eq!(b.root_cgroup().tasks()?.len(), 3);
+
+        a.delete()?;
+        b.delete()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_fake_devices() -> Result<()> {
+        let fake = TempFake::new(gen_cgroup_name!(), &[SubsystemKind::Devices])?;
+
+        let mut cgroup = devices::Subsystem::new(
+            CgroupPath::new(SubsystemKind::Devices, PathBuf::from("a"))
+                .with_backend(fake.backend()),
+        );
+        cgroup.create()?;
+
+        let all = "a *:* rwm".parse::<devices::Access>()?;
+        assert_eq!(cgroup.list()?, vec![all.clone()]);
+
+        // specific entries are not listed while all access is allowed
+        cgroup.deny(&"c 1:3 r".parse()?)?;
+        assert_eq!(cgroup.list()?, vec![all.clone()]);
+
+        cgroup.deny(&"a".parse()?)?;
+        assert!(cgroup.list()?.is_empty());
+
+        cgroup.allow(&"c 1:3 mr".parse()?)?;
+        cgroup.allow(&"b 8:0 r".parse()?)?;
+        assert_eq!(
+            cgroup.list()?,
+            vec!["c 1:3 rm".parse()?, "b 8:0 r".parse()?]
+        );
+
+        cgroup.allow(&"c 1:3 w".parse()?)?;
+        cgroup.deny(&"b 8:0 rw".parse()?)?;
+        assert_eq!(cgroup.list()?, vec!["c 1:3 rwm".parse()?]);
+
+        cgroup.deny(&"c 1:3 rw".parse()?)?;
+        assert_eq!(cgroup.list()?, vec!["c 1:3 m".parse()?]);
+
+        // a child inherits the device access of its parent
+        let mut child = devices::Subsystem::new(cgroup.cgroup_path().with_name("a/b".into()));
+        child.create()?;
+        assert_eq!(child.list()?, vec!["c 1:3 m".parse()?]);
+
+        let err = fake
+            .write(&child.path().join("devices.allow"), b"x 1:3 r")
+            .unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+
+        cgroup.allow(&all)?;
+        assert_eq!(cgroup.list()?, vec![all]);
+
+        child.delete()?;
+        cgroup.delete()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_fake_freezer() -> Result<()> {
+        use freezer::State;
+
+        let fake = TempFake::new(gen_cgroup_name!(), &[SubsystemKind::Freezer])?;
+
+        let path = CgroupPath::new(SubsystemKind::Freezer, PathBuf::from("a/b"));
+        let mut child = freezer::Subsystem::new(path.with_backend(fake.backend()));
+        let mut parent = *child.parent().unwrap();
+        parent.create()?;
+        child.create()?;
+        assert_eq!(child.state()?, State::Thawed);
+
+        parent.freeze()?;
+        assert_eq!(parent.state()?, State::Frozen);
+        assert!(parent.self_freezing()?);
+        assert_eq!(child.state()?, State::Frozen);
+        assert!(!child.self_freezing()?);
+        assert!(child.parent_freezing()?);
+
+        // a child created in a frozen cgroup is frozen
+        let mut grandchild = freezer::Subsystem::new(child.cgroup_path().with_name("a/b/c".into()));
+        grandchild.create()?;
+        assert_eq!(grandchild.state()?, State::Frozen);
+        assert!(grandchild.parent_freezing()?);
+
+        child.freeze()?;
+        parent.thaw()?;
+        assert_eq!(parent.state()?, State::Thawed);
+        assert_eq!(child.state()?, State::Frozen);
+        assert!(!child.parent_freezing()?);
+        assert!(grandchild.parent_freezing()?);
+
+        child.thaw()?;
+        assert_eq!(grandchild.state()?, State::Thawed);
+        assert!(!grandchild.parent_freezing()?);
+
+        let err = fake
+            .write(&child.path().join("freezer.state"), b"FREEZING")
+            .unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+
+        grandchild.delete()?;
+        child.delete()?;
+        parent.delete()?;
+        Ok(())
+    }
+}
@@ -48,7 +48,7 @@
 //! [Documentation/cgroup-v1/blkio-controller.txt]: https://www.kernel.org/doc/Documentation/cgroup-v1/blkio-controller.txt
 
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     io::{self, BufRead},
     path::PathBuf,
     str::FromStr,
@@ -56,7 +56,11 @@ use std::{
 
 use crate::{
     parse::{parse, parse_next},
-    v1::{self, cgroup::CgroupHelper, Cgroup, CgroupPath},
+    v1::{
+        self,
+        cgroup::{optional, CgroupHelper},
+        CgroupPath,
+    },
     Device, Error, ErrorKind, Result,
 };
 
@@ -70,6 +74,8 @@ pub struct Subsystem {
 ///
 /// See the kernel's documentation for more information about the fields.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Resources {
     /// Relative weight of block I/O performed by this cgroup.
     ///
@@ -77,14 +83,16 @@ pub struct Resources {
     pub weight: Option<u16>,
     /// Override `weight` for specific devices.
     ///
-    /// The value must be between 10 and 1,000 (inclusive).
+    /// The value must be between 10 and 1,000 (inclusive), or 0 to remove the override.
     pub weight_device: HashMap<Device, u16>,
 
     /// How much weight this cgroup has while competing against descendant cgroups.
+    ///
+    /// The value must be between 10 and 1,000 (inclusive).
     pub leaf_weight: Option<u16>,
     /// Override `leaf_weight` for specific devices.
     ///
-    /// The value must be between 10 and 1,000 (inclusive).
+    /// The value must be between 10 and 1,000 (inclusive), or 0 to remove the override.
     pub leaf_weight_device: HashMap<Device, u16>,
 
     /// Throttle bytes/s of read access for each device.
@@ -102,6 +110,7 @@ pub struct Resources {
 /// The unit can be either bytes/s, ops/s, or nanosecond, depending on the source from which the
 /// value is obtained.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IoService {
     /// How much I/O service this cgroup has transferred for each device.
     pub devices: HashMap<Device, Operations>,
@@ -114,6 +123,7 @@ pub struct IoService {
 /// The unit can be either bytes/s, ops/s, or nanosecond, depending on the source from which the
 /// value is obtained.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Operations {
     /// Value for read access.
     pub read: u64,
@@ -125,6 +135,10 @@ pub struct Operations {
     pub async_: u64,
     /// Total value.
     pub total: u64,
+    /// Values for types of operation unknown to this crate and added by newer kernels (e.g.
+    /// `Discard`).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extra: BTreeMap<String, u64>,
 }
 
 impl_cgroup! {
@@ -159,6 +173,22 @@ impl_cgroup! {
 
         Ok(())
     }
+
+    /// Reads `resources.blkio` from this cgroup. `weight` and `leaf_weight` fields are `None`,
+    /// and the device maps for them are empty, if the I/O scheduler does not support them.
+    fn resources(&self) -> Result<v1::Resources> {
+        Ok(self::Resources {
+            weight: optional(self.weight())?,
+            weight_device: optional(self.weight_device())?.unwrap_or_default(),
+            leaf_weight: optional(self.leaf_weight())?,
+            leaf_weight_device: optional(self.leaf_weight_device())?.unwrap_or_default(),
+            read_bps_device: self.read_bps_device()?,
+            write_bps_device: self.write_bps_device()?,
+            read_iops_device: self.read_iops_device()?,
+            write_iops_device: self.write_iops_device()?,
+        }
+        .into())
+    }
 }
 
 macro_rules! _gen_getter {
@@ -184,25 +214,26 @@ macro_rules! _gen_getter {
         gen_doc!(err_read; subsys_file!("blkio.throttle", $field)),
         gen_doc!(eg_read; blkio, $field)),
         pub fn $field(&self) -> Result<HashMap<Device, u64>> {
-            self.open_file_read(subsys_file!("blkio.throttle", $field)).and_then(parse_map)
+            self.read_file(subsys_file!("blkio.throttle", $field), parse_map)
         }
     } };
 
     (_rec; $recursive: ident, $field: ident, $ty: ty, $parser: ident) => { with_doc! {
         gen_doc!(reads_see; subsys_file!(blkio, $recursive), $field),
         pub fn $recursive(&self) -> Result<$ty> {
-            self.open_file_read(subsys_file!(blkio, $recursive))
-                .and_then($parser)
+            self.read_file(subsys_file!(blkio, $recursive), $parser)
         }
     } };
 }
 
-const WEIGHT_MIN: u16 = 10;
-const WEIGHT_MAX: u16 = 1000;
+pub(crate) const WEIGHT_MIN: u16 = 10;
+pub(crate) const WEIGHT_MAX: u16 = 1000;
 
 macro_rules! _gen_setter {
     (weight; $desc: literal, $field: ident : link, $setter: ident) => { with_doc! { concat!(
-        _gen_setter!(_sets_see_err_weight; $desc, $field),
+        _gen_setter!(
+            _sets_see_err_weight; $desc, $field, "The value must be between 10 and 1,000 (inclusive)."
+        ),
         gen_doc!(eg_write; blkio, $setter, 1000)),
         pub fn $setter(&mut self, weight: u16) -> Result<()> {
             if weight < WEIGHT_MIN || weight > WEIGHT_MAX {
@@ -214,17 +245,17 @@ macro_rules! _gen_setter {
     } };
 
     (weight_map; $desc: literal, $field: ident : link, $setter: ident) => { with_doc!{ concat!(
-        _gen_setter!(_sets_see_err_weight; $desc, $field),
+        _gen_setter!(
+            _sets_see_err_weight; $desc, $field,
+            "The value must be between 10 and 1,000 (inclusive), or 0 to remove the override."
+        ),
         gen_doc!(eg_write; blkio, $setter, [8, 0].into(), 1000)),
         pub fn $setter(&mut self, device: Device, weight: u16) -> Result<()> {
-            use io::Write;
-
-            if weight < WEIGHT_MIN || weight > WEIGHT_MAX {
+            if weight != 0 && (weight < WEIGHT_MIN || weight > WEIGHT_MAX) {
                 return Err(Error::new(ErrorKind::InvalidArgument));
             }
 
-            let mut file = self.open_file_write(subsys_file!(blkio, $field))?;
-            write!(file, "{} {}", device, weight).map_err(Into::into)
+            self.write_file(subsys_file!(blkio, $field), format!("{} {}", device, weight))
         }
     } };
 
@@ -235,21 +266,16 @@ macro_rules! _gen_setter {
             gen_doc!(err_write; subsys_file!("blkio.throttle", $field)),
             gen_doc!(eg_write; blkio, $setter, [8, 0].into(), 100)),
             pub fn $setter(&mut self, device: Device, $arg: $ty) -> Result<()> {
-                use io::Write;
-
-                let mut file = self.open_file_write(subsys_file!("blkio.throttle", $field))?;
-                // write!(file, "{} {}", device, $arg).map_err(Into::into) // not work
-                file.write_all(format!("{} {}", device, $arg).as_bytes()).map_err(Into::into)
+                self.write_file(
+                    subsys_file!("blkio.throttle", $field),
+                    format!("{} {}", device, $arg),
+                )
             }
         }
     };
 
-    (_sets_see_err_weight; $desc: literal, $field: ident) => { concat!(
-        gen_doc!(
-            sets;
-            subsys_file!(blkio, $field),
-            $desc : "The value must be between 10 and 1,000 (inclusive)."
-        ),
+    (_sets_see_err_weight; $desc: literal, $field: ident, $range: literal) => { concat!(
+        gen_doc!(sets; subsys_file!(blkio, $field), $desc : $range),
         gen_doc!(see; $field),
 "# Errors
 
@@ -425,79 +451,88 @@ fn parse_io_service(reader: impl io::Read) -> Result<IoService> {
     let mut devices = HashMap::new();
     let mut total = None;
 
-    // FIXME: avoid memory allocation
-    let lines = io::BufReader::new(reader)
-        .lines()
-        .collect::<std::result::Result<Vec<_>, std::io::Error>>()?;
-
-    for lines5 in lines.chunks(5) {
-        match &lines5 {
-            // FIXME: order is guaranteed?
-            // FIXME: 5 lines of the same device are guaranteed to be contiguous?
-            [read, write, sync, async_, total] => {
-                let mut e = read.split_whitespace();
-                let device = parse_next(&mut e)?;
-
-                let read = parse_next(e.skip(1))?;
-                let write = parse_next({
-                    let e = write.split_whitespace();
-                    e.skip(2)
-                })?;
-                let sync = parse_next({
-                    let e = sync.split_whitespace();
-                    e.skip(2)
-                })?;
-                let async_ = parse_next({
-                    let e = async_.split_whitespace();
-                    e.skip(2)
-                })?;
-                let total = parse_next({
-                    let e = total.split_whitespace();
-                    e.skip(2)
-                })?;
-
-                devices.insert(
-                    device,
-                    Operations {
-                        read,
-                        write,
-                        sync,
-                        async_,
-                        total,
-                    },
-                );
-            }
-            [tot] => {
-                let mut entry = tot.split_whitespace();
-                if entry.next() != Some("Total") {
-                    bail_parse!();
-                }
+    for line in io::BufReader::new(reader).lines() {
+        let line = line?;
+        let mut entry = line.split_whitespace();
 
-                total = Some(parse_next(&mut entry)?);
+        // "Total" row must be the last one.
+        if total.is_some() {
+            bail_parse!();
+        }
 
-                if entry.next().is_some() {
+        match entry.next() {
+            Some("Total") => total = Some(parse_next(&mut entry)?),
+            Some(device) => {
+                let device: Device = parse_next(std::iter::once(device))?;
+                let operation = match entry.next() {
+                    Some(operation) => operation.to_string(),
+                    None => {
+                        bail_parse!();
+                    }
+                };
+
+                let operations = devices.entry(device).or_insert_with(BTreeMap::new);
+                if operations
+                    .insert(operation, parse_next(&mut entry)?)
+                    .is_some()
+                {
                     bail_parse!();
                 }
-
-                break;
             }
-            _ => {
+            None => {
                 bail_parse!();
             }
         }
-    }
 
-    if let Some(total) = total {
-        Ok(IoService { devices, total })
-    } else {
-        bail_parse!();
+        if entry.next().is_some() {
+            bail_parse!();
+        }
     }
+
+    let total = match total {
+        Some(total) => total,
+        None => {
+            bail_parse!();
+        }
+    };
+
+    let devices = devices
+        .into_iter()
+        .map(|(device, mut extra)| {
+            let mut known = |operation| match extra.remove(operation) {
+                Some(val) => Ok(val),
+                None => Err(Error::new(ErrorKind::Parse)),
+            };
+
+            let (read, write, sync, async_, total) = (
+                known("Read")?,
+                known("Write")?,
+                known("Sync")?,
+                known("Async")?,
+                known("Total")?,
+            );
+
+            Ok((
+                device,
+                Operations {
+                    read,
+                    write,
+                    sync,
+                    async_,
+                    total,
+                    extra,
+                },
+            ))
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(IoService { devices, total })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use v1::SubsystemKind;
+    use v1::{Cgroup, SubsystemKind};
 
     #[test]
     #[rustfmt::skip]
@@ -778,6 +813,7 @@ Total 29281497
                         sync: 6786851,
                         async_: 3570020,
                         total: 10356871,
+                        extra: BTreeMap::new(),
                     },
                 ),
                 (
@@ -788,6 +824,7 @@ Total 29281497
                         sync: 0,
                         async_: 0,
                         total: 0,
+                        extra: BTreeMap::new(),
                     },
                 ),
             },
@@ -796,6 +833,35 @@ Total 29281497
 
         assert_eq!(actual, expected);
 
+        const CONTENT_OK_EXTRA: &str = "\
+259:0 Read 5941
+259:0 Write 10350930
+259:0 Sync 6786851
+259:0 Async 3570020
+259:0 Discard 4096
+259:0 Total 10361063
+Total 10361063
+";
+
+        let actual = parse_io_service(CONTENT_OK_EXTRA.as_bytes())?;
+        assert_eq!(
+            actual,
+            IoService {
+                devices: hashmap! {(
+                    [259, 0].into(),
+                    Operations {
+                        read: 5941,
+                        write: 10350930,
+                        sync: 6786851,
+                        async_: 3570020,
+                        total: 10361063,
+                        extra: [("Discard".to_string(), 4096)].iter().cloned().collect(),
+                    },
+                )},
+                total: 10361063,
+            }
+        );
+
         const CONTENT_OK_EMPTY: &str = "\
 Total 0
 ";
@@ -835,6 +901,16 @@ Total 29281497
 259:0 Total 10356871
         ";
 
+        const CONTENT_NG_DUPLICATE: &str = "\
+259:0 Read 5941
+259:0 Read 5941
+259:0 Write 10350930
+259:0 Sync 6786851
+259:0 Async 3570020
+259:0 Total 10356871
+Total 29281497
+";
+
         const CONTENT_NG_TOTAL_ORDER: &str = "\
 259:0 Read 5941
 259:0 Write 10350930
@@ -853,6 +929,7 @@ Total 29281497
             CONTENT_NG_MISSING_DATA,
             CONTENT_NG_EXTRA_DATA,
             CONTENT_NG_MISSING_TOTAL,
+            CONTENT_NG_DUPLICATE,
             CONTENT_NG_TOTAL_ORDER,
         ] {
             assert_eq!(
@@ -4,10 +4,13 @@
 //!
 //! [`Builder`]: struct.Builder.html
 
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc};
 
 use crate::{
-    v1::{cpuset, devices, freezer, hugetlb, net_cls, rdma, Resources, SubsystemKind, UnifiedRepr},
+    v1::{
+        backend::Backend, cpuset, devices, freezer, hugetlb, net_cls, rdma, Guard, Resources,
+        SubsystemKind, UnifiedRepr,
+    },
     Device, Result,
 };
 
@@ -162,6 +165,7 @@ pub struct Builder {
     name: PathBuf,
     subsystems: Vec<SubsystemKind>,
     skips: Vec<SubsystemKind>,
+    backend: Option<Arc<dyn Backend>>,
     resources: Resources,
 }
 
@@ -187,6 +191,7 @@ impl Builder {
             name,
             subsystems: Vec::new(),
             skips: Vec::new(),
+            backend: None,
             resources: Resources::default(),
         }
     }
@@ -203,6 +208,17 @@ impl Builder {
         self
     }
 
+    /// Accesses the files of the cgroups through a backend, instead of the real cgroup file
+    /// system.
+    ///
+    /// See [`UnifiedRepr::with_backend`] for more information.
+    ///
+    /// [`UnifiedRepr::with_backend`]: ../struct.UnifiedRepr.html#method.with_backend
+    pub fn backend(mut self, backend: Arc<dyn Backend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
     gen_subsystem_builder_calls! {
         (cpu, Cpu, CpuBuilder, "CPU"),
         (cpuset, Cpuset, CpusetBuilder, "cpuset"),
@@ -243,15 +259,38 @@ impl Builder {
     /// [`cpu`]: #method.cpu
     /// [`skip_create`]: #method.skip_create
     pub fn build(self) -> Result<UnifiedRepr> {
-        let mut unified_repr = UnifiedRepr::with_subsystems(self.name, &self.subsystems);
-
-        unified_repr.skip_create(&self.skips);
+        let mut unified_repr = self.unified_repr();
         unified_repr.create()?;
 
         unified_repr.apply(&self.resources)?;
 
         Ok(unified_repr)
     }
+
+    /// Builds a (set of) cgroup(s) with the configuration, as [`build`] method does, and returns a
+    /// [`Guard`] that deletes the cgroups when dropped.
+    ///
+    /// Unlike [`build`] method, the created directories are deleted if failed to apply the
+    /// configuration.
+    ///
+    /// [`build`]: #method.build
+    /// [`Guard`]: ../struct.Guard.html
+    pub fn build_guarded(self) -> Result<Guard> {
+        let mut guard = self.unified_repr().create_guarded()?;
+        guard.apply(&self.resources)?;
+
+        Ok(guard)
+    }
+
+    fn unified_repr(&self) -> UnifiedRepr {
+        let mut unified_repr = UnifiedRepr::with_subsystems(self.name.clone(), &self.subsystems);
+        if let Some(ref backend) = self.backend {
+            unified_repr = unified_repr.with_backend(backend.clone());
+        }
+
+        unified_repr.skip_create(&self.skips);
+        unified_repr
+    }
 }
 
 macro_rules! gen_subsystem_builder {
@@ -781,6 +820,28 @@ mod tests {
         cgroups.delete()
     }
 
+    #[test]
+    fn test_builder_backend() -> Result<()> {
+        use crate::v1::backend::TempFake;
+
+        let name = gen_cgroup_name!();
+        let fake = TempFake::new(&name, &[SubsystemKind::Pids])?;
+
+        #[rustfmt::skip]
+        let mut cgroups = Builder::new(name.clone())
+            .backend(fake.backend())
+            .pids()
+                .max(42.into())
+                .done()
+            .build()?;
+
+        assert_eq!(cgroups.pids().unwrap().max()?, 42.into());
+        let real = crate::v1::pids::Subsystem::new(CgroupPath::new(SubsystemKind::Pids, name));
+        assert!(!real.path().exists());
+
+        cgroups.delete()
+    }
+
     #[test]
     fn err_builder() -> Result<()> {
         let name = gen_cgroup_name!();
@@ -799,6 +860,24 @@ mod tests {
         cgroup.delete()
     }
 
+    #[test]
+    fn err_builder_guarded() {
+        let name = gen_cgroup_name!();
+
+        #[rustfmt::skip]
+        let cgroups = Builder::new(name.clone())
+            .cpuset()
+                .memory_pressure_enabled(true)
+                .done()
+            .build_guarded();
+
+        assert_eq!(cgroups.unwrap_err().kind(), ErrorKind::InvalidOperation);
+
+        // the created directories have been deleted
+        let cgroup = cpuset::Subsystem::new(CgroupPath::new(SubsystemKind::Cpuset, name));
+        assert!(!cgroup.path().exists());
+    }
+
     #[test]
     fn test_builder_not_create_unused_subsystem_directory() -> Result<()> {
         let name = gen_cgroup_name!();
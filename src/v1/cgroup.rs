@@ -1,11 +1,17 @@
 use std::{
-    fs::{self, File},
+    fs::{File, OpenOptions},
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use crate::{
+    file,
     parse::parse_01_bool,
-    v1::{self, Resources, SubsystemKind},
+    v1::{
+        self,
+        backend::{Backend, Sysfs},
+        delete, transaction, DeleteOptions, Resources, SubsystemKind,
+    },
     Error, ErrorKind, Pid, Result,
 };
 
@@ -120,6 +126,35 @@ pub trait Cgroup {
     /// ```
     fn path(&self) -> PathBuf;
 
+    /// Returns the definition of the path to this cgroup, i.e. the `CgroupPath` this cgroup was
+    /// defined with by [`new`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{cpu, Cgroup, CgroupPath, SubsystemKind};
+    ///
+    /// let path = CgroupPath::new(SubsystemKind::Cpu, PathBuf::from("students/charlie"));
+    /// let cgroup = cpu::Subsystem::new(path.clone());
+    ///
+    /// assert_eq!(cgroup.cgroup_path(), &path);
+    /// ```
+    ///
+    /// [`new`]: #tymethod.new
+    fn cgroup_path(&self) -> &CgroupPath;
+
+    /// Returns the backend through which the files of this cgroup are accessed.
+    ///
+    /// This is the backend attached to the `CgroupPath` of this cgroup by
+    /// [`CgroupPath::with_backend`], or [`backend::Sysfs`] by default.
+    ///
+    /// [`CgroupPath::with_backend`]: struct.CgroupPath.html#method.with_backend
+    /// [`backend::Sysfs`]: backend/struct.Sysfs.html
+    fn backend(&self) -> &dyn Backend {
+        &Sysfs
+    }
+
     /// Returns whether this cgroup is the root cgroup of a subsystem.
     ///
     /// # Examples
@@ -155,6 +190,83 @@ pub trait Cgroup {
     /// ```
     fn root_cgroup(&self) -> Box<Self>;
 
+    /// Returns the definition of the parent cgroup of this cgroup, or `None` if this cgroup is the
+    /// root cgroup of a subsystem.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{cpu, Cgroup, CgroupPath, SubsystemKind};
+    ///
+    /// let cgroup = cpu::Subsystem::new(
+    ///     CgroupPath::new(SubsystemKind::Cpu, PathBuf::from("students/charlie")));
+    ///
+    /// let parent = cgroup.parent().unwrap();
+    /// assert_eq!(parent.path(), PathBuf::from("/sys/fs/cgroup/cpu/students"));
+    ///
+    /// assert!(parent.root_cgroup().parent().is_none());
+    /// ```
+    fn parent(&self) -> Option<Box<Self>>;
+
+    /// Reads the list of child cgroups of this cgroup, i.e. subdirectories of the directory of this
+    /// cgroup. The children are sorted by their names.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to read the directory of this cgroup, with kind
+    /// [`ErrorKind::Io`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{cpu, Cgroup, CgroupPath, SubsystemKind};
+    ///
+    /// let cgroup = cpu::Subsystem::new(
+    ///     CgroupPath::new(SubsystemKind::Cpu, PathBuf::from("students")));
+    ///
+    /// for child in cgroup.children()? {
+    ///     println!("{}", child.path().display());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
+    fn children(&self) -> Result<Vec<Self>>
+    where
+        Self: Sized;
+
+    /// Returns an iterator visiting this cgroup and all its descendants recursively.
+    ///
+    /// The cgroups are visited in pre-order, i.e. a cgroup is always visited before its children.
+    /// See [`Walk`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{cpu, Cgroup, CgroupPath, SubsystemKind};
+    ///
+    /// let cgroup = cpu::Subsystem::new(
+    ///     CgroupPath::new(SubsystemKind::Cpu, PathBuf::from("students")));
+    ///
+    /// for descendant in cgroup.walk() {
+    ///     let descendant = descendant?;
+    ///     println!("{}: {:?}", descendant.path().display(), descendant.procs()?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Walk`]: struct.Walk.html
+    fn walk(&self) -> Walk<Self>
+    where
+        Self: Sized;
+
     /// Creates a new directory for this cgroup.
     ///
     /// Note that this method does not create directories recursively; If a parent of the path does
@@ -186,7 +298,7 @@ pub trait Cgroup {
     ///
     /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
     fn create(&mut self) -> Result<()> {
-        fs::create_dir(self.path()).map_err(Into::into)
+        self.backend().create_dir(&self.path()).map_err(Into::into)
     }
 
     /// Applies a set of resource limits and constraints to this cgroup.
@@ -223,6 +335,122 @@ pub trait Cgroup {
     /// ```
     fn apply(&mut self, resources: &Resources) -> Result<()>;
 
+    /// Reads the current resource limits and constraints of this cgroup, i.e. the inverse of
+    /// [`apply`].
+    ///
+    /// Only the fields for the subsystem of this cgroup are populated, and the others are left
+    /// default. A field is `None` if the corresponding file is not present in this cgroup (e.g.
+    /// due to the kernel configuration), or if the field cannot be applied to this cgroup.
+    ///
+    /// See also implementors' documentations for their specific behavior.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to read and parse a file of this cgroup.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{cpu, Cgroup, CgroupPath, SubsystemKind};
+    ///
+    /// let cgroup = cpu::Subsystem::new(
+    ///     CgroupPath::new(SubsystemKind::Cpu, PathBuf::from("students/charlie")));
+    ///
+    /// let resources = cgroup.resources()?;
+    /// println!("{:?}", resources.cpu.shares);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`apply`]: #tymethod.apply
+    fn resources(&self) -> Result<Resources>;
+
+    /// Applies only the changes from `old` to `new` to this cgroup, assuming that this cgroup is
+    /// currently configured with `old`.
+    ///
+    /// This is equivalent to `apply(&old.diff(new))`, and avoids needless writes, some of which
+    /// have side effects (e.g. writing `memory.limit_in_bytes` triggers reclaim). See
+    /// [`Resources::diff`] for how the changes are computed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to apply the changes. See [`apply`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{cpu, Cgroup, CgroupPath, SubsystemKind};
+    ///
+    /// let mut cgroup = cpu::Subsystem::new(
+    ///     CgroupPath::new(SubsystemKind::Cpu, PathBuf::from("students/charlie")));
+    /// cgroup.create()?;
+    ///
+    /// let old = cgroup.resources()?;
+    ///
+    /// let mut new = old.clone();
+    /// new.cpu.shares = Some(1000);
+    ///
+    /// // Only `cpu.shares` is written.
+    /// cgroup.apply_diff(&old, &new)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`apply`]: #tymethod.apply
+    /// [`Resources::diff`]: struct.Resources.html#method.diff
+    fn apply_diff(&mut self, old: &Resources, new: &Resources) -> Result<()> {
+        self.apply(&old.diff(new))
+    }
+
+    /// Applies resource limits and constraints to this cgroup like [`apply`], but restores the
+    /// previous values if any write fails.
+    ///
+    /// Before each file is written, its current value is read and recorded. If a later read or
+    /// write fails, the recorded values are written back in reverse order, so that constraints
+    /// between files (e.g. `memory.limit_in_bytes` must not exceed `memory.memsw.limit_in_bytes`)
+    /// hold while rolling back. Entries that were absent before (e.g. a throttle of a device in a
+    /// blkio map) are reset to their default.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to read or write a value. The kind of the error is that of the
+    /// failure, and its source is an [`ApplyError`] holding the failure and all failures in rolling
+    /// back.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{cpu, Cgroup, CgroupPath, SubsystemKind, Resources};
+    ///
+    /// let mut cgroup = cpu::Subsystem::new(
+    ///     CgroupPath::new(SubsystemKind::Cpu, PathBuf::from("students/charlie")));
+    /// cgroup.create()?;
+    ///
+    /// let mut resources = Resources::default();
+    /// resources.cpu.shares = Some(1000);
+    /// resources.cpu.cfs_quota_us = Some(500 * 1000);
+    ///
+    /// // If writing `cpu.cfs_quota_us` fails, `cpu.shares` is restored.
+    /// cgroup.apply_transactional(&resources)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`apply`]: #tymethod.apply
+    /// [`ApplyError`]: struct.ApplyError.html
+    fn apply_transactional(&mut self, resources: &Resources) -> Result<()>
+    where
+        Self: Sized,
+    {
+        transaction::apply_transactional(self, resources)
+    }
+
     /// Deletes the directory of this cgroup.
     ///
     /// Deleting the directory will fail if this cgroup is in use, i.e. a task is still attached.
@@ -250,7 +478,44 @@ pub trait Cgroup {
     ///
     /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
     fn delete(&mut self) -> Result<()> {
-        fs::remove_dir(self.path()).map_err(Into::into)
+        self.backend().remove_dir(&self.path()).map_err(Into::into)
+    }
+
+    /// Deletes the directories of this cgroup and all its descendants, bottom-up.
+    ///
+    /// Before each cgroup is deleted, processes remaining in it are moved to the parent of this
+    /// cgroup or to the root cgroup, or are killed, as specified by `options.drain`. If the system
+    /// reports that a cgroup is still busy, draining and deleting it are retried as specified by
+    /// `options.retries` and `options.retry_interval`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`ErrorKind::InvalidOperation`] if this cgroup is the root
+    /// cgroup. Returns an error if failed to read the descendants of this cgroup, to move or kill
+    /// the remaining processes, or to delete a directory. Cgroups deleted before the error occurred
+    /// are not restored.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{cpu, Cgroup, CgroupPath, DeleteOptions, Drain, SubsystemKind};
+    ///
+    /// let mut cgroup = cpu::Subsystem::new(
+    ///     CgroupPath::new(SubsystemKind::Cpu, PathBuf::from("students")));
+    ///
+    /// cgroup.delete_recursive(&DeleteOptions { drain: Drain::Root, ..DeleteOptions::default() })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ErrorKind::InvalidOperation`]: ../enum.ErrorKind.html#variant.InvalidOperation
+    fn delete_recursive(&mut self, options: &DeleteOptions) -> Result<()>
+    where
+        Self: Sized,
+    {
+        delete::delete_recursive(self, options)
     }
 
     gen_getter!(
@@ -269,7 +534,7 @@ pub trait Cgroup {
         gen_doc!(err_write; "tasks"),
         gen_doc!(eg_write; cpu, add_task, std::process::id())),
         fn add_task(&mut self, pid: impl Into<Pid>) -> Result<()> {
-            fs::write(self.path().join("tasks"), format!("{}", pid.into())).map_err(Into::into)
+            file::write(self.backend(), self.path().join("tasks"), pid.into().to_string())
         }
     }
 
@@ -320,7 +585,7 @@ pub trait Cgroup {
         gen_doc!(err_write; "cgroup.procs"),
         gen_doc!(eg_write; cpu, add_proc, std::process::id())),
         fn add_proc(&mut self, pid: impl Into<Pid>) -> Result<()> {
-            fs::write(self.path().join("cgroup.procs"), format!("{}", pid.into())).map_err(Into::into)
+            file::write(self.backend(), self.path().join("cgroup.procs"), pid.into().to_string())
         }
     }
 
@@ -376,11 +641,7 @@ pub trait Cgroup {
         gen_doc!(err_write; "notify_on_release"),
         gen_doc!(eg_write; cpu, set_notify_on_release, true)),
         fn set_notify_on_release(&mut self, enable: bool) -> Result<()> {
-            fs::write(
-                self.path().join(NOTIFY_ON_RELEASE),
-                format!("{}", enable as i32),
-            )
-            .map_err(Into::into)
+            file::write(self.backend(), self.path().join(NOTIFY_ON_RELEASE), (enable as i32).to_string())
         }
     }
 
@@ -423,7 +684,7 @@ pub trait Cgroup {
             if !self.is_root() {
                 return Err(Error::new(ErrorKind::InvalidOperation));
             }
-            fs::write(self.path().join(RELEASE_AGENT), agent_path.as_ref()).map_err(Into::into)
+            file::write(self.backend(), self.path().join(RELEASE_AGENT), agent_path)
         }
     }
 
@@ -441,7 +702,11 @@ pub trait Cgroup {
                 return Err(Error::new(ErrorKind::InvalidOperation));
             }
 
-            self.open_file_read("cgroup.sane_behavior").and_then(parse_01_bool)
+            file::read(
+                self.backend(),
+                self.path().join("cgroup.sane_behavior"),
+                parse_01_bool,
+            )
         }
     }
 
@@ -463,7 +728,7 @@ pub trait Cgroup {
     /// # }
     /// ```
     fn file_exists(&self, name: &str) -> bool {
-        self.path().join(name).exists()
+        self.backend().exists(&self.path().join(name))
     }
 
     /// Low-level API that opens a file with read access.
@@ -489,7 +754,11 @@ pub trait Cgroup {
     ///
     /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
     fn open_file_read(&self, name: &str) -> Result<File> {
-        File::open(self.path().join(name)).map_err(Into::into)
+        file::open(
+            self.backend(),
+            self.path().join(name),
+            OpenOptions::new().read(true),
+        )
     }
 
     /// Low-level API that opens a file with write access.
@@ -515,20 +784,41 @@ pub trait Cgroup {
     ///
     /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
     fn open_file_write(&mut self, name: &str) -> Result<File> {
-        fs::OpenOptions::new()
-            .write(true)
-            .open(self.path().join(name))
-            .map_err(Into::into)
+        file::open(
+            self.backend(),
+            self.path().join(name),
+            OpenOptions::new().write(true),
+        )
     }
 }
 
 /// Path to a cgroup in a cgroup file system.
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// A `CgroupPath` also holds the backend through which the files of the cgroup are accessed. See
+/// [`with_backend`] for more information.
+///
+/// [`with_backend`]: #method.with_backend
+#[derive(Debug, Clone)]
 pub struct CgroupPath {
-    subsystem_root: PathBuf, // e.g. /sys/fs/cgroup/cpu
-    name: Option<PathBuf>,   // e.g. students/charlie
+    subsystem_root: PathBuf,           // e.g. /sys/fs/cgroup/cpu
+    name: Option<PathBuf>,             // e.g. students/charlie
+    backend: Option<Arc<dyn Backend>>, // `None` for `Sysfs`
 }
 
+impl PartialEq for CgroupPath {
+    fn eq(&self, other: &Self) -> bool {
+        self.subsystem_root == other.subsystem_root
+            && self.name == other.name
+            && match (&self.backend, &other.backend) {
+                (None, None) => true,
+                (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                _ => false,
+            }
+    }
+}
+
+impl Eq for CgroupPath {}
+
 impl CgroupPath {
     /// Create a new `CgroupPath` with a subsystem kind and a cgroup name.
     ///
@@ -585,9 +875,158 @@ impl CgroupPath {
             } else {
                 Some(name)
             },
+            backend: None,
+        }
+    }
+
+    /// Create a new `CgroupPath` with an explicit subsystem root and a cgroup name.
+    ///
+    /// The resulting path is the concatenation of 1) the given subsystem root, i.e. the mount point
+    /// of the hierarchy (e.g. `/sys/fs/cgroup/cpu,cpuacct`), and 2) the given cgroup name (e.g.
+    /// `students/charlie`). This is useful when the hierarchy is not mounted at the standard
+    /// location, or for testing against a fake tree.
+    ///
+    /// If the name is empty, the resulting path points to the root cgroup of the subsystem.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `subsystem_root` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::CgroupPath;
+    ///
+    /// let path = CgroupPath::with_root(
+    ///     PathBuf::from("/sys/fs/cgroup/cpu,cpuacct"),
+    ///     PathBuf::from("students/charlie"),
+    /// );
+    /// ```
+    pub fn with_root(subsystem_root: PathBuf, name: PathBuf) -> Self {
+        assert!(
+            !subsystem_root.as_os_str().is_empty(),
+            "Subsystem root must not be empty"
+        );
+
+        Self {
+            subsystem_root,
+            name: if name.as_os_str().is_empty() {
+                None
+            } else {
+                Some(name)
+            },
+            backend: None,
         }
     }
 
+    /// Create a new `CgroupPath` with a subsystem kind and a cgroup name, finding the mount point
+    /// of the hierarchy to which the subsystem is attached from `/proc/self/mountinfo`.
+    ///
+    /// See [`mount::MountInfo`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to read and parse `/proc/self/mountinfo`, or with kind
+    /// [`ErrorKind::InvalidArgument`] if the subsystem is not mounted.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{CgroupPath, SubsystemKind};
+    ///
+    /// let path = CgroupPath::discover(SubsystemKind::Cpu, PathBuf::from("students/charlie"))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`mount::MountInfo`]: mount/struct.MountInfo.html
+    /// [`ErrorKind::InvalidArgument`]: ../enum.ErrorKind.html#variant.InvalidArgument
+    pub fn discover(kind: SubsystemKind, name: PathBuf) -> Result<Self> {
+        v1::mount::MountInfo::load()?
+            .cgroup_path(kind, name)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidArgument))
+    }
+
+    /// Attaches a backend through which the files of the cgroup, and of the cgroups derived from
+    /// it (e.g. its parent and children), are accessed. By default, the real cgroup file system is
+    /// accessed.
+    ///
+    /// Two `CgroupPath`s are equal only if they point to the same path and share the same backend.
+    ///
+    /// See [`backend`] module for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::{path::PathBuf, sync::Arc};
+    /// use controlgroup::v1::{backend::Fake, CgroupPath, SubsystemKind};
+    ///
+    /// let fake = Arc::new(Fake::new(std::env::temp_dir().join("controlgroup_with_backend"))?);
+    ///
+    /// let path = CgroupPath::new(SubsystemKind::Cpu, PathBuf::from("students/charlie"))
+    ///     .with_backend(fake);
+    /// # std::fs::remove_dir_all(std::env::temp_dir().join("controlgroup_with_backend"))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`backend`]: backend/index.html
+    pub fn with_backend(mut self, backend: Arc<dyn Backend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    pub(crate) fn backend(&self) -> &dyn Backend {
+        match self.backend {
+            Some(ref backend) => backend.as_ref(),
+            None => &Sysfs,
+        }
+    }
+
+    pub(crate) fn shared_backend(&self) -> Arc<dyn Backend> {
+        match self.backend {
+            Some(ref backend) => backend.clone(),
+            None => Arc::new(Sysfs),
+        }
+    }
+
+    pub(crate) fn parent(&self) -> Option<Self> {
+        self.name.as_ref().map(|name| Self {
+            subsystem_root: self.subsystem_root.clone(),
+            name: name.parent().and_then(|p| {
+                if p.as_os_str().is_empty() {
+                    None
+                } else {
+                    Some(p.to_path_buf())
+                }
+            }),
+            backend: self.backend.clone(),
+        })
+    }
+
+    pub(crate) fn children(&self) -> Result<Vec<Self>> {
+        let mut children = Vec::new();
+
+        for dir in self.backend().list_dirs(&self.to_path_buf())? {
+            let name = match self.name {
+                Some(ref name) => name.join(dir),
+                None => PathBuf::from(dir),
+            };
+            children.push(Self {
+                subsystem_root: self.subsystem_root.clone(),
+                name: Some(name),
+                backend: self.backend.clone(),
+            });
+        }
+
+        children.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(children)
+    }
+
     pub(crate) fn to_path_buf(&self) -> PathBuf {
         if let Some(ref name) = self.name {
             self.subsystem_root.join(name)
@@ -604,8 +1043,22 @@ impl CgroupPath {
         Self {
             subsystem_root: self.subsystem_root.clone(),
             name: None,
+            backend: self.backend.clone(),
         }
     }
+
+    pub(crate) fn with_name(&self, name: PathBuf) -> Self {
+        Self {
+            backend: self.backend.clone(),
+            ..Self::with_root(self.subsystem_root.clone(), name)
+        }
+    }
+
+    pub(crate) fn name(&self) -> &Path {
+        self.name
+            .as_ref()
+            .map_or(Path::new(""), |name| name.as_path())
+    }
 }
 
 macro_rules! impl_cgroup {
@@ -623,6 +1076,14 @@ macro_rules! impl_cgroup {
                 self.path.to_path_buf()
             }
 
+            fn cgroup_path(&self) -> &crate::v1::CgroupPath {
+                &self.path
+            }
+
+            fn backend(&self) -> &dyn crate::v1::backend::Backend {
+                self.path.backend()
+            }
+
             fn is_root(&self) -> bool {
                 self.path.is_subsystem_root()
             }
@@ -631,19 +1092,98 @@ macro_rules! impl_cgroup {
                 Box::new(Self::new(self.path.subsystem_root()))
             }
 
+            fn parent(&self) -> Option<Box<Self>> {
+                self.path.parent().map(|p| Box::new(Self::new(p)))
+            }
+
+            fn children(&self) -> crate::Result<Vec<Self>> {
+                self.path
+                    .children()
+                    .map(|c| c.into_iter().map(Self::new).collect())
+            }
+
+            fn walk(&self) -> crate::v1::Walk<Self> {
+                crate::v1::Walk::new(Self::new(self.path.clone()), Self::children)
+            }
+
             $( $tt )*
         }
     };
 }
 
+/// Iterator visiting a cgroup and all its descendants recursively, in pre-order.
+///
+/// This struct is created by [`Cgroup::walk`] and [`UnifiedRepr::walk`] methods. Each item is the
+/// handler of a visited cgroup, or an error occurred while reading the children of a cgroup. The
+/// iteration continues after an error, skipping the descendants of the cgroup.
+///
+/// Note that the cgroups are read lazily. Cgroups created or deleted during the iteration may or
+/// may not be visited.
+///
+/// [`Cgroup::walk`]: trait.Cgroup.html#method.walk
+/// [`UnifiedRepr::walk`]: struct.UnifiedRepr.html#method.walk
+#[derive(Debug)]
+pub struct Walk<T> {
+    stack: Vec<T>,
+    error: Option<Error>,
+    children: fn(&T) -> Result<Vec<T>>,
+}
+
+impl<T> Walk<T> {
+    pub(crate) fn new(root: T, children: fn(&T) -> Result<Vec<T>>) -> Self {
+        Self {
+            stack: vec![root],
+            error: None,
+            children,
+        }
+    }
+}
+
+impl<T> Iterator for Walk<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+
+        let cgroup = self.stack.pop()?;
+        match (self.children)(&cgroup) {
+            Ok(children) => self.stack.extend(children.into_iter().rev()),
+            Err(e) => self.error = Some(e),
+        }
+
+        Some(Ok(cgroup))
+    }
+}
+
 pub(crate) trait CgroupHelper: Cgroup {
+    fn read_file<T>(&self, name: &str, parser: impl FnOnce(File) -> Result<T>) -> Result<T> {
+        file::read(self.backend(), self.path().join(name), parser)
+    }
+
     fn write_file(&mut self, name: &str, val: impl std::fmt::Display) -> Result<()> {
-        fs::write(self.path().join(name), format!("{}", val)).map_err(Into::into)
+        file::write(self.backend(), self.path().join(name), val.to_string())
     }
 }
 
 impl<T: Cgroup> CgroupHelper for T {}
 
+// Converts an error due to a missing file into `None`. Some files are present only with specific
+// kernel configurations.
+pub(crate) fn optional<T>(result: Result<T>) -> Result<Option<T>> {
+    match result {
+        Ok(x) => Ok(Some(x)),
+        Err(e) => {
+            if e.is_not_found() {
+                Ok(None)
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
 fn parse_tasks_procs(reader: impl std::io::Read) -> Result<Vec<Pid>> {
     use std::io::{BufRead, BufReader};
 
@@ -703,6 +1243,87 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_cgroup_error_context() -> Result<()> {
+        let mut cgroup =
+            cpu::Subsystem::new(CgroupPath::new(SubsystemKind::Cpu, gen_cgroup_name!()));
+
+        let e = cgroup.shares().unwrap_err();
+        assert!(e.is_not_found());
+        assert_eq!(e.path(), Some(cgroup.path().join("cpu.shares").as_path()));
+        assert_eq!(e.attempted_value(), None);
+
+        cgroup.create()?;
+
+        let e = cgroup.write_file("cpu.shares", "invalid").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Io);
+        assert!(e.is_invalid_value());
+        assert_eq!(e.file_name(), Some("cpu.shares"));
+        assert_eq!(e.attempted_value(), Some("invalid"));
+
+        cgroup.delete()
+    }
+
+    #[test]
+    fn test_cgroup_parent_children_walk() -> Result<()> {
+        let name = gen_cgroup_name!();
+        let mut cgroup = cpu::Subsystem::new(CgroupPath::new(SubsystemKind::Cpu, name.clone()));
+        cgroup.create()?;
+        assert!(cgroup.children()?.is_empty());
+
+        let mut children = ["b", "a"]
+            .iter()
+            .map(|c| cpu::Subsystem::new(CgroupPath::new(SubsystemKind::Cpu, name.join(c))))
+            .collect::<Vec<_>>();
+        for c in &mut children {
+            c.create()?;
+        }
+
+        let mut grandchild =
+            cpu::Subsystem::new(CgroupPath::new(SubsystemKind::Cpu, name.join("a/x")));
+        grandchild.create()?;
+
+        assert_eq!(
+            cgroup
+                .children()?
+                .iter()
+                .map(|c| c.path())
+                .collect::<Vec<_>>(),
+            vec![cgroup.path().join("a"), cgroup.path().join("b")]
+        );
+        assert_eq!(grandchild.parent().unwrap().path(), cgroup.path().join("a"));
+        assert_eq!(cgroup.parent().unwrap().path(), cgroup.root_cgroup().path());
+
+        assert_eq!(
+            cgroup
+                .walk()
+                .map(|c| c.map(|c| c.path()))
+                .collect::<Result<Vec<_>>>()?,
+            vec![
+                cgroup.path(),
+                cgroup.path().join("a"),
+                cgroup.path().join("a/x"),
+                cgroup.path().join("b"),
+            ]
+        );
+
+        grandchild.delete()?;
+        for c in &mut children {
+            c.delete()?;
+        }
+        cgroup.delete()
+    }
+
+    #[test]
+    fn err_cgroup_walk() {
+        let cgroup = cpu::Subsystem::new(CgroupPath::new(SubsystemKind::Cpu, gen_cgroup_name!()));
+
+        let mut walk = cgroup.walk();
+        assert_eq!(walk.next().unwrap().unwrap().path(), cgroup.path());
+        assert_eq!(walk.next().unwrap().unwrap_err().kind(), ErrorKind::Io);
+        assert!(walk.next().is_none());
+    }
+
     #[test]
     #[ignore] // must not be executed in parallel
     fn test_cgroup_add_get_remove_tasks() -> Result<()> {
@@ -920,6 +1541,21 @@ mod tests {
         assert_eq!(root.to_path_buf(), PathBuf::from("/sys/fs/cgroup/cpu"),);
     }
 
+    #[test]
+    fn test_cgroup_path_parent() {
+        let path = CgroupPath::new(SubsystemKind::Cpu, PathBuf::from("students/charlie"));
+
+        let parent = path.parent().unwrap();
+        assert_eq!(
+            parent.to_path_buf(),
+            PathBuf::from("/sys/fs/cgroup/cpu/students")
+        );
+
+        let root = parent.parent().unwrap();
+        assert!(root.is_subsystem_root());
+        assert!(root.parent().is_none());
+    }
+
     #[test]
     fn test_parse_tasks_procs() -> Result<()> {
         const CONTENT_OK: &str = "\
@@ -26,6 +26,9 @@ impl CommandExt for std::process::Command {
     /// Multiple cgroups can be registered for the process attachment. The process will be attached
     /// to the cgroups in order of their registration.
     ///
+    /// The process is attached by writing to `cgroup.procs` file of the real cgroup file system in
+    /// the forked child process, bypassing the backend attached to the cgroup, if any.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -49,11 +49,15 @@
 //! [Documentation/scheduler/sched-design-CFS.txt]: https://www.kernel.org/doc/Documentation/scheduler/sched-design-CFS.txt
 //! [Documentation/scheduler/sched-bwc.txt]: https://www.kernel.org/doc/Documentation/scheduler/sched-bwc.txt
 
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
 
 use crate::{
     parse::{parse, parse_next},
-    v1::{self, cgroup::CgroupHelper, Cgroup, CgroupPath},
+    v1::{
+        self,
+        cgroup::{optional, CgroupHelper},
+        CgroupPath,
+    },
     Result,
 };
 
@@ -67,6 +71,8 @@ pub struct Subsystem {
 ///
 /// See the kernel's documentation for more information about the fields.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Resources {
     /// Weight of how much of the total CPU time should be provided to this cgroup.
     pub shares: Option<u64>,
@@ -87,6 +93,7 @@ pub struct Resources {
 
 /// Throttling statistics of a cgroup.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Stat {
     /// Number of periods (as specified in [`Resources.cfs_period_us`]) that have elapsed.
     ///
@@ -96,6 +103,10 @@ pub struct Stat {
     pub nr_throttled: u64,
     /// Total time duration for which this cgroup has been throttled (in nanoseconds).
     pub throttled_time: u64,
+    /// Fields unknown to this crate and added by newer kernels (e.g. `nr_bursts`), with their
+    /// values.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extra: BTreeMap<String, u64>,
 }
 
 impl_cgroup! {
@@ -121,6 +132,19 @@ impl_cgroup! {
 
         Ok(())
     }
+
+    /// Reads `resources.cpu` from this cgroup. The fields other than `shares` are `None` if the
+    /// kernel does not support CFS bandwidth control or realtime group scheduling.
+    fn resources(&self) -> Result<v1::Resources> {
+        Ok(self::Resources {
+            shares: Some(self.shares()?),
+            cfs_quota_us: optional(self.cfs_quota_us())?,
+            cfs_period_us: optional(self.cfs_period_us())?,
+            rt_runtime_us: optional(self.rt_runtime_us())?,
+            rt_period_us: optional(self.rt_period_us())?,
+        }
+        .into())
+    }
 }
 
 impl Subsystem {
@@ -206,6 +230,7 @@ fn parse_stat(reader: impl std::io::Read) -> Result<Stat> {
     use std::io::{BufRead, BufReader};
 
     let (mut nr_periods, mut nr_throttled, mut throttled_time) = (None, None, None);
+    let mut extra = BTreeMap::new();
 
     for line in BufReader::new(reader).lines() {
         let line = line?;
@@ -230,7 +255,15 @@ fn parse_stat(reader: impl std::io::Read) -> Result<Stat> {
                 }
                 throttled_time = Some(parse_next(&mut entry)?);
             }
-            _ => bail_parse!(),
+            Some(key) => {
+                if extra
+                    .insert(key.to_string(), parse_next(&mut entry)?)
+                    .is_some()
+                {
+                    bail_parse!();
+                }
+            }
+            None => bail_parse!(),
         };
 
         if entry.next().is_some() {
@@ -243,6 +276,7 @@ fn parse_stat(reader: impl std::io::Read) -> Result<Stat> {
             nr_periods,
             nr_throttled,
             throttled_time,
+            extra,
         }),
         _ => {
             bail_parse!();
@@ -262,7 +296,7 @@ impl Into<v1::Resources> for Resources {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ErrorKind;
+    use crate::{v1::Cgroup, ErrorKind};
 
     #[test]
     fn test_subsystem_create_file_exists() -> Result<()> {
@@ -286,17 +320,38 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_subsystem_resources() -> Result<()> {
+        let mut cgroup =
+            Subsystem::new(CgroupPath::new(v1::SubsystemKind::Cpu, gen_cgroup_name!()));
+        cgroup.create()?;
+
+        let resources = Resources {
+            shares: Some(1024),
+            cfs_quota_us: Some(100_000),
+            cfs_period_us: Some(1_000_000),
+            rt_runtime_us: Some(0),
+            rt_period_us: Some(1_000_000),
+        }
+        .into();
+        cgroup.apply(&resources)?;
+        assert_eq!(cgroup.resources()?, resources);
+
+        cgroup.delete()
+    }
+
     #[test]
     fn test_subsystem_stat() -> Result<()> {
-        gen_subsystem_test!(
-            Cpu,
-            stat,
-            Stat {
-                nr_periods: 0,
-                nr_throttled: 0,
-                throttled_time: 0
-            }
-        )
+        let mut cgroup =
+            Subsystem::new(CgroupPath::new(v1::SubsystemKind::Cpu, gen_cgroup_name!()));
+        cgroup.create()?;
+
+        let stat = cgroup.stat()?;
+        assert_eq!(stat.nr_periods, 0);
+        assert_eq!(stat.nr_throttled, 0);
+        assert_eq!(stat.throttled_time, 0);
+
+        cgroup.delete()
     }
 
     #[test]
@@ -356,7 +411,29 @@ throttled_time 32
             Stat {
                 nr_periods: 256,
                 nr_throttled: 8,
-                throttled_time: 32
+                throttled_time: 32,
+                extra: BTreeMap::new(),
+            }
+        );
+
+        const CONTENT_OK_EXTRA: &str = "\
+nr_periods 256
+nr_throttled 8
+throttled_time 32
+nr_bursts 4
+burst_time 16
+";
+
+        assert_eq!(
+            parse_stat(CONTENT_OK_EXTRA.as_bytes())?,
+            Stat {
+                nr_periods: 256,
+                nr_throttled: 8,
+                throttled_time: 32,
+                extra: [("nr_bursts", 4), ("burst_time", 16)]
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), *v))
+                    .collect(),
             }
         );
 
@@ -382,18 +459,27 @@ nr_throttled 8 256
 throttled_time 32
 ";
 
-        const CONTENT_NG_EXTRA_ROW: &str = "\
+        const CONTENT_NG_EXTRA_NOT_INT: &str = "\
+nr_periods 256
+nr_throttled 8
+throttled_time 32
+nr_bursts invalid
+";
+
+        const CONTENT_NG_EXTRA_DUPLICATE: &str = "\
 nr_periods 256
 nr_throttled 8
 throttled_time 32
-invalid 256
+nr_bursts 4
+nr_bursts 4
 ";
 
         for case in &[
             CONTENT_NG_NOT_INT,
             CONTENT_NG_MISSING_DATA,
             CONTENT_NG_EXTRA_DATA,
-            CONTENT_NG_EXTRA_ROW,
+            CONTENT_NG_EXTRA_NOT_INT,
+            CONTENT_NG_EXTRA_DUPLICATE,
         ] {
             assert_eq!(
                 parse_stat(case.as_bytes()).unwrap_err().kind(),
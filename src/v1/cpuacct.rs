@@ -47,7 +47,7 @@ use std::{
 
 use crate::{
     parse::{parse, parse_next, parse_vec},
-    v1::{self, cgroup::CgroupHelper, Cgroup, CgroupPath},
+    v1::{self, cgroup::CgroupHelper, CgroupPath},
     Error, Result,
 };
 
@@ -59,6 +59,7 @@ pub struct Subsystem {
 
 /// Statistics about how much CPU time is consumed by tasks in a cgroup.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Stat {
     /// CPU time consumed in the system (kernel) mode.
     pub system: u64,
@@ -73,6 +74,11 @@ impl_cgroup! {
     fn apply(&mut self, _resources: &v1::Resources) -> Result<()> {
         Ok(())
     }
+
+    /// Returns the default `Resources` as a Cpuacct subsystem has no parameters to apply.
+    fn resources(&self) -> Result<v1::Resources> {
+        Ok(v1::Resources::default())
+    }
 }
 
 macro_rules! _gen_getter {
@@ -238,7 +244,7 @@ fn parse_usage_all(reader: impl io::Read) -> Result<Vec<Stat>> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ErrorKind;
+    use crate::{v1::Cgroup, ErrorKind};
 
     #[test]
     #[rustfmt::skip]
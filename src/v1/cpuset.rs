@@ -65,6 +65,8 @@ pub struct Subsystem {
 ///
 /// See the kernel's documentation for more information about the fields.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Resources {
     /// Set of CPUs the tasks of the cgroup can run on.
     pub cpus: Option<IdSet>,
@@ -218,11 +220,41 @@ impl_cgroup! {
 
         Ok(())
     }
+
+    /// Reads `resources.cpuset` from this cgroup. `memory_pressure_enabled` is `None` unless this
+    /// cgroup is the root cgroup.
+    fn resources(&self) -> Result<v1::Resources> {
+        let memory_pressure_enabled = if self.is_root() {
+            Some(self.memory_pressure_enabled()?)
+        } else {
+            None
+        };
+
+        Ok(self::Resources {
+            cpus: Some(self.cpus()?),
+            mems: Some(self.mems()?),
+            memory_migrate: Some(self.memory_migrate()?),
+            cpu_exclusive: Some(self.cpu_exclusive()?),
+            mem_exclusive: Some(self.mem_exclusive()?),
+            mem_hardwall: Some(self.mem_hardwall()?),
+            memory_pressure_enabled,
+            memory_spread_page: Some(self.memory_spread_page()?),
+            memory_spread_slab: Some(self.memory_spread_slab()?),
+            sched_load_balance: Some(self.sched_load_balance()?),
+            sched_relax_domain_level: Some(self.sched_relax_domain_level()?),
+        }
+        .into())
+    }
 }
 
 macro_rules! _gen_getter {
-    ($desc: literal, $field: ident $( : $link : ident )?, $ty: ty, $parser: ident) => {
-        gen_getter!(cpuset, $desc, $field $( : $link )?, $ty, $parser);
+    (
+        $desc: literal $( : $detail: literal )?,
+        $field: ident $( : $link : ident )?,
+        $ty: ty,
+        $parser: ident
+    ) => {
+        gen_getter!(cpuset, $desc $( : $detail )?, $field $( : $link )?, $ty, $parser);
     };
 }
 
@@ -279,6 +311,22 @@ impl Subsystem {
         &"0,1".parse::<cpuset::IdSet>()?
     );
 
+    _gen_getter!(
+        "the set of CPUs this cgroup can actually use,"
+        : "i.e. `cpus` restricted by the CPUs available to the parent cgroup and online.",
+        effective_cpus,
+        IdSet,
+        parse
+    );
+
+    _gen_getter!(
+        "the set of memory nodes this cgroup can actually use,"
+        : "i.e. `mems` restricted by the memory nodes available to the parent cgroup and online.",
+        effective_mems,
+        IdSet,
+        parse
+    );
+
     _gen_getter!(
         "whether the memory used by this cgroup should be migrated
          when memory selection is updated,",
@@ -365,8 +413,7 @@ failed to read and parse `cpuset.memory_pressure_enabled` file.
         gen_doc!(eg_read; cpuset, memory_pressure_enabled)),
         pub fn memory_pressure_enabled(&self) -> Result<bool> {
             if self.is_root() {
-                self.open_file_read(MEMORY_PRESSURE_ENABLED)
-                    .and_then(parse_01_bool)
+                self.read_file(MEMORY_PRESSURE_ENABLED, parse_01_bool)
             } else {
                 Err(Error::new(ErrorKind::InvalidOperation))
             }
@@ -483,7 +530,7 @@ error if failed to write to `cpuset.sched_relax_domain_level` file of this cgrou
         gen_doc!(err_read; "cgroup.clone_children"),
         gen_doc!(eg_read; cpuset, clone_children)),
         pub fn clone_children(&self) -> Result<bool> {
-            self.open_file_read(CLONE_CHILDREN).and_then(parse_01_bool)
+            self.read_file(CLONE_CHILDREN, parse_01_bool)
         }
     }
 
@@ -623,6 +670,9 @@ impl fmt::Display for IdSet {
     }
 }
 
+#[cfg(feature = "serde")]
+impl_serde_str!(IdSet);
+
 impl IdSet {
     /// Creates a new empty set of cpuset IDs.
     ///
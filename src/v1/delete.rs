@@ -0,0 +1,395 @@
+use std::{error::Error as StdError, fmt, io, path::Path, thread, time::Duration};
+
+use crate::{
+    file,
+    parse::parse,
+    v1::{Cgroup, SubsystemKind},
+    Error, ErrorKind, Pid, Result,
+};
+
+/// How to handle tasks remaining in cgroups deleted by [`Cgroup::delete_recursive`] and
+/// [`UnifiedRepr::delete_recursive`].
+///
+/// [`Cgroup::delete_recursive`]: trait.Cgroup.html#method.delete_recursive
+/// [`UnifiedRepr::delete_recursive`]: struct.UnifiedRepr.html#method.delete_recursive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Drain {
+    /// Moves the remaining processes to the parent of the deleted cgroup.
+    Parent,
+    /// Moves the remaining processes to the root cgroup, via [`Cgroup::remove_proc`].
+    ///
+    /// [`Cgroup::remove_proc`]: trait.Cgroup.html#method.remove_proc
+    Root,
+    /// Kills the remaining processes with `SIGKILL`.
+    ///
+    /// If the cgroup is of the freezer subsystem, the processes are frozen before being killed so
+    /// that they cannot fork new processes in the meantime. The cgroup is thawed afterwards even on
+    /// failure, and an error with kind [`ErrorKind::Timeout`] is returned if the cgroup does not
+    /// become `FROZEN` within the retries of [`DeleteOptions`].
+    ///
+    /// [`ErrorKind::Timeout`]: ../enum.ErrorKind.html#variant.Timeout
+    /// [`DeleteOptions`]: struct.DeleteOptions.html
+    Kill,
+}
+
+/// Options for [`Cgroup::delete_recursive`] and [`UnifiedRepr::delete_recursive`].
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use controlgroup::v1::{DeleteOptions, Drain};
+///
+/// let options = DeleteOptions {
+///     drain: Drain::Kill,
+///     retries: 3,
+///     ..DeleteOptions::default()
+/// };
+/// assert_eq!(options.retry_interval, Duration::from_millis(10));
+/// ```
+///
+/// [`Cgroup::delete_recursive`]: trait.Cgroup.html#method.delete_recursive
+/// [`UnifiedRepr::delete_recursive`]: struct.UnifiedRepr.html#method.delete_recursive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeleteOptions {
+    /// How to handle the remaining tasks. Defaults to `Drain::Parent`.
+    pub drain: Drain,
+    /// Number of times to retry deleting a cgroup when the system reports that it is busy (`EBUSY`),
+    /// e.g. because a killed task has not exited yet. Defaults to 10.
+    pub retries: u32,
+    /// Interval before the first retry. The interval is doubled after each retry. Defaults to 10
+    /// ms.
+    pub retry_interval: Duration,
+}
+
+impl Default for DeleteOptions {
+    fn default() -> Self {
+        Self {
+            drain: Drain::Parent,
+            retries: 10,
+            retry_interval: Duration::from_millis(10),
+        }
+    }
+}
+
+/// Failures in deleting cgroups of some subsystems, returned from
+/// [`UnifiedRepr::delete_recursive`] as the source of an [`Error`].
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> controlgroup::Result<()> {
+/// use std::path::PathBuf;
+/// use controlgroup::v1::{DeleteError, DeleteOptions, UnifiedRepr};
+///
+/// let mut cgroups = UnifiedRepr::new(PathBuf::from("students/charlie"));
+///
+/// if let Err(e) = cgroups.delete_recursive(&DeleteOptions::default()) {
+///     let source = std::error::Error::source(&e).and_then(|s| s.downcast_ref::<DeleteError>());
+///     for (kind, err) in source.unwrap().failures() {
+///         eprintln!("{}: {}", kind, err);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`UnifiedRepr::delete_recursive`]: struct.UnifiedRepr.html#method.delete_recursive
+/// [`Error`]: ../struct.Error.html
+#[derive(Debug)]
+pub struct DeleteError {
+    failures: Vec<(SubsystemKind, Error)>,
+}
+
+impl DeleteError {
+    /// Returns the list of subsystems that failed to be deleted, with their errors.
+    pub fn failures(&self) -> &[(SubsystemKind, Error)] {
+        &self.failures
+    }
+
+    /// Returns `Ok(())` if `failures` is empty. Otherwise, returns an error with the kind of the
+    /// first failure and `DeleteError` as its source.
+    pub(crate) fn check(failures: Vec<(SubsystemKind, Error)>) -> Result<()> {
+        match failures.first() {
+            None => Ok(()),
+            Some((_, e)) => Err(Error::with_source(e.kind(), Self { failures })),
+        }
+    }
+}
+
+impl StdError for DeleteError {}
+
+impl fmt::Display for DeleteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to delete cgroups of some subsystems (")?;
+        for (i, (kind, err)) in self.failures.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}: {}", kind, err)?;
+        }
+        f.write_str(")")
+    }
+}
+
+pub(crate) fn delete_recursive<C: Cgroup>(cgroup: &C, options: &DeleteOptions) -> Result<()> {
+    if cgroup.is_root() {
+        return Err(Error::new(ErrorKind::InvalidOperation));
+    }
+
+    let mut parent = match options.drain {
+        Drain::Parent => cgroup.parent(),
+        Drain::Root | Drain::Kill => None,
+    };
+
+    let subtree = cgroup.walk().collect::<Result<Vec<_>>>()?;
+
+    if options.drain == Drain::Kill && cgroup.subsystem() == SubsystemKind::Freezer {
+        freeze_and_kill(cgroup, &subtree, options)?;
+    }
+
+    // bottom-up, i.e. children before their parent
+    for mut descendant in subtree.into_iter().rev() {
+        delete_one(&mut descendant, &mut parent, options)?;
+    }
+
+    Ok(())
+}
+
+// Deletes a cgroup without descendants, moving the remaining processes to its parent. Returns an
+// error with kind `InvalidOperation` if the cgroup has child cgroups, without deleting any of them.
+pub(crate) fn delete_leaf<C: Cgroup>(cgroup: &mut C, options: &DeleteOptions) -> Result<()> {
+    if cgroup.is_root() || !cgroup.children()?.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidOperation));
+    }
+
+    let mut parent = cgroup.parent();
+    delete_one(
+        cgroup,
+        &mut parent,
+        &DeleteOptions {
+            drain: Drain::Parent,
+            ..*options
+        },
+    )
+}
+
+fn delete_one<C: Cgroup>(
+    cgroup: &mut C,
+    parent: &mut Option<Box<C>>,
+    options: &DeleteOptions,
+) -> Result<()> {
+    let mut retries = options.retries;
+    let mut interval = options.retry_interval;
+
+    loop {
+        drain(cgroup, parent, options.drain)?;
+
+        match cgroup.delete() {
+            Err(ref e) if e.is_busy() && retries > 0 => {
+                retries -= 1;
+                thread::sleep(interval);
+                interval *= 2;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn drain<C: Cgroup>(cgroup: &mut C, parent: &mut Option<Box<C>>, drain: Drain) -> Result<()> {
+    for pid in cgroup.procs()? {
+        let result = match (drain, parent.as_mut()) {
+            (Drain::Parent, Some(parent)) => parent.add_proc(pid),
+            (Drain::Kill, _) => kill(pid),
+            _ => cgroup.remove_proc(pid),
+        };
+
+        match result {
+            // the process has exited in the meantime
+            Err(ref e) if e.errno() == Some(libc::ESRCH) => {}
+            result => result?,
+        }
+    }
+
+    Ok(())
+}
+
+fn freeze_and_kill<C: Cgroup>(cgroup: &C, subtree: &[C], options: &DeleteOptions) -> Result<()> {
+    let state = cgroup.path().join("freezer.state");
+
+    file::write(cgroup.backend(), state.clone(), "FROZEN")?;
+
+    let result = wait_frozen(cgroup, &state, options).and_then(|()| {
+        subtree.iter().try_for_each(|c| {
+            c.procs()?.into_iter().try_for_each(|pid| match kill(pid) {
+                Err(ref e) if e.errno() == Some(libc::ESRCH) => Ok(()),
+                result => result,
+            })
+        })
+    });
+
+    // Thaw on every path, so that the cgroup is not left frozen on failure. Killed tasks also
+    // exit only after being thawed.
+    let thawed = file::write(cgroup.backend(), state, "THAWED");
+
+    result.and(thawed)
+}
+
+fn wait_frozen<C: Cgroup>(cgroup: &C, state: &Path, options: &DeleteOptions) -> Result<()> {
+    let mut retries = options.retries;
+    let mut interval = options.retry_interval;
+
+    loop {
+        if file::read(cgroup.backend(), state.to_path_buf(), parse::<String, _>)? == "FROZEN" {
+            return Ok(());
+        }
+        if retries == 0 {
+            return Err(Error::new(ErrorKind::Timeout));
+        }
+
+        retries -= 1;
+        thread::sleep(interval);
+        interval *= 2;
+    }
+}
+
+fn kill(pid: Pid) -> Result<()> {
+    let pid: u32 = pid.into();
+
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::{
+        backend::{StuckFreezer, TempFake},
+        cpu, freezer, CgroupPath,
+    };
+    use std::{process::Command, sync::Arc};
+
+    #[test]
+    fn test_delete_recursive_parent() -> Result<()> {
+        let name = gen_cgroup_name!();
+        let mut cgroup = cpu::Subsystem::new(CgroupPath::new(SubsystemKind::Cpu, name.clone()));
+        cgroup.create()?;
+
+        let mut parent =
+            cpu::Subsystem::new(CgroupPath::new(SubsystemKind::Cpu, name.join("parent")));
+        parent.create()?;
+
+        let mut child = cpu::Subsystem::new(CgroupPath::new(
+            SubsystemKind::Cpu,
+            name.join("parent/child"),
+        ));
+        child.create()?;
+
+        let mut sleep = Command::new("sleep").arg("10").spawn().unwrap();
+        let pid = Pid::from(&sleep);
+        child.add_proc(pid).unwrap();
+
+        parent.delete_recursive(&DeleteOptions::default()).unwrap();
+        assert!(!parent.path().exists());
+        assert_eq!(cgroup.procs().unwrap(), vec![pid]);
+
+        sleep.kill().unwrap();
+        sleep.wait()?;
+        cgroup.delete()
+    }
+
+    #[test]
+    fn test_delete_recursive_kill() -> Result<()> {
+        let name = gen_cgroup_name!();
+        let mut cgroup =
+            freezer::Subsystem::new(CgroupPath::new(SubsystemKind::Freezer, name.clone()));
+        cgroup.create()?;
+
+        let mut child =
+            freezer::Subsystem::new(CgroupPath::new(SubsystemKind::Freezer, name.join("child")));
+        child.create()?;
+
+        let mut sleep = Command::new("sleep").arg("10").spawn().unwrap();
+        child.add_proc(Pid::from(&sleep)).unwrap();
+
+        cgroup
+            .delete_recursive(&DeleteOptions {
+                drain: Drain::Kill,
+                ..DeleteOptions::default()
+            })
+            .unwrap();
+        assert!(!cgroup.path().exists());
+
+        let status = sleep.wait()?;
+        assert!(!status.success());
+
+        Ok(())
+    }
+
+    #[test]
+    fn err_delete_recursive_freeze_timeout() -> Result<()> {
+        let name = gen_cgroup_name!();
+        let fake = TempFake::new(&name, &[SubsystemKind::Freezer])?;
+
+        let path = CgroupPath::new(SubsystemKind::Freezer, name);
+        let stuck = StuckFreezer {
+            fake: fake.backend(),
+            fail_thaw: false,
+        };
+        let mut cgroup = freezer::Subsystem::new(path.with_backend(Arc::new(stuck)));
+        cgroup.create()?;
+
+        let err = cgroup
+            .delete_recursive(&DeleteOptions {
+                drain: Drain::Kill,
+                retries: 2,
+                retry_interval: Duration::from_millis(1),
+            })
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Timeout);
+        assert_eq!(cgroup.state()?, freezer::State::Thawed);
+        assert!(cgroup.file_exists("freezer.state"));
+
+        cgroup.delete()
+    }
+
+    #[test]
+    fn err_delete_recursive() {
+        let mut root = cpu::Subsystem::new(CgroupPath::new(SubsystemKind::Cpu, "".into()));
+        assert_eq!(
+            root.delete_recursive(&DeleteOptions::default())
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidOperation
+        );
+
+        let mut cgroup =
+            cpu::Subsystem::new(CgroupPath::new(SubsystemKind::Cpu, gen_cgroup_name!()));
+        assert_eq!(
+            cgroup
+                .delete_recursive(&DeleteOptions::default())
+                .unwrap_err()
+                .kind(),
+            ErrorKind::Io
+        );
+    }
+
+    #[test]
+    fn test_delete_error() {
+        assert!(DeleteError::check(vec![]).is_ok());
+
+        let err = DeleteError::check(vec![
+            (SubsystemKind::Cpu, Error::new(ErrorKind::Io)),
+            (SubsystemKind::Pids, Error::new(ErrorKind::InvalidOperation)),
+        ])
+        .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Io);
+
+        let source = err.source().unwrap().downcast_ref::<DeleteError>().unwrap();
+        assert_eq!(source.failures().len(), 2);
+        assert_eq!(source.failures()[1].0, SubsystemKind::Pids);
+    }
+}
@@ -49,7 +49,7 @@ use std::{fmt, path::PathBuf, str::FromStr};
 
 use crate::{
     parse::parse_next,
-    v1::{self, cgroup::CgroupHelper, Cgroup, CgroupPath},
+    v1::{self, cgroup::CgroupHelper, CgroupPath},
     Error, Result,
 };
 
@@ -63,6 +63,8 @@ pub struct Subsystem {
 ///
 /// See the kernel's documentation for more information about the fields.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Resources {
     /// Deny this cgroup to perform these accesses.
     pub deny: Vec<Access>,
@@ -177,6 +179,17 @@ impl_cgroup! {
 
         Ok(())
     }
+
+    /// Reads `resources.devices` from this cgroup. `deny` list contains only an access to all
+    /// devices, and `allow` list is the allowed accesses of this cgroup, so that applying the
+    /// result reproduces the current state.
+    fn resources(&self) -> Result<v1::Resources> {
+        Ok(self::Resources {
+            deny: vec!["a".parse()?],
+            allow: self.list()?,
+        }
+        .into())
+    }
 }
 
 macro_rules! _gen_setter {
@@ -354,10 +367,13 @@ impl fmt::Display for AccessType {
     }
 }
 
+#[cfg(feature = "serde")]
+impl_serde_str!(Access, DeviceType, AccessType);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Device, DeviceNumber, ErrorKind};
+    use crate::{v1::Cgroup, Device, DeviceNumber, ErrorKind};
 
     #[test]
     fn test_subsystem_create_file_exists() -> Result<()> {
@@ -376,6 +392,25 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_subsystem_resources() -> Result<()> {
+        let mut cgroup = Subsystem::new(CgroupPath::new(
+            v1::SubsystemKind::Devices,
+            gen_cgroup_name!(),
+        ));
+        cgroup.create()?;
+
+        let resources = Resources {
+            deny: vec!["a".parse::<Access>().unwrap()],
+            allow: vec!["c 1:3 rm".parse::<Access>().unwrap()],
+        }
+        .into();
+        cgroup.apply(&resources)?;
+        assert_eq!(cgroup.resources()?, resources);
+
+        cgroup.delete()
+    }
+
     #[test]
     fn test_subsystem_list() -> Result<()> {
         let allowed_all = Access {
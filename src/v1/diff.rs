@@ -0,0 +1,262 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::{
+    v1::{self, Resources},
+    Max,
+};
+
+impl Resources {
+    /// Computes the changes from `self` to `new`, i.e. the minimal `Resources` that has the same
+    /// effect as `new` when applied to a cgroup configured with `self`.
+    ///
+    /// The result is structured in the same way as `Resources`:
+    ///
+    /// - An `Option` field is `Some` if it is `Some` in `new` and differs from `self`. If it is
+    ///   `Some` in `self` and `None` in `new`, it is set to the value that resets the setting (see
+    ///   below).
+    /// - A `HashMap` field has the entries of `new` that are absent or different in `self`, and
+    ///   the entries only in `self` with the value that removes the setting.
+    /// - `devices` is a whole `new.devices` if either of `deny` or `allow` list differs, and
+    ///   empty otherwise, because the lists are meaningful only as a whole.
+    ///
+    /// The values that reset settings are the defaults of the kernel:
+    ///
+    /// - `cpu`: `shares` is 1024, `cfs_quota_us` is -1 (no limit), `cfs_period_us` is 100,000,
+    ///   `rt_runtime_us` is 0, and `rt_period_us` is 1,000,000.
+    /// - `cpuset`: the flags are `false`, except for `sched_load_balance` which is `true`, and
+    ///   `sched_relax_domain_level` is -1.
+    /// - `memory`: the limits are -1 (no limit), and `move_charge_at_immigrate` is `false`.
+    /// - `hugetlb`: the limit is `Limit::Bytes(u64::MAX)`, which the kernel treats as no limit.
+    /// - `blkio`: the weights and throttles for devices are 0, which removes them.
+    /// - `rdma`: the limits are `Max::Max`.
+    /// - `net_prio`: the priority is 0.
+    /// - `net_cls`: `classid` is 0.
+    /// - `pids`: `max` is `Max::Max`.
+    /// - `freezer`: `state` is `State::Thawed`.
+    ///
+    /// Fields without a well-defined default (`cpuset.cpus`, `cpuset.mems`, `memory.swappiness`,
+    /// `memory.use_hierarchy`, `blkio.weight`, and `blkio.leaf_weight`) are left unchanged when
+    /// unset in `new`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use controlgroup::{Max, v1::Resources};
+    ///
+    /// let mut old = Resources::default();
+    /// old.cpu.shares = Some(1024);
+    /// old.cpu.cfs_quota_us = Some(500 * 1000);
+    /// old.pids.max = Some(Max::Limit(42));
+    ///
+    /// let mut new = old.clone();
+    /// new.cpu.shares = Some(2048);
+    /// new.pids.max = None;
+    ///
+    /// let diff = old.diff(&new);
+    /// assert_eq!(diff.cpu.shares, Some(2048));
+    /// assert_eq!(diff.cpu.cfs_quota_us, None);
+    /// assert_eq!(diff.pids.max, Some(Max::Max)); // reset
+    ///
+    /// assert_eq!(new.diff(&new), Resources::default());
+    /// ```
+    #[allow(clippy::legacy_numeric_constants)] // `u64::MAX` requires Rust 1.43
+    pub fn diff(&self, new: &Resources) -> Resources {
+        fn opt<T>(old: &Option<T>, new: &Option<T>, reset: Option<T>) -> Option<T>
+        where
+            T: Clone + PartialEq,
+        {
+            match (old, new) {
+                (_, Some(_)) if new != old => new.clone(),
+                (Some(_), None) if reset != *old => reset,
+                _ => None,
+            }
+        }
+
+        fn map<K, V>(old: &HashMap<K, V>, new: &HashMap<K, V>, reset: V) -> HashMap<K, V>
+        where
+            K: Clone + Eq + Hash,
+            V: Clone + PartialEq,
+        {
+            let changed = new.iter().filter(|(k, v)| old.get(k) != Some(v));
+            let removed = old
+                .iter()
+                .filter(|(k, v)| !new.contains_key(k) && **v != reset)
+                .map(|(k, _)| (k, &reset));
+
+            changed
+                .chain(removed)
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect()
+        }
+
+        macro_rules! d {
+            (
+                $subsystem: ident;
+                $( $opt: ident = $opt_reset: expr ),* ;
+                $( $map: ident = $map_reset: expr ),* $(, )?
+            ) => {
+                v1::$subsystem::Resources {
+                    $( $opt: opt(&self.$subsystem.$opt, &new.$subsystem.$opt, $opt_reset), )*
+                    $( $map: map(&self.$subsystem.$map, &new.$subsystem.$map, $map_reset), )*
+                }
+            };
+        }
+
+        let devices = if self.devices == new.devices {
+            v1::devices::Resources::default()
+        } else {
+            new.devices.clone()
+        };
+
+        let unlimited = v1::rdma::Limit {
+            hca_handle: Max::Max,
+            hca_object: Max::Max,
+        };
+
+        Resources {
+            cpu: d!(
+                cpu;
+                shares = Some(1024), cfs_quota_us = Some(-1), cfs_period_us = Some(100_000),
+                rt_runtime_us = Some(0), rt_period_us = Some(1_000_000);
+            ),
+            cpuset: d!(
+                cpuset;
+                cpus = None, mems = None, memory_migrate = Some(false),
+                cpu_exclusive = Some(false), mem_exclusive = Some(false),
+                mem_hardwall = Some(false), memory_pressure_enabled = Some(false),
+                memory_spread_page = Some(false), memory_spread_slab = Some(false),
+                sched_load_balance = Some(true), sched_relax_domain_level = Some(-1);
+            ),
+            memory: d!(
+                memory;
+                limit_in_bytes = Some(-1), memsw_limit_in_bytes = Some(-1),
+                kmem_limit_in_bytes = Some(-1), kmem_tcp_limit_in_bytes = Some(-1),
+                soft_limit_in_bytes = Some(-1), swappiness = None,
+                move_charge_at_immigrate = Some(false), use_hierarchy = None;
+            ),
+            hugetlb: d!(hugetlb; ; limits = v1::hugetlb::Limit::Bytes(u64::max_value())),
+            devices,
+            blkio: d!(
+                blkio;
+                weight = None, leaf_weight = None;
+                weight_device = 0, leaf_weight_device = 0, read_bps_device = 0,
+                write_bps_device = 0, read_iops_device = 0, write_iops_device = 0,
+            ),
+            rdma: d!(rdma; ; max = unlimited),
+            net_prio: d!(net_prio; ; ifpriomap = 0),
+            net_cls: d!(net_cls; classid = Some(0.into());),
+            pids: d!(pids; max = Some(Max::Max);),
+            freezer: d!(freezer; state = Some(v1::freezer::State::Thawed);),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        v1::{cpu, Cgroup, CgroupPath, SubsystemKind},
+        Device, Result,
+    };
+
+    #[test]
+    fn test_resources_diff() {
+        let sda = Device {
+            major: 8.into(),
+            minor: 0.into(),
+        };
+        let sdb = Device {
+            major: 8.into(),
+            minor: 16.into(),
+        };
+
+        let mut old = Resources::default();
+        old.cpu.shares = Some(1024);
+        old.cpu.cfs_quota_us = Some(100_000);
+        old.pids.max = Some(Max::Max);
+        old.blkio.read_bps_device = hashmap! { (sda, 1000), (sdb, 1000) };
+        old.net_prio.ifpriomap = hashmap! { ("lo".to_string(), 1) };
+        old.devices.deny = vec!["a".parse().unwrap()];
+
+        assert_eq!(old.diff(&old), Resources::default());
+
+        let mut new = old.clone();
+        new.cpu.shares = Some(2048);
+        new.cpu.cfs_quota_us = None;
+        new.memory.swappiness = Some(60);
+        new.blkio.read_bps_device = hashmap! { (sda, 1000), (sdb, 2000) };
+        new.net_prio.ifpriomap = hashmap! { ("lo".to_string(), 1), ("eth0".to_string(), 2) };
+        new.devices.allow = vec!["c 1:3 mr".parse().unwrap()];
+
+        let mut expected = Resources::default();
+        expected.cpu.shares = Some(2048);
+        expected.cpu.cfs_quota_us = Some(-1);
+        expected.memory.swappiness = Some(60);
+        expected.blkio.read_bps_device = hashmap! { (sdb, 2000) };
+        expected.net_prio.ifpriomap = hashmap! { ("eth0".to_string(), 2) };
+        expected.devices = new.devices.clone();
+
+        assert_eq!(old.diff(&new), expected);
+    }
+
+    #[test]
+    fn test_resources_diff_reset() {
+        let sda = Device {
+            major: 8.into(),
+            minor: 0.into(),
+        };
+
+        let mut old = Resources::default();
+        old.memory.limit_in_bytes = Some(1 << 30);
+        old.memory.swappiness = Some(10);
+        old.pids.max = Some(Max::Limit(42));
+        old.blkio.write_iops_device = hashmap! { (sda, 100) };
+        old.net_prio.ifpriomap = hashmap! { ("lo".to_string(), 1), ("eth0".to_string(), 0) };
+        old.rdma.max = hashmap! {
+            (
+                "mlx4_0".to_string(),
+                v1::rdma::Limit { hca_handle: Max::Limit(2), hca_object: Max::Max },
+            )
+        };
+
+        let new = Resources::default();
+
+        let mut expected = Resources::default();
+        expected.memory.limit_in_bytes = Some(-1);
+        expected.pids.max = Some(Max::Max);
+        expected.blkio.write_iops_device = hashmap! { (sda, 0) };
+        expected.net_prio.ifpriomap = hashmap! { ("lo".to_string(), 0) };
+        expected.rdma.max = hashmap! {
+            (
+                "mlx4_0".to_string(),
+                v1::rdma::Limit { hca_handle: Max::Max, hca_object: Max::Max },
+            )
+        };
+
+        assert_eq!(old.diff(&new), expected);
+        assert_ne!(old.diff(&new), Resources::default());
+    }
+
+    #[test]
+    fn test_apply_diff() -> Result<()> {
+        let mut cgroup =
+            cpu::Subsystem::new(CgroupPath::new(SubsystemKind::Cpu, gen_cgroup_name!()));
+        cgroup.create()?;
+
+        let old = cgroup.resources()?;
+
+        let mut new = old.clone();
+        new.cpu.shares = Some(42);
+        new.cpu.cfs_quota_us = Some(50_000);
+        cgroup.apply_diff(&old, &new)?;
+        assert_eq!(cgroup.resources()?, new);
+
+        // lifts the quota
+        let mut lifted = new.clone();
+        lifted.cpu.cfs_quota_us = None;
+        cgroup.apply_diff(&new, &lifted)?;
+        assert_eq!(cgroup.cfs_quota_us()?, -1);
+
+        cgroup.delete()
+    }
+}
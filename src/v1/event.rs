@@ -0,0 +1,152 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    os::unix::io::{AsRawFd, FromRawFd, RawFd},
+};
+
+use crate::{file, v1::Cgroup, Result};
+
+/// Listener of events notified by a cgroup through an eventfd.
+///
+/// An `EventListener` is registered to a cgroup via `cgroup.event_control` file, e.g. by
+/// [`memory::Subsystem::notify_oom`]. The registration is removed when the listener is dropped,
+/// as the eventfd is closed.
+///
+/// `EventListener` implements [`AsRawFd`], so that the eventfd can be integrated into your own
+/// event loop, e.g. using `epoll`. The eventfd becomes readable when an event is notified.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> controlgroup::Result<()> {
+/// use std::path::PathBuf;
+/// use controlgroup::v1::{memory, Cgroup, CgroupPath, SubsystemKind};
+///
+/// let mut cgroup = memory::Subsystem::new(
+///     CgroupPath::new(SubsystemKind::Memory, PathBuf::from("students/charlie")));
+/// cgroup.create()?;
+///
+/// let listener = cgroup.notify_oom()?;
+///
+/// // Blocks until the cgroup hits OOM.
+/// let count = listener.wait()?;
+/// println!("OOM occurred {} time(s)", count);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`memory::Subsystem::notify_oom`]: memory/struct.Subsystem.html#method.notify_oom
+/// [`AsRawFd`]: https://doc.rust-lang.org/std/os/unix/io/trait.AsRawFd.html
+#[derive(Debug)]
+pub struct EventListener {
+    eventfd: File,
+}
+
+impl EventListener {
+    /// Blocks until an event is notified, and returns the number of events notified since the
+    /// last call to `wait` or [`try_wait`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to read the eventfd, with kind [`ErrorKind::Io`].
+    ///
+    /// [`try_wait`]: #method.try_wait
+    /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
+    pub fn wait(&self) -> Result<u64> {
+        Ok(self.read()?)
+    }
+
+    /// Returns the number of events notified since the last call to [`wait`] or `try_wait`, or
+    /// `None` if no event has been notified. This method does not block.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to poll or read the eventfd, with kind [`ErrorKind::Io`].
+    ///
+    /// [`wait`]: #method.wait
+    /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
+    pub fn try_wait(&self) -> Result<Option<u64>> {
+        let mut pollfd = libc::pollfd {
+            fd: self.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+
+        match unsafe { libc::poll(&mut pollfd, 1, 0) } {
+            -1 => Err(io::Error::last_os_error().into()),
+            0 => Ok(None),
+            _ => self.wait().map(Some),
+        }
+    }
+
+    /// Reads the event counter from the eventfd. If the eventfd is non-blocking and no event has
+    /// been notified, returns an error with kind `io::ErrorKind::WouldBlock`.
+    pub(crate) fn read(&self) -> io::Result<u64> {
+        let mut buf = [0; 8];
+        (&self.eventfd).read_exact(&mut buf)?;
+        Ok(u64::from_ne_bytes(buf))
+    }
+}
+
+impl AsRawFd for EventListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.eventfd.as_raw_fd()
+    }
+}
+
+/// Registers a new eventfd to `control_file` of `cgroup` via `cgroup.event_control` file, with
+/// optional `args`.
+pub(crate) fn register<C: Cgroup>(
+    cgroup: &mut C,
+    control_file: &str,
+    args: Option<&str>,
+) -> Result<EventListener> {
+    let fd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC) };
+    if fd == -1 {
+        return Err(io::Error::last_os_error().into());
+    }
+    let eventfd = unsafe { File::from_raw_fd(fd) };
+
+    // The kernel does not need the control file to be kept open after the registration.
+    let control = cgroup.open_file_read(control_file)?;
+
+    let line = match args {
+        Some(args) => format!("{} {} {}", fd, control.as_raw_fd(), args),
+        None => format!("{} {}", fd, control.as_raw_fd()),
+    };
+    file::write(
+        cgroup.backend(),
+        cgroup.path().join("cgroup.event_control"),
+        line,
+    )?;
+
+    Ok(EventListener { eventfd })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::{memory, CgroupPath, SubsystemKind};
+    use std::io::Write;
+
+    #[test]
+    fn test_event_listener() -> Result<()> {
+        let mut cgroup =
+            memory::Subsystem::new(CgroupPath::new(SubsystemKind::Memory, gen_cgroup_name!()));
+        cgroup.create()?;
+
+        let listener = register(&mut cgroup, "memory.oom_control", None)?;
+        assert_eq!(listener.try_wait()?, None);
+
+        // notify events manually
+        (&listener.eventfd).write_all(&2_u64.to_ne_bytes())?;
+        assert_eq!(listener.try_wait()?, Some(2));
+        assert_eq!(listener.try_wait()?, None);
+
+        (&listener.eventfd).write_all(&1_u64.to_ne_bytes())?;
+        assert_eq!(listener.wait()?, 1);
+
+        drop(listener);
+        cgroup.delete()
+    }
+}
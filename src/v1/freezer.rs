@@ -43,7 +43,12 @@
 //!
 //! [Documentation/cgroup-v1/freezer-subsystem.txt]: https://www.kernel.org/doc/Documentation/cgroup-v1/freezer-subsystem.txt
 
-use std::{fmt, path::PathBuf};
+use std::{
+    fmt,
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
 
 use crate::{
     parse::{parse, parse_01_bool},
@@ -61,6 +66,8 @@ pub struct Subsystem {
 ///
 /// See the kernel's documentation for more information about the field.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Resources {
     /// If `State::Frozen`, tasks in this cgroup will be frozen. If `State::Thawed`, they will be
     /// thawed. Note that applying `State::Freezing` is invalid, and `apply` will raise an error.
@@ -127,6 +134,17 @@ impl_cgroup! {
             None => Ok(())
         }
     }
+
+    /// Reads `resources.freezer.state` from this cgroup. `State::Freezing` is read as
+    /// `State::Frozen`, as the tasks are in processes of being frozen.
+    fn resources(&self) -> Result<v1::Resources> {
+        let state = match self.state()? {
+            State::Freezing => State::Frozen,
+            s => s,
+        };
+
+        Ok(self::Resources { state: Some(state) }.into())
+    }
 }
 
 macro_rules! _gen_setter {
@@ -170,6 +188,104 @@ impl Subsystem {
 
     _gen_setter!("Freezes", freeze, State::Frozen);
     _gen_setter!("Thaws, i.e. un-freezes", thaw, State::Thawed);
+
+    /// Freezes tasks in this cgroup and its descendants, and blocks until all of them are
+    /// actually frozen, or `timeout` elapses.
+    ///
+    /// Writing `FROZEN` to `freezer.state` file only starts freezing; the cgroup is
+    /// `State::Freezing` until all tasks are frozen, which may take long, or never complete if a
+    /// task is in uninterruptible sleep. This method polls `freezer.state` file of this cgroup
+    /// and all its descendants with exponential backoff, until all of them become
+    /// `State::Frozen`.
+    ///
+    /// If `timeout` elapses or waiting fails, this cgroup is thawed back unless it had been
+    /// already frozen (i.e. `self_freezing` was `true`) when this method was called, and an error
+    /// is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`ErrorKind::Timeout`] if the cgroup is not frozen within
+    /// `timeout`. If thawing the cgroup back failed too, the source of the error is that failure.
+    ///
+    /// Returns an error with kind [`ErrorKind::InvalidOperation`] if this cgroup is thawed by
+    /// someone else while waiting, i.e. both `self_freezing` and `parent_freezing` become `false`.
+    ///
+    /// Returns an error if failed to read or write `freezer.state`, `freezer.self_freezing` or
+    /// `freezer.parent_freezing` file of this cgroup or its descendants, with kind
+    /// [`ErrorKind::Io`] or [`ErrorKind::Parse`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::{path::PathBuf, time::Duration};
+    /// use controlgroup::v1::{freezer, Cgroup, CgroupPath, SubsystemKind};
+    ///
+    /// let mut cgroup = freezer::Subsystem::new(
+    ///     CgroupPath::new(SubsystemKind::Freezer, PathBuf::from("students/charlie")));
+    ///
+    /// cgroup.freeze_and_wait(Duration::from_secs(1))?;
+    /// assert_eq!(cgroup.state()?, freezer::State::Frozen);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ErrorKind::Timeout`]: ../../enum.ErrorKind.html#variant.Timeout
+    /// [`ErrorKind::InvalidOperation`]: ../../enum.ErrorKind.html#variant.InvalidOperation
+    /// [`ErrorKind::Io`]: ../../enum.ErrorKind.html#variant.Io
+    /// [`ErrorKind::Parse`]: ../../enum.ErrorKind.html#variant.Parse
+    pub fn freeze_and_wait(&mut self, timeout: Duration) -> Result<()> {
+        let already_frozen = self.self_freezing()?;
+        self.freeze()?;
+
+        let result = self.wait_frozen(timeout);
+        if result.is_ok() || already_frozen {
+            return result;
+        }
+
+        match (result, self.thaw()) {
+            (Err(e), Err(thaw)) if e.kind() == ErrorKind::Timeout => {
+                Err(Error::with_source(ErrorKind::Timeout, thaw))
+            }
+            (result, _) => result,
+        }
+    }
+
+    fn wait_frozen(&self, timeout: Duration) -> Result<()> {
+        const MIN_INTERVAL: Duration = Duration::from_millis(1);
+        const MAX_INTERVAL: Duration = Duration::from_millis(100);
+
+        let deadline = Instant::now() + timeout;
+        let mut interval = MIN_INTERVAL;
+
+        loop {
+            if self.is_subtree_frozen()? {
+                return Ok(());
+            }
+
+            if !self.self_freezing()? && !self.parent_freezing()? {
+                return Err(Error::new(ErrorKind::InvalidOperation));
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(Error::new(ErrorKind::Timeout));
+            }
+
+            thread::sleep(std::cmp::min(interval, deadline - now));
+            interval = std::cmp::min(interval * 2, MAX_INTERVAL);
+        }
+    }
+
+    fn is_subtree_frozen(&self) -> Result<bool> {
+        for cgroup in self.walk() {
+            if cgroup?.state()? != State::Frozen {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
 }
 
 impl Into<v1::Resources> for Resources {
@@ -206,6 +322,9 @@ impl fmt::Display for State {
     }
 }
 
+#[cfg(feature = "serde")]
+impl_serde_str!(State);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -281,4 +400,66 @@ mod tests {
         child.delete()?;
         parent.delete()
     }
+
+    #[test]
+    fn test_subsystem_freeze_and_wait() -> Result<()> {
+        use std::process::Command;
+
+        let name = gen_cgroup_name!();
+
+        let mut child = Subsystem::new(CgroupPath::new(SubsystemKind::Freezer, name.join("child")));
+        let mut parent = Subsystem::new(CgroupPath::new(SubsystemKind::Freezer, name));
+
+        parent.create()?;
+        child.create()?;
+
+        let mut sleep = Command::new("sleep").arg("10").spawn().unwrap();
+        child.add_proc(&sleep).unwrap();
+
+        parent.freeze_and_wait(Duration::from_secs(10)).unwrap();
+        assert_eq!(parent.state().unwrap(), State::Frozen);
+        assert_eq!(child.state().unwrap(), State::Frozen);
+        assert!(child.parent_freezing().unwrap());
+
+        // already frozen
+        parent.freeze_and_wait(Duration::from_secs(0)).unwrap();
+
+        parent.thaw().unwrap();
+        assert_eq!(child.state().unwrap(), State::Thawed);
+
+        sleep.kill().unwrap();
+        sleep.wait()?;
+
+        child.delete()?;
+        parent.delete()
+    }
+
+    #[test]
+    fn err_subsystem_freeze_and_wait() -> Result<()> {
+        use std::{error::Error as _, sync::Arc};
+        use v1::backend::{StuckFreezer, TempFake};
+
+        let name = gen_cgroup_name!();
+
+        for &fail_thaw in &[false, true] {
+            let fake = TempFake::new(&name, &[SubsystemKind::Freezer])?;
+
+            let path = CgroupPath::new(SubsystemKind::Freezer, name.clone());
+            let stuck = StuckFreezer {
+                fake: fake.backend(),
+                fail_thaw,
+            };
+            let mut cgroup = Subsystem::new(path.with_backend(Arc::new(stuck)));
+            cgroup.create()?;
+
+            let err = cgroup
+                .freeze_and_wait(Duration::from_millis(10))
+                .unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::Timeout);
+            assert_eq!(err.source().is_some(), fail_thaw);
+            assert_eq!(cgroup.self_freezing()?, fail_thaw);
+        }
+
+        Ok(())
+    }
 }
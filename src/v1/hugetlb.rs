@@ -52,7 +52,7 @@ use std::{collections::HashMap, fmt, path::PathBuf};
 use crate::{
     parse::parse,
     v1::{self, cgroup::CgroupHelper, Cgroup, CgroupPath},
-    Result,
+    Error, Result,
 };
 
 /// Handler of a HugeTLB subsystem.
@@ -65,6 +65,8 @@ pub struct Subsystem {
 ///
 /// See the kernel's documentation for more information about the fields.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Resources {
     /// How many hugepage TLBs this cgroup can use for each hugepage size.
     pub limits: HashMap<HugepageSize, Limit>,
@@ -72,6 +74,7 @@ pub struct Resources {
 
 /// Limit on hugepage TLB usage in different units.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Limit {
     /// Limit hugepage TLB usage in bytes.
     Bytes(u64),
@@ -80,6 +83,36 @@ pub enum Limit {
 }
 
 /// Hugepage sizes.
+///
+/// `HugepageSize` implements [`FromStr`], so you can [`parse`] a string into a `HugepageSize`. If
+/// failed, `parse` returns an error with kind [`ErrorKind::Parse`].
+///
+/// ```
+/// use controlgroup::v1::hugetlb::HugepageSize;
+///
+/// let mb2 = "2MB".parse::<HugepageSize>().unwrap();
+/// assert_eq!(mb2, HugepageSize::Mb2);
+///
+/// let gb1 = "1GB".parse::<HugepageSize>().unwrap();
+/// assert_eq!(gb1, HugepageSize::Gb1);
+/// ```
+///
+/// `HugepageSize` also implements [`Display`]. The resulting string is in the same form as in the
+/// names of hugetlb files (e.g. `hugetlb.2MB.limit_in_bytes`).
+///
+/// ```
+/// use std::string::ToString;
+/// use controlgroup::v1::hugetlb::HugepageSize;
+///
+/// assert_eq!(HugepageSize::Mb2.to_string(), "2MB");
+/// assert_eq!(HugepageSize::Gb1.to_string(), "1GB");
+/// ```
+///
+/// [`FromStr`]: https://doc.rust-lang.org/std/str/trait.FromStr.html
+/// [`parse`]: https://doc.rust-lang.org/std/primitive.str.html#method.parse
+/// [`ErrorKind::Parse`]: ../../enum.ErrorKind.html#variant.Parse
+///
+/// [`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum HugepageSize {
     /// 8 KB hugepage.
@@ -102,6 +135,18 @@ pub enum HugepageSize {
     Gb1,
 }
 
+pub(crate) const SIZES: [HugepageSize; 9] = [
+    HugepageSize::Kb8,
+    HugepageSize::Kb64,
+    HugepageSize::Kb256,
+    HugepageSize::Mb1,
+    HugepageSize::Mb2,
+    HugepageSize::Mb4,
+    HugepageSize::Mb16,
+    HugepageSize::Mb256,
+    HugepageSize::Gb1,
+];
+
 impl_cgroup! {
     Subsystem, HugeTlb,
 
@@ -113,6 +158,17 @@ impl_cgroup! {
 
         Ok(())
     }
+
+    /// Reads `resources.hugetlb.limits` from this cgroup, in bytes for each hugepage size
+    /// supported by the system.
+    fn resources(&self) -> Result<v1::Resources> {
+        let mut limits = HashMap::new();
+        for &size in SIZES.iter().filter(|&&s| self.size_supported(s)) {
+            limits.insert(size, Limit::Bytes(self.limit_in_bytes(size)?));
+        }
+
+        Ok(self::Resources { limits }.into())
+    }
 }
 
 macro_rules! _gen_getter {
@@ -123,8 +179,7 @@ macro_rules! _gen_getter {
             gen_doc!(err_read; subsys_file!("hugetlb.<hugepage size>", $in_bytes)),
             gen_doc!(eg_read; hugetlb, $in_bytes, hugetlb::HugepageSize::Mb2)),
             pub fn $in_bytes(&self, size: HugepageSize) -> Result<u64> {
-                self.open_file_read(&format!("hugetlb.{}.{}", size, stringify!($in_bytes)))
-                    .and_then(parse)
+                self.read_file(&format!("hugetlb.{}.{}", size, stringify!($in_bytes)), parse)
             }
         }
 
@@ -233,8 +288,7 @@ impl Subsystem {
         gen_doc!(err_read; "hugetlb.<hugepage size>.failcnt"),
         gen_doc!(eg_read; hugetlb, failcnt, hugetlb::HugepageSize::Mb2)),
         pub fn failcnt(&self, size: HugepageSize) -> Result<u64> {
-            self.open_file_read(&format!("hugetlb.{}.{}", size, FAILCNT))
-                .and_then(parse)
+            self.read_file(&format!("hugetlb.{}.{}", size, FAILCNT), parse)
         }
     }
 }
@@ -265,7 +319,7 @@ impl HugepageSize {
         }
     }
 
-    fn pages_to_bytes(self, pages: u64) -> u64 {
+    pub(crate) fn pages_to_bytes(self, pages: u64) -> u64 {
         match self {
             Self::Kb8 => pages * (8 << 10),
             Self::Kb64 => pages * (64 << 10),
@@ -282,6 +336,27 @@ impl HugepageSize {
     }
 }
 
+impl std::str::FromStr for HugepageSize {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "8KB" => Ok(Self::Kb8),
+            "64KB" => Ok(Self::Kb64),
+            "256KB" => Ok(Self::Kb256),
+            "1MB" => Ok(Self::Mb1),
+            "2MB" => Ok(Self::Mb2),
+            "4MB" => Ok(Self::Mb4),
+            "16MB" => Ok(Self::Mb16),
+            "256MB" => Ok(Self::Mb256),
+            "1GB" => Ok(Self::Gb1),
+            _ => {
+                bail_parse!();
+            }
+        }
+    }
+}
+
 impl fmt::Display for HugepageSize {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -302,6 +377,9 @@ impl fmt::Display for HugepageSize {
     }
 }
 
+#[cfg(feature = "serde")]
+impl_serde_str!(HugepageSize);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -347,6 +425,22 @@ mod tests {
         cgroup.delete()
     }
 
+    #[test]
+    fn test_hugepage_size_from_str() {
+        for &size in SIZES.iter() {
+            assert_eq!(size.to_string().parse::<HugepageSize>().unwrap(), size);
+        }
+
+        assert_eq!(
+            "Mb2".parse::<HugepageSize>().unwrap_err().kind(),
+            crate::ErrorKind::Parse
+        );
+        assert_eq!(
+            "2M".parse::<HugepageSize>().unwrap_err().kind(),
+            crate::ErrorKind::Parse
+        );
+    }
+
     #[test]
     fn test_subsystem_size_supported() -> Result<()> {
         let mut cgroup =
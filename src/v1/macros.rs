@@ -84,7 +84,7 @@ macro_rules! gen_getter {
         gen_doc!(err_read; subsys_file!($subsys, $field)),
         gen_doc!(eg_read; $subsys, $field)),
         pub fn $field(&self) -> Result<$ty> {
-            self.open_file_read(subsys_file!($subsys, $field)).and_then($parser)
+            self.read_file(subsys_file!($subsys, $field), $parser)
         }
     } };
 
@@ -101,7 +101,7 @@ macro_rules! gen_getter {
         gen_doc!(err_read; $file),
         gen_doc!(eg_read; cpu, $getter)),
         fn $getter(&self) -> Result<$ty> {
-            self.open_file_read($file).and_then($parser)
+            crate::file::read(self.backend(), self.path().join($file), $parser)
         }
     } };
 }
@@ -49,13 +49,20 @@
 //! [Documentation/cgroup-v1/memory.txt]: https://www.kernel.org/doc/Documentation/cgroup-v1/memory.txt
 
 use std::{
+    collections::BTreeMap,
+    fmt,
     io::{self, BufRead},
     path::PathBuf,
+    str::FromStr,
 };
 
 use crate::{
     parse::{parse, parse_01_bool, parse_next},
-    v1::{self, cgroup::CgroupHelper, Cgroup, CgroupPath},
+    v1::{
+        self,
+        cgroup::{optional, CgroupHelper},
+        event, Cgroup, CgroupPath, EventListener,
+    },
     Error, ErrorKind, Result,
 };
 
@@ -69,6 +76,8 @@ pub struct Subsystem {
 ///
 /// See the kernel's documentation for more information about the fields.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Resources {
     /// Limit the memory usage of this cgroup. Setting -1 removes the current limit.
     pub limit_in_bytes: Option<i64>,
@@ -96,6 +105,7 @@ pub struct Resources {
 ///
 /// See the kernel's documentation for more information about the fields.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub struct Stat {
     pub cache: u64,
@@ -135,6 +145,11 @@ pub struct Stat {
     pub total_active_file: u64,
     pub total_inactive_file: u64,
     pub total_unevictable: u64,
+
+    /// Fields unknown to this crate and added by newer kernels (e.g. `workingset_refault`), with
+    /// their values.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extra: BTreeMap<String, u64>,
 }
 
 /// Statistics of memory usage per NUMA node.
@@ -143,6 +158,7 @@ pub struct Stat {
 ///
 /// See the kernel's documentation for more information about the fields.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub struct NumaStat {
     pub total: (u64, Vec<u64>),
@@ -154,12 +170,41 @@ pub struct NumaStat {
     pub hierarchical_file: (u64, Vec<u64>),
     pub hierarchical_anon: (u64, Vec<u64>),
     pub hierarchical_unevictable: (u64, Vec<u64>),
+
+    /// Fields unknown to this crate and added by newer kernels (e.g. `shmem`), with their values.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extra: BTreeMap<String, (u64, Vec<u64>)>,
+}
+
+/// Statistics of a slab cache, as a row of `memory.kmem.slabinfo` file.
+///
+/// See the kernel's documentation of `/proc/slabinfo` for more information about the fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SlabCache {
+    /// Name of this cache, e.g. `dentry`.
+    pub name: String,
+    /// Number of objects in use.
+    pub active_objs: u64,
+    /// Total number of allocated objects.
+    pub num_objs: u64,
+    /// Size of each object (in bytes).
+    pub objsize: u64,
+    /// Number of objects stored in each slab.
+    pub objperslab: u64,
+    /// Number of pages allocated for each slab.
+    pub pagesperslab: u64,
+    /// Number of slabs containing at least one object in use.
+    pub active_slabs: u64,
+    /// Total number of allocated slabs.
+    pub num_slabs: u64,
 }
 
 /// OOM status and controls.
 ///
 /// See the kernel's documentation for more information about the fields.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OomControl {
     /// Whether the OOM killer is disabled for this cgroup.
     pub oom_kill_disable: bool,
@@ -169,6 +214,56 @@ pub struct OomControl {
     pub oom_kill: Option<u64>,
 }
 
+/// Level of memory pressure notified by `memory.pressure_level` file.
+///
+/// `PressureLevel` implements [`FromStr`] and [`Display`]. The string representation is in lower
+/// case, as written to `cgroup.event_control` file.
+///
+/// ```
+/// use controlgroup::v1::memory::PressureLevel;
+///
+/// assert_eq!("medium".parse::<PressureLevel>().unwrap(), PressureLevel::Medium);
+/// assert_eq!(PressureLevel::Critical.to_string(), "critical");
+/// ```
+///
+/// [`FromStr`]: https://doc.rust-lang.org/std/str/trait.FromStr.html
+/// [`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PressureLevel {
+    /// The system is reclaiming memory for new allocations.
+    Low,
+    /// The system is experiencing medium memory pressure, e.g. swapping or evicting active file
+    /// caches.
+    Medium,
+    /// The system is actively thrashing, and about to run out of memory.
+    Critical,
+}
+
+/// Propagation mode of memory pressure events, as described in the kernel's documentation.
+///
+/// `PressureMode` implements [`FromStr`] and [`Display`]. The string representation is in lower
+/// case, as written to `cgroup.event_control` file.
+///
+/// ```
+/// use controlgroup::v1::memory::PressureMode;
+///
+/// assert_eq!("local".parse::<PressureMode>().unwrap(), PressureMode::Local);
+/// assert_eq!(PressureMode::Hierarchy.to_string(), "hierarchy");
+/// ```
+///
+/// [`FromStr`]: https://doc.rust-lang.org/std/str/trait.FromStr.html
+/// [`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PressureMode {
+    /// Events are notified to the listener of this cgroup, only if no listener of descendant
+    /// cgroups has already been notified.
+    Default,
+    /// Events are always notified to the listeners of this cgroup and all ancestors.
+    Hierarchy,
+    /// Events are notified only to the listener of the cgroup under pressure.
+    Local,
+}
+
 impl_cgroup! {
     Subsystem, Memory,
 
@@ -194,6 +289,32 @@ impl_cgroup! {
 
         Ok(())
     }
+
+    /// Reads `resources.memory` from this cgroup.
+    ///
+    /// The limits are `None` if this cgroup is the root cgroup, on which they cannot be set.
+    /// `memsw_limit_in_bytes`, `kmem_limit_in_bytes`, and `kmem_tcp_limit_in_bytes` are also
+    /// `None` if the kernel does not support them.
+    fn resources(&self) -> Result<v1::Resources> {
+        let mut res = self::Resources {
+            swappiness: Some(self.swappiness()?),
+            move_charge_at_immigrate: Some(self.move_charge_at_immigrate()?),
+            use_hierarchy: Some(self.use_hierarchy()?),
+            ..self::Resources::default()
+        };
+
+        if !self.is_root() {
+            // "unlimited" is represented as a large number that fits in `i64`
+            res.limit_in_bytes = Some(self.limit_in_bytes()? as i64);
+            res.memsw_limit_in_bytes = optional(self.memsw_limit_in_bytes())?.map(|l| l as i64);
+            res.kmem_limit_in_bytes = optional(self.kmem_limit_in_bytes())?.map(|l| l as i64);
+            res.kmem_tcp_limit_in_bytes =
+                optional(self.kmem_tcp_limit_in_bytes())?.map(|l| l as i64);
+            res.soft_limit_in_bytes = Some(self.soft_limit_in_bytes()? as i64);
+        }
+
+        Ok(res.into())
+    }
 }
 
 macro_rules! _gen_getter {
@@ -214,21 +335,21 @@ macro_rules! _gen_getter {
         with_doc! {
             gen_doc!(reads_see; subsys_file!("memory.memsw", $field), $field),
             pub fn $memsw(&self) -> Result<$ty> {
-                self.open_file_read(concat!("memory.memsw.", stringify!($field))).and_then(parse)
+                self.read_file(concat!("memory.memsw.", stringify!($field)), parse)
             }
         }
 
         with_doc! {
             gen_doc!(reads_see; subsys_file!("memory.kmem", $field), $field),
             pub fn $kmem(&self) -> Result<$ty> {
-                self.open_file_read(concat!("memory.kmem.", stringify!($field))).and_then(parse)
+                self.read_file(concat!("memory.kmem.", stringify!($field)), parse)
             }
         }
 
         with_doc! {
             gen_doc!(reads_see; subsys_file!("memory.kmem.tcp", $field), $field),
             pub fn $tcp(&self) -> Result<$ty> {
-                self.open_file_read(concat!("memory.kmem.tcp.", stringify!($field))).and_then(parse)
+                self.read_file(concat!("memory.kmem.tcp.", stringify!($field)), parse)
             }
         }
     };
@@ -417,6 +538,161 @@ impl Subsystem {
         true
     );
 
+    /// Registers a listener that is notified when this cgroup hits OOM, via
+    /// `cgroup.event_control` file.
+    ///
+    /// An event is notified when the OOM killer is invoked in this cgroup, or when this cgroup
+    /// becomes under OOM while the OOM killer is disabled.
+    ///
+    /// See [`EventListener`] for how to wait for events.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to create an eventfd, to open `memory.oom_control` file, or to
+    /// write to `cgroup.event_control` file of this cgroup, with kind [`ErrorKind::Io`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{memory, Cgroup, CgroupPath, SubsystemKind};
+    ///
+    /// let mut cgroup = memory::Subsystem::new(
+    ///     CgroupPath::new(SubsystemKind::Memory, PathBuf::from("students/charlie")));
+    ///
+    /// let oom = cgroup.notify_oom()?;
+    ///
+    /// if let Some(count) = oom.try_wait()? {
+    ///     println!("OOM occurred {} time(s)", count);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`EventListener`]: ../struct.EventListener.html
+    /// [`ErrorKind::Io`]: ../../enum.ErrorKind.html#variant.Io
+    pub fn notify_oom(&mut self) -> Result<EventListener> {
+        event::register(self, "memory.oom_control", None)
+    }
+
+    /// Registers a listener that is notified when this cgroup is under memory pressure of `level`,
+    /// via `memory.pressure_level` and `cgroup.event_control` files.
+    ///
+    /// An event is notified at the given level only; register a listener for each level to be
+    /// notified at multiple levels. `mode` specifies how events are propagated between this
+    /// cgroup and its ancestors. See [`PressureMode`] for more information.
+    ///
+    /// See [`EventListener`] for how to wait for events.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to create an eventfd, to open `memory.pressure_level` file, or
+    /// to write to `cgroup.event_control` file of this cgroup, with kind [`ErrorKind::Io`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{
+    ///     memory::{self, PressureLevel, PressureMode},
+    ///     Cgroup, CgroupPath, SubsystemKind,
+    /// };
+    ///
+    /// let mut cgroup = memory::Subsystem::new(
+    ///     CgroupPath::new(SubsystemKind::Memory, PathBuf::from("students/charlie")));
+    ///
+    /// let pressure = cgroup.notify_pressure_level(PressureLevel::Medium, PressureMode::Default)?;
+    ///
+    /// loop {
+    ///     pressure.wait()?;
+    ///     // Shrink caches ...
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`PressureMode`]: enum.PressureMode.html
+    /// [`EventListener`]: ../struct.EventListener.html
+    /// [`ErrorKind::Io`]: ../../enum.ErrorKind.html#variant.Io
+    pub fn notify_pressure_level(
+        &mut self,
+        level: PressureLevel,
+        mode: PressureMode,
+    ) -> Result<EventListener> {
+        event::register(
+            self,
+            "memory.pressure_level",
+            Some(&format!("{},{}", level, mode)),
+        )
+    }
+
+    /// Registers a listener that is notified when the memory usage of this cgroup crosses
+    /// `threshold` bytes in either direction, via `cgroup.event_control` file.
+    ///
+    /// To watch multiple thresholds, call this method for each threshold. Each registration is
+    /// removed when the returned listener is dropped.
+    ///
+    /// See [`EventListener`] for how to wait for events.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to create an eventfd, to open `memory.usage_in_bytes` file, or
+    /// to write to `cgroup.event_control` file of this cgroup, with kind [`ErrorKind::Io`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{memory, Cgroup, CgroupPath, SubsystemKind};
+    ///
+    /// let mut cgroup = memory::Subsystem::new(
+    ///     CgroupPath::new(SubsystemKind::Memory, PathBuf::from("students/charlie")));
+    ///
+    /// const GB: u64 = 1 << 30;
+    /// let thresholds = [3 * GB, 3 * GB + GB / 2]
+    ///     .iter()
+    ///     .map(|&t| cgroup.notify_usage_threshold(t).map(|l| (t, l)))
+    ///     .collect::<controlgroup::Result<Vec<_>>>()?;
+    ///
+    /// for (threshold, listener) in &thresholds {
+    ///     if listener.try_wait()?.is_some() {
+    ///         println!("memory usage crossed {} bytes", threshold);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`EventListener`]: ../struct.EventListener.html
+    /// [`ErrorKind::Io`]: ../../enum.ErrorKind.html#variant.Io
+    pub fn notify_usage_threshold(&mut self, threshold: u64) -> Result<EventListener> {
+        event::register(self, "memory.usage_in_bytes", Some(&threshold.to_string()))
+    }
+
+    /// Registers a listener that is notified when the total of memory and swap usage of this
+    /// cgroup crosses `threshold` bytes in either direction, via `cgroup.event_control` file.
+    ///
+    /// See [`notify_usage_threshold`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to create an eventfd, to open `memory.memsw.usage_in_bytes`
+    /// file, or to write to `cgroup.event_control` file of this cgroup, with kind
+    /// [`ErrorKind::Io`].
+    ///
+    /// [`notify_usage_threshold`]: #method.notify_usage_threshold
+    /// [`ErrorKind::Io`]: ../../enum.ErrorKind.html#variant.Io
+    pub fn notify_memsw_usage_threshold(&mut self, threshold: u64) -> Result<EventListener> {
+        event::register(
+            self,
+            "memory.memsw.usage_in_bytes",
+            Some(&threshold.to_string()),
+        )
+    }
+
     _gen_getter!(
         "whether pages may be recharged to the new cgroup when a task is moved,",
         move_charge_at_immigrate: link,
@@ -457,9 +733,125 @@ impl Subsystem {
         }
     }
 
-    // kmem.slabinfo
+    with_doc! { concat!(
+        gen_doc!(
+            reads;
+            "memory.kmem.slabinfo",
+            "the statistics of slab caches charged to this cgroup" :
+                "The file is empty on kernels that do not account slab caches per cgroup."
+        ),
+        gen_doc!(see),
+        gen_doc!(err_read; "memory.kmem.slabinfo"),
+        gen_doc!(eg_read; memory, kmem_slabinfo)),
+        pub fn kmem_slabinfo(&self) -> Result<Vec<SlabCache>> {
+            self.read_file("memory.kmem.slabinfo", parse_slabinfo)
+        }
+    }
+
+    /// Reads `memory.kmem.slabinfo` file and returns at most `n` slab caches that occupy the
+    /// largest memory, in the descending order of [`SlabCache::size_in_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to read and parse `memory.kmem.slabinfo` file of this cgroup.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{memory, Cgroup, CgroupPath, SubsystemKind};
+    ///
+    /// let cgroup = memory::Subsystem::new(
+    ///     CgroupPath::new(SubsystemKind::Memory, PathBuf::from("students/charlie")));
+    ///
+    /// for cache in cgroup.top_slab_caches(5)? {
+    ///     println!("{}: {} bytes", cache.name, cache.size_in_bytes());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`SlabCache::size_in_bytes`]: struct.SlabCache.html#method.size_in_bytes
+    pub fn top_slab_caches(&self, n: usize) -> Result<Vec<SlabCache>> {
+        let mut caches = self.kmem_slabinfo()?;
+        top_by_size(&mut caches, n);
+        Ok(caches)
+    }
+}
+
+impl SlabCache {
+    /// Returns the size of memory allocated for slabs of this cache (in bytes), i.e.
+    /// `num_slabs * pagesperslab * page_size`, saturating at `u64::MAX`.
+    ///
+    /// This includes the space wasted by slab metadata and fragmentation, which `num_objs *
+    /// objsize` does not.
+    pub fn size_in_bytes(&self) -> u64 {
+        self.num_slabs
+            .saturating_mul(self.pagesperslab)
+            .saturating_mul(page_size())
+    }
+
+    /// Returns the size of memory used by active objects in this cache (in bytes), i.e.
+    /// `active_objs * objsize`, saturating at `u64::MAX`.
+    pub fn active_size_in_bytes(&self) -> u64 {
+        self.active_objs.saturating_mul(self.objsize)
+    }
+}
+
+impl FromStr for PressureLevel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "low" => Ok(Self::Low),
+            "medium" => Ok(Self::Medium),
+            "critical" => Ok(Self::Critical),
+            _ => {
+                bail_parse!();
+            }
+        }
+    }
+}
+
+impl fmt::Display for PressureLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Low => write!(f, "low"),
+            Self::Medium => write!(f, "medium"),
+            Self::Critical => write!(f, "critical"),
+        }
+    }
 }
 
+impl FromStr for PressureMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "default" => Ok(Self::Default),
+            "hierarchy" => Ok(Self::Hierarchy),
+            "local" => Ok(Self::Local),
+            _ => {
+                bail_parse!();
+            }
+        }
+    }
+}
+
+impl fmt::Display for PressureMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::Hierarchy => write!(f, "hierarchy"),
+            Self::Local => write!(f, "local"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl_serde_str!(PressureLevel, PressureMode);
+
 impl Into<v1::Resources> for Resources {
     fn into(self) -> v1::Resources {
         v1::Resources {
@@ -478,6 +870,7 @@ fn parse_stat(reader: impl io::Read) -> Result<Stat> {
         ([ $( $key: ident ),* ], [ $( $key_opt: ident ),* ]) => {
             $( let mut $key: Option<u64> = None; )*
             $( let mut $key_opt: Option<u64> = None; )*
+            let mut extra = BTreeMap::new();
 
             for line in buf.lines() {
                 let line = line?;
@@ -496,7 +889,12 @@ fn parse_stat(reader: impl io::Read) -> Result<Stat> {
                             $key_opt = Some(parse_next(&mut entry)?);
                         }
                     )*
-                    _ => { bail_parse!(); }
+                    Some(key) => {
+                        if extra.insert(key.to_string(), parse_next(&mut entry)?).is_some() {
+                            bail_parse!();
+                        }
+                    }
+                    None => { bail_parse!(); }
                 }
 
                 if entry.next().is_some() { bail_parse!(); }
@@ -506,6 +904,7 @@ fn parse_stat(reader: impl io::Read) -> Result<Stat> {
                 Ok(Stat {
                     $( $key: $key.unwrap(), )*
                     $( $key_opt, )*
+                    extra,
                 })
             } else {
                 bail_parse!();
@@ -537,8 +936,9 @@ fn parse_numa_stat(reader: impl io::Read) -> Result<NumaStat> {
         ($key0: ident, $( $key: ident ),*) => {
             let mut $key0 = None;
             $( let mut $key = None; )*
+            let mut extra = BTreeMap::new();
 
-            g!(_parse_keys; $key0, $( $key ),*);
+            g!(_parse_keys; extra; $key0, $( $key ),*);
 
             if $( $key.is_some() && )* $key0.is_some() {
                 let $key0 = $key0.unwrap();
@@ -546,36 +946,43 @@ fn parse_numa_stat(reader: impl io::Read) -> Result<NumaStat> {
 
                 let len = $key0.1.len();
                 $( if $key.1.len() != len { bail_parse!(); } )*
+                if extra.values().any(|v: &(u64, Vec<u64>)| v.1.len() != len) { bail_parse!(); }
 
                 Ok(NumaStat {
                     $key0,
                     $( $key, )*
+                    extra,
                 })
             } else {
                 bail_parse!();
             }
         };
 
-        (_parse_keys; $( $key: ident ),*) => {
+        (_parse_keys; $extra: ident; $( $key: ident ),*) => {
             for line in buf.lines() {
                 let line = line?;
+                let mut entry = line.split(|c| c == ' ' || c == '=');
+
+                let total = parse_next(entry.by_ref().skip(1))?;
+                // FIXME: validate keys
+                let nodes = entry
+                    .skip(1)
+                    .step_by(2)
+                    .map(|n| n.parse::<u64>())
+                    .collect::<std::result::Result<Vec<_>, std::num::ParseIntError>>()?;
+
                 match line.split('=').next() {
                     $(
                         Some(stringify!($key)) => {
-                            let mut entry = line.split(|c| c == ' ' || c == '=');
-
-                            let total = parse_next(entry.by_ref().skip(1))?;
-                            // FIXME: validate keys
-                            let nodes = entry
-                                .skip(1)
-                                .step_by(2)
-                                .map(|n| n.parse::<u64>())
-                                .collect::<std::result::Result<Vec<_>, std::num::ParseIntError>>()?;
-
                             $key = Some((total, nodes));
                         }
                     )*
-                    _ => { bail_parse!(); }
+                    Some(key) => {
+                        if $extra.insert(key.to_string(), (total, nodes)).is_some() {
+                            bail_parse!();
+                        }
+                    }
+                    None => { bail_parse!(); }
                 }
             }
 
@@ -588,6 +995,90 @@ fn parse_numa_stat(reader: impl io::Read) -> Result<NumaStat> {
     }
 }
 
+fn parse_slabinfo(reader: impl io::Read) -> Result<Vec<SlabCache>> {
+    let mut lines = io::BufReader::new(reader).lines();
+
+    match lines.next() {
+        Some(header) => {
+            if header? != "slabinfo - version: 2.1" {
+                bail_parse!();
+            }
+        }
+        None => return Ok(Vec::new()),
+    }
+
+    let mut caches = Vec::new();
+
+    for line in lines {
+        let line = line?;
+        if line.starts_with('#') {
+            continue;
+        }
+
+        // name <active_objs> <num_objs> <objsize> <objperslab> <pagesperslab>
+        //     : tunables <limit> <batchcount> <sharedfactor>
+        //     : slabdata <active_slabs> <num_slabs> <sharedavail>
+        let mut entry = line.split_whitespace();
+
+        let name = match entry.next() {
+            Some(name) => name.to_string(),
+            None => {
+                bail_parse!();
+            }
+        };
+        let active_objs = parse_next(&mut entry)?;
+        let num_objs = parse_next(&mut entry)?;
+        let objsize = parse_next(&mut entry)?;
+        let objperslab = parse_next(&mut entry)?;
+        let pagesperslab = parse_next(&mut entry)?;
+
+        if entry.next() != Some(":") || entry.next() != Some("tunables") {
+            bail_parse!();
+        }
+        for _ in 0..3 {
+            parse_next::<u64, _, _>(&mut entry)?;
+        }
+
+        if entry.next() != Some(":") || entry.next() != Some("slabdata") {
+            bail_parse!();
+        }
+        let active_slabs = parse_next(&mut entry)?;
+        let num_slabs = parse_next(&mut entry)?;
+        parse_next::<u64, _, _>(&mut entry)?;
+
+        if entry.next().is_some() {
+            bail_parse!();
+        }
+
+        caches.push(SlabCache {
+            name,
+            active_objs,
+            num_objs,
+            objsize,
+            objperslab,
+            pagesperslab,
+            active_slabs,
+            num_slabs,
+        });
+    }
+
+    Ok(caches)
+}
+
+fn page_size() -> u64 {
+    // `sysconf(_SC_PAGESIZE)` never fails on Linux.
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as u64 }
+}
+
+fn top_by_size(caches: &mut Vec<SlabCache>, n: usize) {
+    caches.sort_by(|a, b| {
+        b.size_in_bytes()
+            .cmp(&a.size_in_bytes())
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    caches.truncate(n);
+}
+
 fn parse_oom_control(reader: impl io::Read) -> Result<OomControl> {
     let buf = io::BufReader::new(reader);
 
@@ -752,6 +1243,8 @@ mod tests {
                 hierarchical_file: (0, vec![0]),
                 hierarchical_anon: (0, vec![0]),
                 hierarchical_unevictable: (0, vec![0]),
+
+                extra: BTreeMap::new(),
             }
         )
     }
@@ -842,6 +1335,93 @@ mod tests {
         cgroup.delete()
     }
 
+    #[test]
+    fn test_subsystem_notify_oom() -> Result<()> {
+        use crate::v1::{CommandExt, DeleteOptions, Drain};
+        use std::process::Command;
+
+        let mut cgroup = Subsystem::new(CgroupPath::new(SubsystemKind::Memory, gen_cgroup_name!()));
+        cgroup.create()?;
+
+        let oom = cgroup.notify_oom()?;
+        assert_eq!(oom.try_wait()?, None);
+
+        cgroup.set_limit_in_bytes(8 * (1 << 20))?;
+        cgroup.set_memsw_limit_in_bytes(8 * (1 << 20))?;
+
+        // allocate 64 MiB of memory
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg("x=$(head -c 67108864 /dev/zero | tr '\\0' a)")
+            .cgroup(&mut cgroup)
+            .status()
+            .unwrap();
+        assert!(!status.success());
+
+        assert!(oom.try_wait()?.is_some());
+
+        // Killed processes may remain until they exit
+        drop(oom);
+        cgroup.delete_recursive(&DeleteOptions {
+            drain: Drain::Kill,
+            ..DeleteOptions::default()
+        })
+    }
+
+    #[test]
+    fn test_subsystem_notify_usage_threshold() -> Result<()> {
+        use crate::v1::CommandExt;
+        use std::process::Command;
+
+        const MB: u64 = 1 << 20;
+
+        let mut cgroup = Subsystem::new(CgroupPath::new(SubsystemKind::Memory, gen_cgroup_name!()));
+        cgroup.create()?;
+
+        let low = cgroup.notify_usage_threshold(MB)?;
+        let high = cgroup.notify_usage_threshold(1024 * MB)?;
+        let memsw = cgroup.notify_memsw_usage_threshold(MB)?;
+
+        // allocate 16 MiB of memory
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg("x=$(head -c 16777216 /dev/zero | tr '\\0' a)")
+            .cgroup(&mut cgroup)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        assert!(low.try_wait()?.is_some());
+        assert!(memsw.try_wait()?.is_some());
+        assert_eq!(high.try_wait()?, None);
+
+        drop((low, high, memsw));
+        cgroup.delete()
+    }
+
+    #[test]
+    fn test_subsystem_notify_pressure_level() -> Result<()> {
+        let mut cgroup = Subsystem::new(CgroupPath::new(SubsystemKind::Memory, gen_cgroup_name!()));
+        cgroup.create()?;
+
+        for &level in &[
+            PressureLevel::Low,
+            PressureLevel::Medium,
+            PressureLevel::Critical,
+        ] {
+            for &mode in &[
+                PressureMode::Default,
+                PressureMode::Hierarchy,
+                PressureMode::Local,
+            ] {
+                let listener = cgroup.notify_pressure_level(level, mode)?;
+                assert_eq!(listener.try_wait()?, None);
+            }
+        }
+
+        cgroup.delete()
+    }
+
     #[test]
     fn test_subsystem_move_charge_at_immigrate() -> Result<()> {
         gen_subsystem_test!(
@@ -879,6 +1459,23 @@ mod tests {
         cgroup.delete()
     }
 
+    #[test]
+    fn test_subsystem_kmem_slabinfo() -> Result<()> {
+        let mut cgroup = Subsystem::new(CgroupPath::new(SubsystemKind::Memory, gen_cgroup_name!()));
+        cgroup.create()?;
+
+        let caches = cgroup.kmem_slabinfo()?;
+        assert!(caches.iter().all(|c| c.active_objs <= c.num_objs));
+
+        let top = cgroup.top_slab_caches(3)?;
+        assert!(top.len() <= 3);
+        assert!(top
+            .windows(2)
+            .all(|w| w[0].size_in_bytes() >= w[1].size_in_bytes()));
+
+        cgroup.delete()
+    }
+
     #[test]
     #[ignore] // must not be executed in parallel
     fn test_subsystem_stat_throttled() -> Result<()> {
@@ -998,14 +1595,48 @@ total_unevictable 14004224
                 total_inactive_file: 2238832640,
                 total_active_file: 4166680576,
                 total_unevictable: 14004224,
+                extra: BTreeMap::new(),
             }
         );
 
+        let content_ok_extra = format!(
+            "{}workingset_refault 4096\ntotal_workingset_refault 8192\n",
+            CONTENT_OK
+        );
+        let stat_extra = parse_stat(content_ok_extra.as_bytes())?;
+        assert_eq!(
+            stat_extra.extra,
+            [
+                ("workingset_refault".to_string(), 4096),
+                ("total_workingset_refault".to_string(), 8192)
+            ]
+            .iter()
+            .cloned()
+            .collect()
+        );
+        assert_eq!(
+            Stat {
+                extra: BTreeMap::new(),
+                ..stat_extra
+            },
+            stat
+        );
+
         assert_eq!(
             parse_stat("".as_bytes()).unwrap_err().kind(),
             ErrorKind::Parse
         );
 
+        for case in &[
+            format!("{}workingset_refault invalid\n", CONTENT_OK),
+            format!("{}workingset_refault 1\nworkingset_refault 1\n", CONTENT_OK),
+        ] {
+            assert_eq!(
+                parse_stat(case.as_bytes()).unwrap_err().kind(),
+                ErrorKind::Parse
+            );
+        }
+
         Ok(())
     }
 
@@ -1037,9 +1668,27 @@ hierarchical_unevictable=3419 N0=3419 N1=7
                 hierarchical_file: (1383803, vec![1383803, 5]),
                 hierarchical_anon: (2209488, vec![2209492, 6]),
                 hierarchical_unevictable: (3419, vec![3419, 7]),
+                extra: BTreeMap::new(),
             }
         );
 
+        let content_ok_extra = format!("{}shmem=2 N0=1 N1=1\n", CONTENT_OK);
+        assert_eq!(
+            parse_numa_stat(content_ok_extra.as_bytes())?.extra,
+            [("shmem".to_string(), (2, vec![1, 1]))]
+                .iter()
+                .cloned()
+                .collect()
+        );
+
+        let content_ng_extra = format!("{}shmem=2 N0=2\n", CONTENT_OK);
+        assert_eq!(
+            parse_numa_stat(content_ng_extra.as_bytes())
+                .unwrap_err()
+                .kind(),
+            ErrorKind::Parse
+        );
+
         assert_eq!(
             parse_numa_stat("".as_bytes()).unwrap_err().kind(),
             ErrorKind::Parse
@@ -1048,6 +1697,89 @@ hierarchical_unevictable=3419 N0=3419 N1=7
         Ok(())
     }
 
+    #[test]
+    fn test_parse_slabinfo() -> Result<()> {
+        #![allow(clippy::legacy_numeric_constants)] // `u64::MAX` requires Rust 1.43
+
+        const CONTENT_OK: &str = "\
+slabinfo - version: 2.1
+# name            <active_objs> <num_objs> <objsize> <objperslab> <pagesperslab> : tunables <limit> <batchcount> <sharedfactor> : slabdata <active_slabs> <num_slabs> <sharedavail>
+inode_cache          312    312    608   26    4 : tunables    0    0    0 : slabdata     12     12      0
+dentry              1386   1512    192   21    1 : tunables    0    0    0 : slabdata     72     72      0
+kmalloc-64           512    512     64   64    1 : tunables  120   60    8 : slabdata      8      8      0
+";
+
+        let caches = parse_slabinfo(CONTENT_OK.as_bytes())?;
+        assert_eq!(caches.len(), 3);
+        assert_eq!(
+            caches[1],
+            SlabCache {
+                name: "dentry".to_string(),
+                active_objs: 1386,
+                num_objs: 1512,
+                objsize: 192,
+                objperslab: 21,
+                pagesperslab: 1,
+                active_slabs: 72,
+                num_slabs: 72,
+            }
+        );
+        assert_eq!(caches[1].size_in_bytes(), 72 * page_size());
+        assert_eq!(caches[0].size_in_bytes(), 12 * 4 * page_size());
+        assert_eq!(caches[1].active_size_in_bytes(), 1386 * 192);
+
+        let huge = SlabCache {
+            num_slabs: u64::max_value(),
+            active_objs: u64::max_value(),
+            ..caches[0].clone()
+        };
+        assert_eq!(huge.size_in_bytes(), u64::max_value());
+        assert_eq!(huge.active_size_in_bytes(), u64::max_value());
+
+        let mut top = caches;
+        top_by_size(&mut top, 2);
+        assert_eq!(
+            top.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(),
+            vec!["dentry", "inode_cache"]
+        );
+
+        assert_eq!(parse_slabinfo("".as_bytes())?, vec![]);
+
+        const CONTENT_NG_VERSION: &str = "\
+slabinfo - version: 1.1
+dentry              1386   1512    192   21    1 : tunables    0    0    0 : slabdata     72     72      0
+";
+
+        const CONTENT_NG_NOT_INT: &str = "\
+slabinfo - version: 2.1
+dentry              1386   invalid    192   21    1 : tunables    0    0    0 : slabdata     72     72      0
+";
+
+        const CONTENT_NG_MISSING_DATA: &str = "\
+slabinfo - version: 2.1
+dentry              1386   1512    192   21    1 : tunables    0    0    0
+";
+
+        const CONTENT_NG_EXTRA_DATA: &str = "\
+slabinfo - version: 2.1
+dentry              1386   1512    192   21    1 : tunables    0    0    0 : slabdata     72     72      0 0
+";
+
+        for case in &[
+            CONTENT_NG_VERSION,
+            CONTENT_NG_NOT_INT,
+            CONTENT_NG_MISSING_DATA,
+            CONTENT_NG_EXTRA_DATA,
+        ] {
+            assert_eq!(
+                parse_slabinfo(case.as_bytes()).unwrap_err().kind(),
+                ErrorKind::Parse
+            );
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn test_parse_oom_control() -> Result<()> {
         const CONTENT_OK_WITH_OOM_KILL: &str = "\
@@ -1118,6 +1850,33 @@ invalid 0
         Ok(())
     }
 
+    #[test]
+    fn test_pressure_level_mode() {
+        for &level in &[
+            PressureLevel::Low,
+            PressureLevel::Medium,
+            PressureLevel::Critical,
+        ] {
+            assert_eq!(level.to_string().parse::<PressureLevel>().unwrap(), level);
+        }
+        for &mode in &[
+            PressureMode::Default,
+            PressureMode::Hierarchy,
+            PressureMode::Local,
+        ] {
+            assert_eq!(mode.to_string().parse::<PressureMode>().unwrap(), mode);
+        }
+
+        assert_eq!(
+            "high".parse::<PressureLevel>().unwrap_err().kind(),
+            ErrorKind::Parse
+        );
+        assert_eq!(
+            "global".parse::<PressureMode>().unwrap_err().kind(),
+            ErrorKind::Parse
+        );
+    }
+
     #[test]
     fn test_parse_01_bool_option() {
         assert_eq!(parse_01_bool_option(Some("0")).unwrap(), false);
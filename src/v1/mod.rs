@@ -13,6 +13,9 @@
 //!
 //! [`Builder`] provides a way to configure a set of cgroups in the builder pattern.
 //!
+//! [`backend`] module provides a way to access cgroup files through a custom backend, e.g. a fake
+//! cgroup file system for testing.
+//!
 //! For more information about cgroup v1, see the kernel's documentation
 //! [Documentation/cgroup-v1/cgroups.txt].
 //!
@@ -34,44 +37,69 @@
 //! [`Cgroup`]: trait.Cgroup.html
 //! [`UnifiedRepr`]: struct.UnifiedRepr.html
 //! [`Builder`]: builder/struct.Builder.html
+//! [`backend`]: backend/index.html
 //!
 //! [Documentation/cgroup-v1/cgroups.txt]: https://www.kernel.org/doc/Documentation/cgroup-v1/cgroups.txt
 
-use std::{fmt, path::Path};
+use std::{fmt, path::Path, str::FromStr};
+
+use crate::{Error, Result};
 
 #[macro_use]
 mod macros;
 
 #[macro_use]
 mod cgroup;
+#[cfg(feature = "tokio")]
+mod async_cgroup;
+pub mod backend;
 pub mod blkio;
 pub mod builder;
 mod command_ext;
 pub mod cpu;
 pub mod cpuacct;
 pub mod cpuset;
+mod delete;
 pub mod devices;
+mod diff;
+mod event;
 pub mod freezer;
 pub mod hugetlb;
 pub mod memory;
+pub mod mount;
 pub mod net_cls;
 pub mod net_prio;
+#[cfg(feature = "oci")]
+pub mod oci;
 pub mod perf_event;
 pub mod pids;
+pub mod proc_cgroup;
+pub mod prometheus;
 pub mod rdma;
+pub mod release_agent;
+pub mod sampler;
+mod transaction;
 mod unified_repr;
+mod validate;
 
+#[cfg(feature = "tokio")]
+pub use async_cgroup::{AsyncCgroup, AsyncEventListener, AsyncUnifiedRepr};
 pub use builder::Builder;
-pub use cgroup::{Cgroup, CgroupPath};
+pub use cgroup::{Cgroup, CgroupPath, Walk};
 pub use command_ext::CommandExt;
-pub use unified_repr::UnifiedRepr;
+pub use delete::{DeleteError, DeleteOptions, Drain};
+pub use event::EventListener;
+pub use transaction::ApplyError;
+pub use unified_repr::{Guard, UnifiedRepr};
+pub use validate::{ValidationError, Violation};
 
 const CGROUPFS_MOUNT_POINT: &str = "/sys/fs/cgroup";
 
 /// Kinds of subsystems that are now available in this crate.
 ///
-/// `SubsystemKind` implements [`AsRef`]`<`[`Path`]`>` and [`Display`]. The resulting path or string
-/// is a standard directory name for the subsystem (e.g. `SubsystemKind::Cpu` => `cpu`).
+/// `SubsystemKind` implements [`AsRef`]`<`[`Path`]`>`, [`Display`], and [`FromStr`]. The resulting
+/// path or string is a standard directory name for the subsystem (e.g. `SubsystemKind::Cpu` =>
+/// `cpu`).
 ///
 /// ```
 /// use std::path::Path;
@@ -82,11 +110,14 @@ const CGROUPFS_MOUNT_POINT: &str = "/sys/fs/cgroup";
 ///
 /// assert_eq!(SubsystemKind::Devices.to_string(), "devices");
 /// assert_eq!(SubsystemKind::PerfEvent.to_string(), "perf_event");
+///
+/// assert_eq!("net_cls".parse::<SubsystemKind>().unwrap(), SubsystemKind::NetCls);
 /// ```
 ///
 /// [`AsRef`]: https://doc.rust-lang.org/std/convert/trait.AsRef.html
 /// [`Path`]: https://doc.rust-lang.org/std/path/struct.Path.html
 /// [`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+/// [`FromStr`]: https://doc.rust-lang.org/std/str/trait.FromStr.html
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SubsystemKind {
     /// CPU subsystem.
@@ -119,6 +150,8 @@ pub enum SubsystemKind {
 
 /// Compound of resource limits and constraints for all subsystems.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Resources {
     /// Resource limit on how much CPU time this cgroup can use.
     pub cpu: cpu::Resources,
@@ -157,6 +190,34 @@ impl fmt::Display for SubsystemKind {
     }
 }
 
+impl FromStr for SubsystemKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "cpu" => Ok(Self::Cpu),
+            "cpuset" => Ok(Self::Cpuset),
+            "cpuacct" => Ok(Self::Cpuacct),
+            "memory" => Ok(Self::Memory),
+            "hugetlb" => Ok(Self::HugeTlb),
+            "devices" => Ok(Self::Devices),
+            "blkio" => Ok(Self::BlkIo),
+            "rdma" => Ok(Self::Rdma),
+            "net_prio" => Ok(Self::NetPrio),
+            "net_cls" => Ok(Self::NetCls),
+            "pids" => Ok(Self::Pids),
+            "freezer" => Ok(Self::Freezer),
+            "perf_event" => Ok(Self::PerfEvent),
+            _ => {
+                bail_parse!();
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl_serde_str!(SubsystemKind);
+
 impl SubsystemKind {
     fn as_str(self) -> &'static str {
         match self {
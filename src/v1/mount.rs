@@ -0,0 +1,411 @@
+//! Discovery of cgroup file system mount points.
+//!
+//! By default, this crate assumes that each v1 hierarchy is mounted at the standard location, i.e.
+//! `/sys/fs/cgroup/<subsystem name>`. This is not the case for some environments, such as
+//! containers with a different layout, or hosts on which multiple subsystems are co-mounted on a
+//! single hierarchy (e.g. `cpu,cpuacct`).
+//!
+//! [`MountInfo`] parses `/proc/self/mountinfo` and finds the real mount point of each hierarchy
+//! and the subsystems attached to it.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # fn main() -> controlgroup::Result<()> {
+//! use std::path::PathBuf;
+//! use controlgroup::v1::{cpu, mount::MountInfo, Cgroup, SubsystemKind};
+//!
+//! let mount_info = MountInfo::load()?;
+//!
+//! if let Some(hierarchy) = mount_info.hierarchy(SubsystemKind::Cpu) {
+//!     println!("CPU subsystem is mounted at {}", hierarchy.mount_point().display());
+//!     println!("co-mounted with {:?}", hierarchy.subsystems());
+//! }
+//!
+//! let path = mount_info.cgroup_path(SubsystemKind::Cpu, PathBuf::from("students/charlie"));
+//! if let Some(path) = path {
+//!     let mut cgroup = cpu::Subsystem::new(path);
+//!     cgroup.create()?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`MountInfo`]: struct.MountInfo.html
+
+use std::{
+    ffi::OsString,
+    fs::File,
+    io::{self, BufRead},
+    os::unix::ffi::OsStringExt,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    v1::{CgroupPath, SubsystemKind},
+    Error, ErrorKind, Result,
+};
+
+const MOUNTINFO: &str = "/proc/self/mountinfo";
+
+/// Mount points of cgroup file systems, as in `/proc/self/mountinfo` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MountInfo {
+    hierarchies: Vec<Hierarchy>,
+    unified: Option<PathBuf>,
+}
+
+/// A v1 hierarchy mounted on the system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hierarchy {
+    mount_point: PathBuf,
+    root: PathBuf,
+    subsystems: Vec<SubsystemKind>,
+    name: Option<String>,
+}
+
+impl MountInfo {
+    /// Reads and parses `/proc/self/mountinfo` file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to read and parse `/proc/self/mountinfo` file.
+    pub fn load() -> Result<Self> {
+        Self::parse(File::open(MOUNTINFO)?)
+    }
+
+    /// Parses a content in the format of `/proc/<pid>/mountinfo` file.
+    ///
+    /// Mount entries other than cgroup file systems are ignored. Subsystems not available in this
+    /// crate are also ignored. If a hierarchy is mounted at multiple locations, the first one is
+    /// used.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`ErrorKind::Parse`] if failed to parse the content.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::Path;
+    /// use controlgroup::v1::{mount::MountInfo, SubsystemKind};
+    ///
+    /// let content = "\
+    /// 33 25 0:29 / /cgroup/cpu,cpuacct rw,relatime shared:9 - cgroup cgroup rw,cpu,cpuacct
+    /// 34 25 0:30 / /cgroup/unified rw,relatime shared:10 - cgroup2 cgroup2 rw
+    /// ";
+    /// let mount_info = MountInfo::parse(content.as_bytes())?;
+    ///
+    /// let cpuacct = mount_info.hierarchy(SubsystemKind::Cpuacct).unwrap();
+    /// assert_eq!(cpuacct.mount_point(), Path::new("/cgroup/cpu,cpuacct"));
+    /// assert_eq!(cpuacct.subsystems(), &[SubsystemKind::Cpu, SubsystemKind::Cpuacct]);
+    ///
+    /// assert_eq!(mount_info.unified_mount_point(), Some(Path::new("/cgroup/unified")));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ErrorKind::Parse`]: ../../enum.ErrorKind.html#variant.Parse
+    pub fn parse(reader: impl io::Read) -> Result<Self> {
+        let mut mount_info = Self::default();
+
+        for line in io::BufReader::new(reader).lines() {
+            let line = line?;
+
+            // Format: "id parent major:minor root mount_point options [optional fields...] -
+            //          fs_type source super_options"
+            let mut entry = line.split(' ');
+            let root = entry.nth(3).ok_or_else(|| Error::new(ErrorKind::Parse))?;
+            let mount_point = entry.next().ok_or_else(|| Error::new(ErrorKind::Parse))?;
+
+            let mut entry = entry.skip_while(|e| *e != "-").skip(1);
+            let fs_type = entry.next().ok_or_else(|| Error::new(ErrorKind::Parse))?;
+            let super_options = entry.nth(1).ok_or_else(|| Error::new(ErrorKind::Parse))?;
+
+            match fs_type {
+                "cgroup" => {
+                    let mut subsystems = Vec::new();
+                    let mut name = None;
+
+                    for opt in super_options.split(',') {
+                        let mut kv = opt.splitn(2, '=');
+                        match (kv.next(), kv.next()) {
+                            (Some("name"), Some(n)) => name = Some(n.to_string()),
+                            (Some(kind), None) => {
+                                if let Ok(kind) = kind.parse::<SubsystemKind>() {
+                                    subsystems.push(kind);
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+
+                    let duplicated = mount_info.hierarchies.iter().any(|h| {
+                        (!subsystems.is_empty() && h.subsystems == subsystems)
+                            || (subsystems.is_empty() && name.is_some() && h.name == name)
+                    });
+                    if !duplicated {
+                        mount_info.hierarchies.push(Hierarchy {
+                            mount_point: unescape(mount_point)?,
+                            root: unescape(root)?,
+                            subsystems,
+                            name,
+                        });
+                    }
+                }
+                "cgroup2" if mount_info.unified.is_none() => {
+                    mount_info.unified = Some(unescape(mount_point)?);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(mount_info)
+    }
+
+    /// Returns the list of v1 hierarchies mounted on the system, including named hierarchies
+    /// without subsystems (e.g. `name=systemd`).
+    pub fn hierarchies(&self) -> &[Hierarchy] {
+        &self.hierarchies
+    }
+
+    /// Returns the hierarchy to which a subsystem is attached, or `None` if the subsystem is not
+    /// mounted.
+    pub fn hierarchy(&self, kind: SubsystemKind) -> Option<&Hierarchy> {
+        self.hierarchies
+            .iter()
+            .find(|h| h.subsystems.contains(&kind))
+    }
+
+    /// Returns the named hierarchy (e.g. `name=systemd`), or `None` if no hierarchy with the name
+    /// is mounted.
+    pub fn named_hierarchy(&self, name: &str) -> Option<&Hierarchy> {
+        self.hierarchies.iter().find(|h| h.name() == Some(name))
+    }
+
+    /// Returns the mount point of the v2 unified hierarchy, or `None` if it is not mounted.
+    #[allow(clippy::option_as_ref_deref)] // `Option::as_deref` requires Rust 1.40
+    pub fn unified_mount_point(&self) -> Option<&Path> {
+        self.unified.as_ref().map(PathBuf::as_path)
+    }
+
+    /// Returns a `CgroupPath` to a cgroup with the given name in the hierarchy to which a subsystem
+    /// is attached, or `None` if the subsystem is not mounted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{mount::MountInfo, CgroupPath, SubsystemKind};
+    ///
+    /// let content = "33 25 0:29 / /cgroup/cpu,cpuacct rw - cgroup cgroup rw,cpu,cpuacct\n";
+    /// let mount_info = MountInfo::parse(content.as_bytes())?;
+    ///
+    /// let name = PathBuf::from("students/charlie");
+    /// assert_eq!(
+    ///     mount_info.cgroup_path(SubsystemKind::Cpuacct, name.clone()),
+    ///     Some(CgroupPath::with_root(PathBuf::from("/cgroup/cpu,cpuacct"), name.clone()))
+    /// );
+    /// assert!(mount_info.cgroup_path(SubsystemKind::Memory, name).is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn cgroup_path(&self, kind: SubsystemKind, name: PathBuf) -> Option<CgroupPath> {
+        self.hierarchy(kind)
+            .map(|h| CgroupPath::with_root(h.mount_point.clone(), name))
+    }
+}
+
+impl Hierarchy {
+    /// Returns the mount point of this hierarchy.
+    pub fn mount_point(&self) -> &Path {
+        &self.mount_point
+    }
+
+    /// Returns the path within this hierarchy which forms the root of the mount (e.g. `/` for the
+    /// whole hierarchy, or a cgroup path in a container with a private cgroup namespace).
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the subsystems attached to this hierarchy.
+    pub fn subsystems(&self) -> &[SubsystemKind] {
+        &self.subsystems
+    }
+
+    /// Returns the name of this hierarchy specified with `name=` mount option, if any.
+    #[allow(clippy::option_as_ref_deref)] // `Option::as_deref` requires Rust 1.40
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(String::as_str)
+    }
+}
+
+// Decodes octal escape sequences (e.g. `\040` for a space) in a path field of mountinfo.
+fn unescape(s: &str) -> Result<PathBuf> {
+    let bytes = s.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' {
+            let oct = bytes
+                .get(i + 1..i + 4)
+                .and_then(|o| std::str::from_utf8(o).ok())
+                .and_then(|o| u8::from_str_radix(o, 8).ok())
+                .ok_or_else(|| Error::new(ErrorKind::Parse))?;
+            result.push(oct);
+            i += 4;
+        } else {
+            result.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    Ok(PathBuf::from(OsString::from_vec(result)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use SubsystemKind::*;
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        const CONTENT: &str = "\
+24 29 0:22 / /sys rw,nosuid,nodev,noexec,relatime shared:7 - sysfs sysfs rw
+32 24 0:28 / /sys/fs/cgroup ro,nosuid,nodev,noexec shared:9 - tmpfs tmpfs ro,mode=755
+33 32 0:29 / /sys/fs/cgroup/unified rw,nosuid,nodev,noexec,relatime shared:10 - cgroup2 cgroup2 rw,nsdelegate
+34 32 0:30 / /sys/fs/cgroup/systemd rw,nosuid,nodev,noexec,relatime shared:11 - cgroup cgroup rw,xattr,name=systemd
+37 32 0:33 / /sys/fs/cgroup/cpu,cpuacct rw,nosuid,nodev,noexec,relatime shared:16 - cgroup cgroup rw,cpu,cpuacct
+38 32 0:34 / /sys/fs/cgroup/net_cls,net_prio rw,nosuid,nodev,noexec,relatime shared:17 - cgroup cgroup rw,net_cls,net_prio
+39 32 0:35 /docker/abc /sys/fs/cgroup/memory rw,nosuid,nodev,noexec,relatime - cgroup cgroup rw,memory
+40 32 0:36 / /sys/fs/cgroup/misc rw,nosuid,nodev,noexec,relatime shared:18 - cgroup cgroup rw,misc
+41 32 0:37 / /mnt/my\\040cgroup rw,relatime shared:19 - cgroup cgroup rw,pids
+42 32 0:33 / /mnt/cpu rw,relatime shared:16 - cgroup cgroup rw,cpu,cpuacct
+";
+
+        let mount_info = MountInfo::parse(CONTENT.as_bytes())?;
+
+        assert_eq!(
+            mount_info.hierarchies(),
+            &[
+                Hierarchy {
+                    mount_point: PathBuf::from("/sys/fs/cgroup/systemd"),
+                    root: PathBuf::from("/"),
+                    subsystems: vec![],
+                    name: Some("systemd".to_string()),
+                },
+                Hierarchy {
+                    mount_point: PathBuf::from("/sys/fs/cgroup/cpu,cpuacct"),
+                    root: PathBuf::from("/"),
+                    subsystems: vec![Cpu, Cpuacct],
+                    name: None,
+                },
+                Hierarchy {
+                    mount_point: PathBuf::from("/sys/fs/cgroup/net_cls,net_prio"),
+                    root: PathBuf::from("/"),
+                    subsystems: vec![NetCls, NetPrio],
+                    name: None,
+                },
+                Hierarchy {
+                    mount_point: PathBuf::from("/sys/fs/cgroup/memory"),
+                    root: PathBuf::from("/docker/abc"),
+                    subsystems: vec![Memory],
+                    name: None,
+                },
+                Hierarchy {
+                    mount_point: PathBuf::from("/sys/fs/cgroup/misc"),
+                    root: PathBuf::from("/"),
+                    subsystems: vec![],
+                    name: None,
+                },
+                Hierarchy {
+                    mount_point: PathBuf::from("/mnt/my cgroup"),
+                    root: PathBuf::from("/"),
+                    subsystems: vec![Pids],
+                    name: None,
+                },
+            ]
+        );
+
+        assert_eq!(
+            mount_info.hierarchy(Cpuacct).map(Hierarchy::mount_point),
+            Some(Path::new("/sys/fs/cgroup/cpu,cpuacct"))
+        );
+        assert!(mount_info.hierarchy(Freezer).is_none());
+
+        assert_eq!(
+            mount_info
+                .named_hierarchy("systemd")
+                .map(Hierarchy::mount_point),
+            Some(Path::new("/sys/fs/cgroup/systemd"))
+        );
+        assert!(mount_info.named_hierarchy("none").is_none());
+
+        assert_eq!(
+            mount_info.unified_mount_point(),
+            Some(Path::new("/sys/fs/cgroup/unified"))
+        );
+
+        assert_eq!(
+            mount_info.cgroup_path(NetPrio, PathBuf::from("students/charlie")),
+            Some(CgroupPath::with_root(
+                PathBuf::from("/sys/fs/cgroup/net_cls,net_prio"),
+                PathBuf::from("students/charlie")
+            ))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_err() {
+        for case in &[
+            "33 32 0:29 /\n",
+            "33 32 0:29 / /sys/fs/cgroup/cpu rw\n",
+            "33 32 0:29 / /sys/fs/cgroup/cpu rw - cgroup\n",
+            "33 32 0:29 / /sys/fs/cgroup/cpu\\04 rw - cgroup cgroup rw,cpu\n",
+        ] {
+            assert_eq!(
+                MountInfo::parse(case.as_bytes()).unwrap_err().kind(),
+                ErrorKind::Parse
+            );
+        }
+    }
+
+    #[test]
+    fn test_unescape() -> Result<()> {
+        assert_eq!(unescape("/a\\040b")?, PathBuf::from("/a b"));
+        assert_eq!(unescape("/a\\011b\\134c")?, PathBuf::from("/a\tb\\c"));
+        assert_eq!(unescape("/abc")?, PathBuf::from("/abc"));
+        assert_eq!(unescape("/a\\9").unwrap_err().kind(), ErrorKind::Parse);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load() -> Result<()> {
+        let mount_info = MountInfo::load()?;
+        assert_eq!(mount_info, MountInfo::parse(File::open(MOUNTINFO)?)?);
+
+        // The mount point may differ from the standard location, e.g. `cpu,cpuacct`
+        let hierarchy = mount_info.hierarchy(Cpu).unwrap();
+        assert!(hierarchy.subsystems().contains(&Cpu));
+        assert!(hierarchy.mount_point().join("cpu.shares").exists());
+
+        let name = PathBuf::from("students/charlie");
+        assert_eq!(
+            mount_info.cgroup_path(Cpu, name.clone()),
+            Some(CgroupPath::with_root(
+                hierarchy.mount_point().to_path_buf(),
+                name
+            ))
+        );
+
+        Ok(())
+    }
+}
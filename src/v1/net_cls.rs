@@ -40,7 +40,7 @@ use std::{fmt, path::PathBuf, str::FromStr};
 
 use crate::{
     parse::parse,
-    v1::{self, Cgroup, CgroupPath},
+    v1::{self, cgroup::CgroupHelper, CgroupPath},
     Error, Result,
 };
 
@@ -54,6 +54,8 @@ pub struct Subsystem {
 ///
 /// See the kernel's documentation for more information about the field.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Resources {
     /// Class ID to be attached to network packets originating from this cgroup.
     pub classid: Option<ClassId>,
@@ -136,6 +138,11 @@ impl_cgroup! {
 
         Ok(())
     }
+
+    /// Reads `resources.net_cls.classid` from this cgroup.
+    fn resources(&self) -> Result<v1::Resources> {
+        Ok(self::Resources { classid: Some(self.classid()?) }.into())
+    }
 }
 
 const CLASSID: &str = "net_cls.classid";
@@ -151,7 +158,7 @@ impl Subsystem {
         gen_doc!(err_read; "net_cls.classid"),
         gen_doc!(eg_read; net_cls, classid)),
         pub fn classid(&self) -> Result<ClassId> {
-            let raw: u32 = self.open_file_read(CLASSID).and_then(parse)?;
+            let raw: u32 = self.read_file(CLASSID, parse)?;
             Ok(raw.into())
         }
     }
@@ -167,7 +174,7 @@ impl Subsystem {
         gen_doc!(eg_write; net_cls, set_classid, [0x10, 0x1].into())),
         pub fn set_classid(&mut self, id: ClassId) -> Result<()> {
             let raw: u32 = id.into();
-            std::fs::write(self.path().join(CLASSID), format!("{:#08X}", raw)).map_err(Into::into)
+            self.write_file(CLASSID, format!("{:#08X}", raw))
         }
     }
 }
@@ -203,6 +210,9 @@ impl fmt::Display for ClassId {
     }
 }
 
+#[cfg(feature = "serde")]
+impl_serde_str!(ClassId);
+
 impl From<u32> for ClassId {
     fn from(id: u32) -> Self {
         Self {
@@ -236,6 +246,7 @@ impl Into<[u16; 2]> for ClassId {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::v1::Cgroup;
 
     #[test]
     fn test_subsystem_create_file_exists() -> Result<()> {
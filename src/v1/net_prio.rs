@@ -39,8 +39,9 @@
 use std::{collections::HashMap, path::PathBuf};
 
 use crate::{
+    file,
     parse::{parse, parse_next},
-    v1::{self, Cgroup, CgroupPath},
+    v1::{self, cgroup::CgroupHelper, Cgroup, CgroupPath},
     Error, ErrorKind, Result,
 };
 
@@ -54,6 +55,8 @@ pub struct Subsystem {
 ///
 /// See the kernel's documentation for more information about the field.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Resources {
     /// Map of priorities assigned to traffic originating from this cgroup.
     ///
@@ -74,6 +77,11 @@ impl_cgroup! {
             self.set_ifpriomap(prio_map.iter())
         }
     }
+
+    /// Reads `resources.net_prio.ifpriomap` from this cgroup.
+    fn resources(&self) -> Result<v1::Resources> {
+        Ok(self::Resources { ifpriomap: self.ifpriomap()? }.into())
+    }
 }
 
 impl Subsystem {
@@ -107,14 +115,14 @@ impl Subsystem {
             T: crate::RefKv<K, u32>,
             K: std::fmt::Display,
         {
-            use std::io::Write;
-
-            let mut file = self.open_file_write("net_prio.ifpriomap")?;
+            // one line per write
             for if_prio in prio_map {
                 let (interface, prio) = if_prio.ref_kv();
-
-                // write!(file, "{} {}", interface, prio)?; // not work
-                file.write_all(format!("{} {}", interface, prio).as_bytes())?;
+                file::write(
+                    self.backend(),
+                    self.path().join("net_prio.ifpriomap"),
+                    format!("{} {}", interface, prio),
+                )?;
             }
 
             Ok(())
@@ -0,0 +1,941 @@
+//! Conversion between `linux.resources` of the [OCI runtime specification] and [`Resources`].
+//!
+//! [`LinuxResources`] and the types it consists of mirror the `linux.resources` object in
+//! `config.json` of an OCI bundle, and implement `Serialize` and `Deserialize` of [serde] in the
+//! same format. [`LinuxResources::to_resources`] converts them into `Resources`, and
+//! [`LinuxResources::from_resources`] does the reverse.
+//!
+//! The fields are mapped as follows:
+//!
+//! - `cpu`: `shares`, `quota`, `period`, `realtimeRuntime`, and `realtimePeriod` to
+//!   [`cpu::Resources`]; `cpus` and `mems` to `cpus` and `mems` of [`cpuset::Resources`].
+//! - `memory`: `limit`, `reservation`, `swap`, `kernel`, `kernelTCP`, `swappiness`, and
+//!   `useHierarchy` to `limit_in_bytes`, `soft_limit_in_bytes`, `memsw_limit_in_bytes`,
+//!   `kmem_limit_in_bytes`, `kmem_tcp_limit_in_bytes`, `swappiness`, and `use_hierarchy` of
+//!   [`memory::Resources`], respectively.
+//! - `blockIO`: weights and throttles to [`blkio::Resources`].
+//! - `hugepageLimits`: to [`hugetlb::Resources`], in bytes.
+//! - `network`: `classID` to [`net_cls::Resources`], and `priorities` to [`net_prio::Resources`].
+//! - `pids`: to [`pids::Resources`]. A non-positive limit means no limit.
+//! - `rdma`: to [`rdma::Resources`]. An absent limit means no limit.
+//! - `devices`: to [`devices::Resources`]. Rules with `allow: false` go to `deny` list, and the
+//!   others go to `allow` list, keeping their order in each list. Since `deny` list is applied
+//!   before `allow` list, all deny rules must precede all allow rules; otherwise the conversion
+//!   fails rather than silently changing the meaning of the rules.
+//!
+//! `memory.disableOOMKiller`, and fields not supported by cgroup v1 (e.g. `unified`), are not
+//! converted.
+//!
+//! This module is available only if `oci` feature is enabled.
+//!
+//! # Examples
+//!
+//! ```
+//! # fn main() -> controlgroup::Result<()> {
+//! use controlgroup::v1::oci::LinuxResources;
+//!
+//! let config = r#"{
+//!     "ociVersion": "1.0.2",
+//!     "linux": {
+//!         "resources": {
+//!             "cpu": { "shares": 512, "cpus": "0-1" },
+//!             "memory": { "limit": 536870912 },
+//!             "devices": [
+//!                 { "allow": false, "access": "rwm" },
+//!                 { "allow": true, "type": "c", "major": 1, "minor": 3, "access": "rw" }
+//!             ]
+//!         }
+//!     }
+//! }"#;
+//!
+//! let resources = LinuxResources::from_config(config.as_bytes())?.to_resources()?;
+//!
+//! assert_eq!(resources.cpu.shares, Some(512));
+//! assert_eq!(resources.cpuset.cpus, Some("0-1".parse()?));
+//! assert_eq!(resources.memory.limit_in_bytes, Some(512 * (1 << 20)));
+//! assert_eq!(resources.devices.deny, vec!["a *:* rwm".parse()?]);
+//! assert_eq!(resources.devices.allow, vec!["c 1:3 rw".parse()?]);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [OCI runtime specification]: https://github.com/opencontainers/runtime-spec/blob/master/config-linux.md#control-groups
+//! [serde]: https://serde.rs
+//!
+//! [`Resources`]: ../struct.Resources.html
+//! [`LinuxResources`]: struct.LinuxResources.html
+//! [`LinuxResources::to_resources`]: struct.LinuxResources.html#method.to_resources
+//! [`LinuxResources::from_resources`]: struct.LinuxResources.html#method.from_resources
+//!
+//! [`cpu::Resources`]: ../cpu/struct.Resources.html
+//! [`cpuset::Resources`]: ../cpuset/struct.Resources.html
+//! [`memory::Resources`]: ../memory/struct.Resources.html
+//! [`blkio::Resources`]: ../blkio/struct.Resources.html
+//! [`hugetlb::Resources`]: ../hugetlb/struct.Resources.html
+//! [`net_cls::Resources`]: ../net_cls/struct.Resources.html
+//! [`net_prio::Resources`]: ../net_prio/struct.Resources.html
+//! [`pids::Resources`]: ../pids/struct.Resources.html
+//! [`rdma::Resources`]: ../rdma/struct.Resources.html
+//! [`devices::Resources`]: ../devices/struct.Resources.html
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    io,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    v1::{
+        blkio,
+        devices::{Access, DeviceType},
+        hugetlb, rdma, Resources,
+    },
+    Device, DeviceNumber, Error, ErrorKind, Max, Result,
+};
+
+/// `linux.resources` object of the OCI runtime specification.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinuxResources {
+    /// Device access rules, applied in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub devices: Vec<LinuxDeviceCgroup>,
+    /// Memory restrictions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<LinuxMemory>,
+    /// CPU restrictions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<LinuxCpu>,
+    /// Process number restriction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pids: Option<LinuxPids>,
+    /// Block I/O restrictions.
+    #[serde(default, rename = "blockIO", skip_serializing_if = "Option::is_none")]
+    pub block_io: Option<LinuxBlockIo>,
+    /// Hugepage TLB restrictions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hugepage_limits: Vec<LinuxHugepageLimit>,
+    /// Network traffic configuration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<LinuxNetwork>,
+    /// RDMA/IB restrictions for each device name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rdma: BTreeMap<String, LinuxRdma>,
+}
+
+/// Device access rule in `linux.resources.devices`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinuxDeviceCgroup {
+    /// Whether the access is allowed or denied.
+    pub allow: bool,
+    /// Device type, `a` (all), `c` (character), or `b` (block). Absent means all.
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub device_type: Option<String>,
+    /// Major number of the device. Absent or -1 means any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub major: Option<i64>,
+    /// Minor number of the device. Absent or -1 means any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minor: Option<i64>,
+    /// Access type, a combination of `r` (read), `w` (write), and `m` (mknod). Absent means all.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access: Option<String>,
+}
+
+/// Memory restrictions in `linux.resources.memory`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinuxMemory {
+    /// Limit of memory usage (in bytes).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+    /// Soft limit of memory usage (in bytes).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reservation: Option<i64>,
+    /// Limit of memory and swap usage (in bytes).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub swap: Option<i64>,
+    /// Limit of kernel memory usage (in bytes).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kernel: Option<i64>,
+    /// Limit of kernel memory usage for TCP (in bytes).
+    #[serde(default, rename = "kernelTCP", skip_serializing_if = "Option::is_none")]
+    pub kernel_tcp: Option<i64>,
+    /// Swappiness.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub swappiness: Option<u64>,
+    /// Whether the OOM killer is disabled. Not converted into `Resources`.
+    #[serde(
+        default,
+        rename = "disableOOMKiller",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub disable_oom_killer: Option<bool>,
+    /// Whether the hierarchical memory accounting is enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_hierarchy: Option<bool>,
+}
+
+/// CPU restrictions in `linux.resources.cpu`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinuxCpu {
+    /// Relative share of CPU time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shares: Option<u64>,
+    /// Total available CPU time within a period (in microseconds).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota: Option<i64>,
+    /// Length of a period (in microseconds).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub period: Option<u64>,
+    /// Total available CPU time for realtime tasks within a period (in microseconds).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub realtime_runtime: Option<i64>,
+    /// Length of a period for realtime tasks (in microseconds).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub realtime_period: Option<u64>,
+    /// CPUs to use, in the cpuset IDs format (e.g. `0-3,7`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<String>,
+    /// Memory nodes to use, in the cpuset IDs format.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mems: Option<String>,
+}
+
+/// Process number restriction in `linux.resources.pids`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinuxPids {
+    /// Maximum number of tasks. A non-positive value means no limit.
+    pub limit: i64,
+}
+
+/// Block I/O restrictions in `linux.resources.blockIO`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinuxBlockIo {
+    /// Relative weight of block I/O.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u16>,
+    /// Relative weight of block I/O while competing against descendant cgroups.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaf_weight: Option<u16>,
+    /// Weights for specific devices.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub weight_device: Vec<LinuxWeightDevice>,
+    /// Throttles of bytes/s of read access for specific devices.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub throttle_read_bps_device: Vec<LinuxThrottleDevice>,
+    /// Throttles of bytes/s of write access for specific devices.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub throttle_write_bps_device: Vec<LinuxThrottleDevice>,
+    /// Throttles of ops/s of read access for specific devices.
+    #[serde(
+        default,
+        rename = "throttleReadIOPSDevice",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub throttle_read_iops_device: Vec<LinuxThrottleDevice>,
+    /// Throttles of ops/s of write access for specific devices.
+    #[serde(
+        default,
+        rename = "throttleWriteIOPSDevice",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub throttle_write_iops_device: Vec<LinuxThrottleDevice>,
+}
+
+/// Weights for a device in `linux.resources.blockIO.weightDevice`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinuxWeightDevice {
+    /// Major number of the device.
+    pub major: i64,
+    /// Minor number of the device.
+    pub minor: i64,
+    /// Relative weight of block I/O for the device.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u16>,
+    /// Relative leaf weight of block I/O for the device.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaf_weight: Option<u16>,
+}
+
+/// Throttle for a device in `linux.resources.blockIO.throttle*Device`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinuxThrottleDevice {
+    /// Major number of the device.
+    pub major: i64,
+    /// Minor number of the device.
+    pub minor: i64,
+    /// Rate limit in bytes/s or ops/s.
+    pub rate: u64,
+}
+
+/// Hugepage TLB restriction in `linux.resources.hugepageLimits`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinuxHugepageLimit {
+    /// Hugepage size, e.g. `2MB` and `1GB`.
+    pub page_size: String,
+    /// Limit of hugepage TLB usage (in bytes).
+    pub limit: u64,
+}
+
+/// Network traffic configuration in `linux.resources.network`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinuxNetwork {
+    /// Class ID to tag network packets with.
+    #[serde(default, rename = "classID", skip_serializing_if = "Option::is_none")]
+    pub class_id: Option<u32>,
+    /// Priorities of network traffic for each interface.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub priorities: Vec<LinuxInterfacePriority>,
+}
+
+/// Priority of network traffic for an interface in `linux.resources.network.priorities`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinuxInterfacePriority {
+    /// Name of the interface.
+    pub name: String,
+    /// Priority of network traffic for the interface.
+    pub priority: u32,
+}
+
+/// RDMA/IB restriction for a device in `linux.resources.rdma`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinuxRdma {
+    /// Maximum number of HCA handles. Absent means no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hca_handles: Option<u32>,
+    /// Maximum number of HCA objects. Absent means no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hca_objects: Option<u32>,
+}
+
+impl LinuxResources {
+    /// Reads `linux.resources` object from `config.json` of an OCI bundle. Returns the default
+    /// (empty) `LinuxResources` if the object is absent.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`ErrorKind::Parse`] if failed to read or parse the JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::fs::File;
+    /// use controlgroup::v1::oci::LinuxResources;
+    ///
+    /// let resources = LinuxResources::from_config(File::open("bundle/config.json")?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ErrorKind::Parse`]: ../../enum.ErrorKind.html#variant.Parse
+    pub fn from_config(reader: impl io::Read) -> Result<Self> {
+        let config: serde_json::Value = serde_json::from_reader(reader).map_err(Error::parse)?;
+        match config.pointer("/linux/resources") {
+            Some(resources) => Self::deserialize(resources).map_err(Error::parse),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Converts this `LinuxResources` into `Resources`.
+    ///
+    /// See the [module-level documentation] for how the fields are mapped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`ErrorKind::Parse`] if failed to parse a string, such as
+    /// `cpu.cpus`, device types and access types.
+    ///
+    /// Returns an error with kind [`ErrorKind::InvalidArgument`] if a number is out of the range of
+    /// the corresponding field (e.g. a device number that does not fit in `u16`), if a hugepage
+    /// size is not supported by this crate, or if a device rule with `allow: false` follows one
+    /// with `allow: true`.
+    ///
+    /// [module-level documentation]: index.html
+    /// [`ErrorKind::Parse`]: ../../enum.ErrorKind.html#variant.Parse
+    /// [`ErrorKind::InvalidArgument`]: ../../enum.ErrorKind.html#variant.InvalidArgument
+    pub fn to_resources(&self) -> Result<Resources> {
+        let mut resources = Resources::default();
+
+        for rule in &self.devices {
+            let access = Access {
+                device_type: match rule.device_type {
+                    Some(ref t) => t.parse()?,
+                    None => DeviceType::All,
+                },
+                device_number: [device_number(rule.major)?, device_number(rule.minor)?].into(),
+                access_type: match rule.access {
+                    Some(ref a) => a.parse()?,
+                    None => "rwm".parse()?,
+                },
+            };
+
+            if rule.allow {
+                resources.devices.allow.push(access);
+            } else if resources.devices.allow.is_empty() {
+                resources.devices.deny.push(access);
+            } else {
+                // cannot be applied in order, as `deny` list is applied first
+                return Err(Error::new(ErrorKind::InvalidArgument));
+            }
+        }
+
+        if let Some(ref memory) = self.memory {
+            let res = &mut resources.memory;
+            res.limit_in_bytes = memory.limit;
+            res.soft_limit_in_bytes = memory.reservation;
+            res.memsw_limit_in_bytes = memory.swap;
+            res.kmem_limit_in_bytes = memory.kernel;
+            res.kmem_tcp_limit_in_bytes = memory.kernel_tcp;
+            res.swappiness = memory.swappiness;
+            res.use_hierarchy = memory.use_hierarchy;
+        }
+
+        if let Some(ref cpu) = self.cpu {
+            let res = &mut resources.cpu;
+            res.shares = cpu.shares;
+            res.cfs_quota_us = cpu.quota;
+            res.cfs_period_us = cpu.period;
+            res.rt_runtime_us = cpu.realtime_runtime;
+            res.rt_period_us = cpu.realtime_period;
+
+            let ids = |ids: &Option<String>| match ids {
+                Some(ref ids) if !ids.is_empty() => ids.parse().map(Some),
+                _ => Ok(None),
+            };
+            resources.cpuset.cpus = ids(&cpu.cpus)?;
+            resources.cpuset.mems = ids(&cpu.mems)?;
+        }
+
+        if let Some(pids) = self.pids {
+            resources.pids.max = Some(if pids.limit > 0 {
+                Max::Limit(u32::try_from(pids.limit).map_err(|_| invalid())?)
+            } else {
+                Max::Max
+            });
+        }
+
+        if let Some(ref block_io) = self.block_io {
+            resources.blkio = block_io.to_resources()?;
+        }
+
+        for limit in &self.hugepage_limits {
+            let size = limit.page_size.parse().map_err(|_| invalid())?;
+            resources
+                .hugetlb
+                .limits
+                .insert(size, hugetlb::Limit::Bytes(limit.limit));
+        }
+
+        if let Some(ref network) = self.network {
+            resources.net_cls.classid = network.class_id.map(Into::into);
+            resources.net_prio.ifpriomap = network
+                .priorities
+                .iter()
+                .map(|p| (p.name.clone(), p.priority))
+                .collect();
+        }
+
+        resources.rdma.max = self
+            .rdma
+            .iter()
+            .map(|(name, rdma)| {
+                let max = |n: Option<u32>| n.map_or(Max::Max, Max::Limit);
+                let limit = rdma::Limit {
+                    hca_handle: max(rdma.hca_handles),
+                    hca_object: max(rdma.hca_objects),
+                };
+                (name.clone(), limit)
+            })
+            .collect();
+
+        Ok(resources)
+    }
+
+    /// Converts `Resources` into `LinuxResources`.
+    ///
+    /// See the [module-level documentation] for how the fields are mapped. Fields of subsystems
+    /// not covered by the OCI runtime specification (e.g. freezer) are ignored. Entries of maps are
+    /// sorted, e.g. by device number, so that the result is deterministic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use controlgroup::{Max, v1::{oci::LinuxResources, Resources}};
+    ///
+    /// let mut resources = Resources::default();
+    /// resources.cpu.shares = Some(512);
+    /// resources.pids.max = Some(Max::Max);
+    ///
+    /// let linux = LinuxResources::from_resources(&resources);
+    /// assert_eq!(linux.cpu.unwrap().shares, Some(512));
+    /// assert_eq!(linux.pids.unwrap().limit, -1);
+    /// ```
+    ///
+    /// [module-level documentation]: index.html
+    pub fn from_resources(resources: &Resources) -> Self {
+        fn non_default<T: Default + PartialEq>(t: T) -> Option<T> {
+            if t == T::default() {
+                None
+            } else {
+                Some(t)
+            }
+        }
+
+        let rule = |allow: bool, access: &Access| LinuxDeviceCgroup {
+            allow,
+            device_type: Some(access.device_type.to_string()),
+            major: from_device_number(access.device_number.major),
+            minor: from_device_number(access.device_number.minor),
+            access: Some(access.access_type.to_string()),
+        };
+
+        let devices = resources
+            .devices
+            .deny
+            .iter()
+            .map(|a| rule(false, a))
+            .chain(resources.devices.allow.iter().map(|a| rule(true, a)))
+            .collect();
+
+        let memory = &resources.memory;
+        let memory = non_default(LinuxMemory {
+            limit: memory.limit_in_bytes,
+            reservation: memory.soft_limit_in_bytes,
+            swap: memory.memsw_limit_in_bytes,
+            kernel: memory.kmem_limit_in_bytes,
+            kernel_tcp: memory.kmem_tcp_limit_in_bytes,
+            swappiness: memory.swappiness,
+            disable_oom_killer: None,
+            use_hierarchy: memory.use_hierarchy,
+        });
+
+        let (cpu, cpuset) = (&resources.cpu, &resources.cpuset);
+        let cpu = non_default(LinuxCpu {
+            shares: cpu.shares,
+            quota: cpu.cfs_quota_us,
+            period: cpu.cfs_period_us,
+            realtime_runtime: cpu.rt_runtime_us,
+            realtime_period: cpu.rt_period_us,
+            cpus: cpuset.cpus.as_ref().map(ToString::to_string),
+            mems: cpuset.mems.as_ref().map(ToString::to_string),
+        });
+
+        let pids = resources.pids.max.map(|max| LinuxPids {
+            limit: match max {
+                Max::Max => -1,
+                Max::Limit(n) => i64::from(n),
+            },
+        });
+
+        let mut sizes = resources.hugetlb.limits.iter().collect::<Vec<_>>();
+        sizes.sort_by_key(|(size, _)| size.pages_to_bytes(1));
+        let hugepage_limits = sizes
+            .into_iter()
+            .map(|(size, limit)| LinuxHugepageLimit {
+                page_size: size.to_string(),
+                limit: match *limit {
+                    hugetlb::Limit::Bytes(bytes) => bytes,
+                    hugetlb::Limit::Pages(pages) => size.pages_to_bytes(pages),
+                },
+            })
+            .collect();
+
+        let mut priorities = resources
+            .net_prio
+            .ifpriomap
+            .iter()
+            .map(|(name, &priority)| LinuxInterfacePriority {
+                name: name.clone(),
+                priority,
+            })
+            .collect::<Vec<_>>();
+        priorities.sort_by(|a, b| a.name.cmp(&b.name));
+        let network = non_default(LinuxNetwork {
+            class_id: resources.net_cls.classid.map(Into::into),
+            priorities,
+        });
+
+        let rdma = resources
+            .rdma
+            .max
+            .iter()
+            .map(|(name, limit)| {
+                let limit_of = |max: Max| match max {
+                    Max::Max => None,
+                    Max::Limit(n) => Some(n),
+                };
+                let rdma = LinuxRdma {
+                    hca_handles: limit_of(limit.hca_handle),
+                    hca_objects: limit_of(limit.hca_object),
+                };
+                (name.clone(), rdma)
+            })
+            .collect();
+
+        Self {
+            devices,
+            memory,
+            cpu,
+            pids,
+            block_io: non_default(LinuxBlockIo::from_resources(&resources.blkio)),
+            hugepage_limits,
+            network,
+            rdma,
+        }
+    }
+}
+
+impl LinuxBlockIo {
+    fn to_resources(&self) -> Result<blkio::Resources> {
+        let mut resources = blkio::Resources {
+            weight: self.weight,
+            leaf_weight: self.leaf_weight,
+            ..blkio::Resources::default()
+        };
+
+        for w in &self.weight_device {
+            let device = device(w.major, w.minor)?;
+            if let Some(weight) = w.weight {
+                resources.weight_device.insert(device, weight);
+            }
+            if let Some(leaf_weight) = w.leaf_weight {
+                resources.leaf_weight_device.insert(device, leaf_weight);
+            }
+        }
+
+        let throttle = |throttles: &[LinuxThrottleDevice]| -> Result<HashMap<Device, u64>> {
+            throttles
+                .iter()
+                .map(|t| Ok((device(t.major, t.minor)?, t.rate)))
+                .collect()
+        };
+
+        resources.read_bps_device = throttle(&self.throttle_read_bps_device)?;
+        resources.write_bps_device = throttle(&self.throttle_write_bps_device)?;
+        resources.read_iops_device = throttle(&self.throttle_read_iops_device)?;
+        resources.write_iops_device = throttle(&self.throttle_write_iops_device)?;
+
+        Ok(resources)
+    }
+
+    fn from_resources(resources: &blkio::Resources) -> Self {
+        let mut weight_device = BTreeMap::new();
+        for (device, &weight) in &resources.weight_device {
+            weight_device
+                .entry(from_device(*device))
+                .or_insert((None, None))
+                .0 = Some(weight);
+        }
+        for (device, &leaf_weight) in &resources.leaf_weight_device {
+            weight_device
+                .entry(from_device(*device))
+                .or_insert((None, None))
+                .1 = Some(leaf_weight);
+        }
+
+        let throttle = |throttles: &HashMap<Device, u64>| {
+            let mut throttles = throttles
+                .iter()
+                .map(|(device, &rate)| {
+                    let (major, minor) = from_device(*device);
+                    LinuxThrottleDevice { major, minor, rate }
+                })
+                .collect::<Vec<_>>();
+            throttles.sort_by_key(|t| (t.major, t.minor));
+            throttles
+        };
+
+        Self {
+            weight: resources.weight,
+            leaf_weight: resources.leaf_weight,
+            weight_device: weight_device
+                .into_iter()
+                .map(
+                    |((major, minor), (weight, leaf_weight))| LinuxWeightDevice {
+                        major,
+                        minor,
+                        weight,
+                        leaf_weight,
+                    },
+                )
+                .collect(),
+            throttle_read_bps_device: throttle(&resources.read_bps_device),
+            throttle_write_bps_device: throttle(&resources.write_bps_device),
+            throttle_read_iops_device: throttle(&resources.read_iops_device),
+            throttle_write_iops_device: throttle(&resources.write_iops_device),
+        }
+    }
+}
+
+fn invalid() -> Error {
+    Error::new(ErrorKind::InvalidArgument)
+}
+
+fn device_number(n: Option<i64>) -> Result<DeviceNumber> {
+    match n {
+        None | Some(-1) => Ok(DeviceNumber::Any),
+        Some(n) => u16::try_from(n)
+            .map(DeviceNumber::Number)
+            .map_err(|_| invalid()),
+    }
+}
+
+fn from_device_number(n: DeviceNumber) -> Option<i64> {
+    match n {
+        DeviceNumber::Any => None,
+        DeviceNumber::Number(n) => Some(i64::from(n)),
+    }
+}
+
+fn device(major: i64, minor: i64) -> Result<Device> {
+    let number = |n: i64| u16::try_from(n).map_err(|_| invalid());
+    Ok([number(major)?, number(minor)?].into())
+}
+
+// Blkio files do not accept wildcards, so `Any` is not expected here and mapped to -1.
+fn from_device(device: Device) -> (i64, i64) {
+    let number = |n| from_device_number(n).unwrap_or(-1);
+    (number(device.major), number(device.minor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::net_cls::ClassId;
+
+    const CONFIG: &str = r#"{
+        "ociVersion": "1.0.2",
+        "linux": {
+            "resources": {
+                "devices": [
+                    { "allow": false, "access": "rwm" },
+                    { "allow": true, "type": "c", "major": 1, "minor": 3, "access": "rwm" },
+                    { "allow": true, "type": "c", "major": 136, "access": "rw" }
+                ],
+                "memory": {
+                    "limit": 536870912,
+                    "reservation": 268435456,
+                    "swap": 1073741824,
+                    "kernel": -1,
+                    "kernelTCP": -1,
+                    "swappiness": 0,
+                    "disableOOMKiller": false,
+                    "useHierarchy": true
+                },
+                "cpu": {
+                    "shares": 1024,
+                    "quota": 500000,
+                    "period": 1000000,
+                    "realtimeRuntime": 950000,
+                    "realtimePeriod": 1000000,
+                    "cpus": "2-3",
+                    "mems": "0"
+                },
+                "pids": { "limit": 32771 },
+                "blockIO": {
+                    "weight": 500,
+                    "leafWeight": 300,
+                    "weightDevice": [
+                        { "major": 8, "minor": 0, "weight": 600, "leafWeight": 300 },
+                        { "major": 8, "minor": 16, "weight": 500 }
+                    ],
+                    "throttleReadBpsDevice": [{ "major": 8, "minor": 0, "rate": 600 }],
+                    "throttleWriteIOPSDevice": [{ "major": 8, "minor": 16, "rate": 300 }]
+                },
+                "hugepageLimits": [{ "pageSize": "2MB", "limit": 209715200 }],
+                "network": {
+                    "classID": 1048577,
+                    "priorities": [{ "name": "eth0", "priority": 500 }]
+                },
+                "rdma": {
+                    "mlx5_1": { "hcaHandles": 3, "hcaObjects": 10000 },
+                    "mlx4_0": { "hcaObjects": 1000 }
+                }
+            }
+        }
+    }"#;
+
+    fn resources() -> Result<Resources> {
+        let mut resources = Resources::default();
+
+        resources.devices.deny = vec!["a *:* rwm".parse()?];
+        resources.devices.allow = vec!["c 1:3 rwm".parse()?, "c 136:* rw".parse()?];
+
+        resources.memory.limit_in_bytes = Some(512 << 20);
+        resources.memory.soft_limit_in_bytes = Some(256 << 20);
+        resources.memory.memsw_limit_in_bytes = Some(1 << 30);
+        resources.memory.kmem_limit_in_bytes = Some(-1);
+        resources.memory.kmem_tcp_limit_in_bytes = Some(-1);
+        resources.memory.swappiness = Some(0);
+        resources.memory.use_hierarchy = Some(true);
+
+        resources.cpu.shares = Some(1024);
+        resources.cpu.cfs_quota_us = Some(500_000);
+        resources.cpu.cfs_period_us = Some(1_000_000);
+        resources.cpu.rt_runtime_us = Some(950_000);
+        resources.cpu.rt_period_us = Some(1_000_000);
+        resources.cpuset.cpus = Some("2-3".parse()?);
+        resources.cpuset.mems = Some("0".parse()?);
+
+        resources.pids.max = Some(Max::Limit(32771));
+
+        resources.blkio.weight = Some(500);
+        resources.blkio.leaf_weight = Some(300);
+        resources.blkio.weight_device = hashmap! { ([8, 0].into(), 600), ([8, 16].into(), 500) };
+        resources.blkio.leaf_weight_device = hashmap! { ([8, 0].into(), 300) };
+        resources.blkio.read_bps_device = hashmap! { ([8, 0].into(), 600) };
+        resources.blkio.write_iops_device = hashmap! { ([8, 16].into(), 300) };
+
+        resources.hugetlb.limits = hashmap! {
+            (hugetlb::HugepageSize::Mb2, hugetlb::Limit::Bytes(200 << 20))
+        };
+
+        resources.net_cls.classid = Some(ClassId::from([0x10, 0x1]));
+        resources.net_prio.ifpriomap = hashmap! { ("eth0".to_string(), 500) };
+
+        resources.rdma.max = hashmap! {
+            (
+                "mlx5_1".to_string(),
+                rdma::Limit { hca_handle: Max::Limit(3), hca_object: Max::Limit(10000) },
+            ),
+            (
+                "mlx4_0".to_string(),
+                rdma::Limit { hca_handle: Max::Max, hca_object: Max::Limit(1000) },
+            ),
+        };
+
+        Ok(resources)
+    }
+
+    #[test]
+    fn test_to_resources() -> Result<()> {
+        let linux = LinuxResources::from_config(CONFIG.as_bytes())?;
+        assert_eq!(linux.to_resources()?, resources()?);
+
+        assert_eq!(
+            LinuxResources::from_config(&b"{}"[..])?,
+            LinuxResources::default()
+        );
+        assert_eq!(
+            LinuxResources::default().to_resources()?,
+            Resources::default()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_resources_err() {
+        let to_resources = |json: &str| {
+            serde_json::from_str::<LinuxResources>(json)
+                .unwrap()
+                .to_resources()
+                .unwrap_err()
+                .kind()
+        };
+
+        assert_eq!(
+            to_resources(r#"{ "devices": [{ "allow": true, "type": "x" }] }"#),
+            ErrorKind::Parse
+        );
+        assert_eq!(
+            to_resources(r#"{ "devices": [{ "allow": true, "access": "rwx" }] }"#),
+            ErrorKind::Parse
+        );
+        assert_eq!(
+            to_resources(r#"{ "devices": [{ "allow": true, "major": 65536 }] }"#),
+            ErrorKind::InvalidArgument
+        );
+        assert_eq!(
+            to_resources(
+                r#"{ "devices": [
+                    { "allow": true, "type": "a" },
+                    { "allow": false, "type": "c", "major": 1, "minor": 3 }
+                ] }"#
+            ),
+            ErrorKind::InvalidArgument
+        );
+        assert_eq!(
+            to_resources(r#"{ "cpu": { "cpus": "0-" } }"#),
+            ErrorKind::Parse
+        );
+        assert_eq!(
+            to_resources(r#"{ "pids": { "limit": 4294967296 } }"#),
+            ErrorKind::InvalidArgument
+        );
+        assert_eq!(
+            to_resources(r#"{ "hugepageLimits": [{ "pageSize": "3MB", "limit": 0 }] }"#),
+            ErrorKind::InvalidArgument
+        );
+
+        assert_eq!(
+            LinuxResources::from_config(&b"{ \"linux\": { \"resources\": 1 } }"[..])
+                .unwrap_err()
+                .kind(),
+            ErrorKind::Parse
+        );
+    }
+
+    #[test]
+    fn test_pids_unlimited() -> Result<()> {
+        for &limit in &[0, -1] {
+            let linux = LinuxResources {
+                pids: Some(LinuxPids { limit }),
+                ..LinuxResources::default()
+            };
+            assert_eq!(linux.to_resources()?.pids.max, Some(Max::Max));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_resources() -> Result<()> {
+        let linux = LinuxResources::from_resources(&resources()?);
+
+        assert_eq!(linux.to_resources()?, resources()?);
+        assert_eq!(
+            linux.block_io.as_ref().unwrap().weight_device,
+            vec![
+                LinuxWeightDevice {
+                    major: 8,
+                    minor: 0,
+                    weight: Some(600),
+                    leaf_weight: Some(300),
+                },
+                LinuxWeightDevice {
+                    major: 8,
+                    minor: 16,
+                    weight: Some(500),
+                    leaf_weight: None,
+                },
+            ]
+        );
+        assert_eq!(linux.devices[2].minor, None);
+
+        // round trip through JSON
+        let json = serde_json::to_string(&linux).unwrap();
+        assert!(json.contains("\"blockIO\""));
+        assert!(json.contains("\"throttleWriteIOPSDevice\""));
+        assert!(json.contains("\"classID\":1048577"));
+        assert_eq!(
+            serde_json::from_str::<LinuxResources>(&json).unwrap(),
+            linux
+        );
+
+        assert_eq!(
+            LinuxResources::from_resources(&Resources::default()),
+            LinuxResources::default()
+        );
+
+        Ok(())
+    }
+}
@@ -57,4 +57,9 @@ impl_cgroup! {
     fn apply(&mut self, _resources: &v1::Resources) -> Result<()> {
         Ok(())
     }
+
+    /// Returns the default `Resources` as a perf_event cgroup has no parameters.
+    fn resources(&self) -> Result<v1::Resources> {
+        Ok(v1::Resources::default())
+    }
 }
@@ -43,7 +43,7 @@ use std::path::PathBuf;
 
 use crate::{
     parse::{parse, parse_next},
-    v1::{self, cgroup::CgroupHelper, Cgroup, CgroupPath},
+    v1::{self, cgroup::CgroupHelper, CgroupPath},
     Max, Result,
 };
 
@@ -57,6 +57,8 @@ pub struct Subsystem {
 ///
 /// See the kernel's documentation for more information about the fields.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Resources {
     /// If [`Max::Max`], the system does not limit the number of processes this cgroup can have. If
     /// [`Max::Limit(n)`], this cgroup can have `n` processes at most.
@@ -77,6 +79,11 @@ impl_cgroup! {
 
         Ok(())
     }
+
+    /// Reads `resources.pids.max` from this cgroup.
+    fn resources(&self) -> Result<v1::Resources> {
+        Ok(self::Resources { max: Some(self.max()?) }.into())
+    }
 }
 
 impl Subsystem {
@@ -142,6 +149,7 @@ fn parse_events(mut reader: impl std::io::Read) -> Result<(Max, u64)> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::v1::Cgroup;
 
     #[test]
     fn test_subsystem_create_file_exists() -> Result<()> {
@@ -159,6 +167,23 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_subsystem_resources() -> Result<()> {
+        let mut cgroup =
+            Subsystem::new(CgroupPath::new(v1::SubsystemKind::Pids, gen_cgroup_name!()));
+        cgroup.create()?;
+        assert_eq!(cgroup.resources()?.pids.max, Some(Max::Max));
+
+        let resources = Resources {
+            max: Some(Max::Limit(42)),
+        }
+        .into();
+        cgroup.apply(&resources)?;
+        assert_eq!(cgroup.resources()?, resources);
+
+        cgroup.delete()
+    }
+
     #[test]
     fn test_subsystem_max() -> Result<()> {
         gen_subsystem_test!(Pids, max, Max::Max, set_max, Max::Limit(42))
@@ -0,0 +1,364 @@
+//! Cgroup membership of processes.
+//!
+//! [`ProcCgroup`] parses `/proc/<pid>/cgroup` file, which lists the cgroups a process belongs to in
+//! each hierarchy. Combined with [`MountInfo`], the cgroups can be converted into [`CgroupPath`]s
+//! or a [`UnifiedRepr`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # fn main() -> controlgroup::Result<()> {
+//! use controlgroup::{Pid, v1::{mount::MountInfo, proc_cgroup::ProcCgroup, SubsystemKind}};
+//!
+//! let pid = Pid::from(std::process::id());
+//!
+//! let proc_cgroup = ProcCgroup::load(pid)?;
+//! println!("{:?}", proc_cgroup.path(SubsystemKind::Cpu));
+//!
+//! let paths = proc_cgroup.cgroup_paths(&MountInfo::load()?);
+//! println!("{:?}", paths.get(&SubsystemKind::Memory));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`ProcCgroup`]: struct.ProcCgroup.html
+//! [`MountInfo`]: ../mount/struct.MountInfo.html
+//! [`CgroupPath`]: ../struct.CgroupPath.html
+//! [`UnifiedRepr`]: ../struct.UnifiedRepr.html
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    parse::parse_next,
+    v1::{mount::MountInfo, CgroupPath, SubsystemKind},
+    v2, Error, ErrorKind, Pid, Result,
+};
+
+/// Cgroups a process belongs to, as in `/proc/<pid>/cgroup` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProcCgroup {
+    entries: Vec<Entry>,
+}
+
+/// An entry of `/proc/<pid>/cgroup` file, i.e. the cgroup a process belongs to in a hierarchy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Entry {
+    hierarchy_id: u32,
+    subsystems: Vec<SubsystemKind>,
+    name: Option<String>,
+    path: PathBuf,
+}
+
+impl ProcCgroup {
+    /// Reads and parses `/proc/<pid>/cgroup` file of a process.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to read and parse `/proc/<pid>/cgroup` file.
+    pub fn load(pid: Pid) -> Result<Self> {
+        Self::parse(File::open(format!("/proc/{}/cgroup", pid))?)
+    }
+
+    /// Parses a content in the format of `/proc/<pid>/cgroup` file.
+    ///
+    /// Subsystems not available in this crate are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`ErrorKind::Parse`] if failed to parse the content.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::Path;
+    /// use controlgroup::v1::{proc_cgroup::ProcCgroup, SubsystemKind};
+    ///
+    /// let content = "\
+    /// 4:cpu,cpuacct:/students/charlie
+    /// 1:name=systemd:/user.slice
+    /// 0::/user.slice/session-1.scope
+    /// ";
+    /// let proc_cgroup = ProcCgroup::parse(content.as_bytes())?;
+    ///
+    /// assert_eq!(proc_cgroup.path(SubsystemKind::Cpuacct), Some(Path::new("/students/charlie")));
+    /// assert_eq!(proc_cgroup.named_path("systemd"), Some(Path::new("/user.slice")));
+    /// assert_eq!(proc_cgroup.unified_path(), Some(Path::new("/user.slice/session-1.scope")));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ErrorKind::Parse`]: ../../enum.ErrorKind.html#variant.Parse
+    pub fn parse(reader: impl io::Read) -> Result<Self> {
+        let mut entries = Vec::new();
+
+        for line in io::BufReader::new(reader).lines() {
+            let line = line?;
+
+            // Format: "hierarchy_id:controller_list:cgroup_path". The path may contain ':'.
+            let mut entry = line.splitn(3, ':');
+            let hierarchy_id = parse_next(&mut entry)?;
+            let controllers = entry.next().ok_or_else(|| Error::new(ErrorKind::Parse))?;
+            let path = entry.next().ok_or_else(|| Error::new(ErrorKind::Parse))?;
+
+            let mut subsystems = Vec::new();
+            let mut name = None;
+            for c in controllers.split(',').filter(|c| !c.is_empty()) {
+                let mut kv = c.splitn(2, '=');
+                match (kv.next(), kv.next()) {
+                    (Some("name"), Some(n)) => name = Some(n.to_string()),
+                    (Some(kind), None) => {
+                        if let Ok(kind) = kind.parse::<SubsystemKind>() {
+                            subsystems.push(kind);
+                        }
+                    }
+                    _ => {
+                        bail_parse!();
+                    }
+                }
+            }
+
+            entries.push(Entry {
+                hierarchy_id,
+                subsystems,
+                name,
+                path: PathBuf::from(path),
+            });
+        }
+
+        Ok(Self { entries })
+    }
+
+    /// Returns the list of entries, one for each hierarchy.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Returns the path to the cgroup the process belongs to in the hierarchy to which a subsystem
+    /// is attached, relative to the root of the hierarchy. Returns `None` if no entry for the
+    /// subsystem is found.
+    pub fn path(&self, kind: SubsystemKind) -> Option<&Path> {
+        self.entries
+            .iter()
+            .find(|e| e.subsystems.contains(&kind))
+            .map(|e| e.path.as_path())
+    }
+
+    /// Returns the path to the cgroup the process belongs to in a named hierarchy (e.g.
+    /// `name=systemd`), or `None` if no entry for the hierarchy is found.
+    pub fn named_path(&self, name: &str) -> Option<&Path> {
+        self.entries
+            .iter()
+            .find(|e| e.name() == Some(name))
+            .map(|e| e.path.as_path())
+    }
+
+    /// Returns the path to the cgroup the process belongs to in the v2 unified hierarchy (the `0::`
+    /// entry), or `None` if no entry for the unified hierarchy is found.
+    pub fn unified_path(&self) -> Option<&Path> {
+        self.entries
+            .iter()
+            .find(|e| e.is_unified())
+            .map(|e| e.path.as_path())
+    }
+
+    /// Returns a map of subsystem kinds to the paths to the cgroups the process belongs to.
+    ///
+    /// The paths are resolved against the mount points in `mount_info`. Subsystems not mounted are
+    /// not included in the map. Co-mounted subsystems (e.g. `cpu,cpuacct`) are mapped to the same
+    /// path.
+    pub fn cgroup_paths(&self, mount_info: &MountInfo) -> HashMap<SubsystemKind, CgroupPath> {
+        let mut paths = HashMap::new();
+
+        for entry in &self.entries {
+            for &kind in &entry.subsystems {
+                if let Some(hierarchy) = mount_info.hierarchy(kind) {
+                    paths.insert(
+                        kind,
+                        CgroupPath::with_root(
+                            hierarchy.mount_point().to_path_buf(),
+                            relative_name(&entry.path, hierarchy.root()),
+                        ),
+                    );
+                }
+            }
+        }
+
+        paths
+    }
+
+    /// Returns a handler of the cgroup the process belongs to in the v2 unified hierarchy, resolved
+    /// against the mount point in `mount_info`. Returns `None` if the unified hierarchy is not
+    /// mounted or no entry for it is found.
+    pub fn unified_cgroup(&self, mount_info: &MountInfo) -> Option<v2::Cgroup> {
+        match (mount_info.unified_mount_point(), self.unified_path()) {
+            (Some(root), Some(path)) => Some(v2::Cgroup::with_root(
+                root.to_path_buf(),
+                relative_name(path, Path::new("/")),
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl Entry {
+    /// Returns the ID of the hierarchy. The ID is always 0 for the v2 unified hierarchy.
+    pub fn hierarchy_id(&self) -> u32 {
+        self.hierarchy_id
+    }
+
+    /// Returns the subsystems attached to the hierarchy.
+    pub fn subsystems(&self) -> &[SubsystemKind] {
+        &self.subsystems
+    }
+
+    /// Returns the name of the hierarchy specified with `name=` mount option, if any.
+    #[allow(clippy::option_as_ref_deref)] // `Option::as_deref` requires Rust 1.40
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(String::as_str)
+    }
+
+    /// Returns the path to the cgroup, relative to the root of the hierarchy.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns whether this entry is for the v2 unified hierarchy.
+    pub fn is_unified(&self) -> bool {
+        self.hierarchy_id == 0 && self.subsystems.is_empty() && self.name.is_none()
+    }
+}
+
+// Converts a cgroup path in `/proc/<pid>/cgroup` into a cgroup name relative to the mount point,
+// whose root in the hierarchy is `mount_root`.
+fn relative_name(path: &Path, mount_root: &Path) -> PathBuf {
+    let path = path.strip_prefix(mount_root).unwrap_or(path);
+    path.strip_prefix("/").unwrap_or(path).to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use SubsystemKind::*;
+
+    const CONTENT: &str = "\
+12:pids:/user.slice/user-1000.slice
+11:misc:/
+5:cpu,cpuacct:/students/charlie
+4:memory:/docker/abc/students/charlie
+3:net_cls,net_prio:/
+1:name=systemd:/user.slice/user-1000.slice/session-1.scope
+0::/user.slice/user-1000.slice/session-1.scope
+";
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        let proc_cgroup = ProcCgroup::parse(CONTENT.as_bytes())?;
+
+        assert_eq!(proc_cgroup.entries().len(), 7);
+        assert_eq!(
+            proc_cgroup.entries()[2],
+            Entry {
+                hierarchy_id: 5,
+                subsystems: vec![Cpu, Cpuacct],
+                name: None,
+                path: PathBuf::from("/students/charlie"),
+            }
+        );
+        assert!(proc_cgroup.entries()[1].subsystems().is_empty());
+        assert!(!proc_cgroup.entries()[1].is_unified());
+        assert!(proc_cgroup.entries()[6].is_unified());
+
+        assert_eq!(proc_cgroup.path(Cpu), Some(Path::new("/students/charlie")));
+        assert_eq!(proc_cgroup.path(NetPrio), Some(Path::new("/")));
+        assert!(proc_cgroup.path(Freezer).is_none());
+
+        assert_eq!(
+            proc_cgroup.named_path("systemd"),
+            Some(Path::new("/user.slice/user-1000.slice/session-1.scope"))
+        );
+        assert_eq!(
+            proc_cgroup.unified_path(),
+            Some(Path::new("/user.slice/user-1000.slice/session-1.scope"))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_err() {
+        for case in &["invalid:cpu:/\n", "1:cpu\n", "1:cpu=x:/\n"] {
+            assert_eq!(
+                ProcCgroup::parse(case.as_bytes()).unwrap_err().kind(),
+                ErrorKind::Parse
+            );
+        }
+    }
+
+    #[test]
+    fn test_cgroup_paths() -> Result<()> {
+        const MOUNTINFO: &str = "\
+33 32 0:29 / /sys/fs/cgroup/unified rw - cgroup2 cgroup2 rw
+37 32 0:33 / /sys/fs/cgroup/cpu,cpuacct rw - cgroup cgroup rw,cpu,cpuacct
+38 32 0:34 / /sys/fs/cgroup/net_cls,net_prio rw - cgroup cgroup rw,net_cls,net_prio
+39 32 0:35 /docker/abc /sys/fs/cgroup/memory rw - cgroup cgroup rw,memory
+";
+        let mount_info = MountInfo::parse(MOUNTINFO.as_bytes())?;
+        let proc_cgroup = ProcCgroup::parse(CONTENT.as_bytes())?;
+
+        let name = PathBuf::from("students/charlie");
+        assert_eq!(
+            proc_cgroup.cgroup_paths(&mount_info),
+            hashmap! {
+                (
+                    Cpu,
+                    CgroupPath::with_root(PathBuf::from("/sys/fs/cgroup/cpu,cpuacct"), name.clone())
+                ),
+                (
+                    Cpuacct,
+                    CgroupPath::with_root(PathBuf::from("/sys/fs/cgroup/cpu,cpuacct"), name.clone())
+                ),
+                (
+                    Memory,
+                    CgroupPath::with_root(PathBuf::from("/sys/fs/cgroup/memory"), name)
+                ),
+                (
+                    NetCls,
+                    CgroupPath::with_root(PathBuf::from("/sys/fs/cgroup/net_cls,net_prio"), PathBuf::new())
+                ),
+                (
+                    NetPrio,
+                    CgroupPath::with_root(PathBuf::from("/sys/fs/cgroup/net_cls,net_prio"), PathBuf::new())
+                ),
+            }
+        );
+
+        assert_eq!(
+            proc_cgroup.unified_cgroup(&mount_info),
+            Some(v2::Cgroup::with_root(
+                PathBuf::from("/sys/fs/cgroup/unified"),
+                PathBuf::from("user.slice/user-1000.slice/session-1.scope")
+            ))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load() -> Result<()> {
+        let proc_cgroup = ProcCgroup::load(Pid::from(std::process::id()))?;
+        let paths = proc_cgroup.cgroup_paths(&MountInfo::load()?);
+
+        assert!(paths.contains_key(&Cpu));
+        assert!(paths.values().all(|p| p.to_path_buf().exists()));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,477 @@
+//! Exporter of statistics in the Prometheus text exposition format.
+//!
+//! [`Exporter`] reads statistics of a [`UnifiedRepr`], or of a whole subtree of cgroups, and
+//! renders them as Prometheus metrics. Each sample is labeled with `cgroup`, the path of the
+//! cgroup relative to the root (e.g. `/students/charlie`), and with `device`, `cpu`, `operation`,
+//! or `size` where applicable.
+//!
+//! The following metrics are exported, if the corresponding subsystem is supported by the
+//! `UnifiedRepr` and the file is present on the system:
+//!
+//! - cpuacct: `cgroup_cpu_usage_seconds_total`, `cgroup_cpu_user_seconds_total`,
+//!   `cgroup_cpu_system_seconds_total`, `cgroup_cpu_usage_per_cpu_seconds_total`
+//! - CPU: `cgroup_cpu_periods_total`, `cgroup_cpu_throttled_periods_total`,
+//!   `cgroup_cpu_throttled_seconds_total`
+//! - memory: `cgroup_memory_usage_bytes`, `cgroup_memory_max_usage_bytes`,
+//!   `cgroup_memory_limit_bytes`, `cgroup_memory_failures_total`, `cgroup_memory_<field>_bytes`
+//!   for memory sizes in `memory.stat` (e.g. `cgroup_memory_rss_bytes`),
+//!   `cgroup_memory_page_faults_total`, `cgroup_memory_major_page_faults_total`
+//! - hugetlb: `cgroup_hugetlb_usage_bytes`, `cgroup_hugetlb_max_usage_bytes`,
+//!   `cgroup_hugetlb_failures_total`
+//! - blkio: `cgroup_blkio_io_service_bytes_total`, `cgroup_blkio_io_serviced_total`
+//! - pids: `cgroup_pids_current`, `cgroup_pids_fork_failures_total`
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # fn main() -> controlgroup::Result<()> {
+//! use std::path::PathBuf;
+//! use controlgroup::v1::{prometheus::Exporter, UnifiedRepr};
+//!
+//! let mut exporter = Exporter::new();
+//! exporter.add_recursive(&UnifiedRepr::new(PathBuf::from("students")))?;
+//!
+//! print!("{}", exporter);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! See `examples/prometheus_exporter.rs` for an HTTP server that serves the metrics.
+//!
+//! [`Exporter`]: struct.Exporter.html
+//! [`UnifiedRepr`]: ../struct.UnifiedRepr.html
+
+use std::{collections::BTreeMap, fmt, path::Path};
+
+use crate::{
+    v1::{blkio, cgroup::optional, hugetlb, UnifiedRepr},
+    Result,
+};
+
+/// Collector of metrics of cgroups, rendered in the Prometheus text exposition format via
+/// [`Display`].
+///
+/// Samples of the same metric are grouped together, even if they are added from different
+/// cgroups. Metrics are rendered in the lexicographical order of their names.
+///
+/// See the [module-level documentation] for more information.
+///
+/// [`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+/// [module-level documentation]: index.html
+#[derive(Debug, Default)]
+pub struct Exporter {
+    families: BTreeMap<&'static str, Family>,
+}
+
+#[derive(Debug)]
+struct Family {
+    help: &'static str,
+    kind: Kind,
+    samples: Vec<(String, String)>, // (labels, value)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Counter,
+    Gauge,
+}
+
+const NANOS_PER_SEC: f64 = 1_000_000_000.0;
+
+impl Exporter {
+    /// Creates a new empty exporter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads statistics of `cgroups` and adds them to this exporter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to read or parse a statistics file, with kind
+    /// [`ErrorKind::Io`] or [`ErrorKind::Parse`]. Files present only with specific kernel
+    /// configurations (e.g. `blkio.io_service_bytes`) are skipped if missing.
+    ///
+    /// [`ErrorKind::Io`]: ../../enum.ErrorKind.html#variant.Io
+    /// [`ErrorKind::Parse`]: ../../enum.ErrorKind.html#variant.Parse
+    pub fn add(&mut self, cgroups: &UnifiedRepr) -> Result<()> {
+        let name = match cgroups.name() {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+        let cgroup = Path::new("/").join(name);
+        let cgroup = label("cgroup", &cgroup.to_string_lossy());
+
+        if let Some(cpuacct) = cgroups.cpuacct() {
+            self.counter(
+                "cgroup_cpu_usage_seconds_total",
+                "Total CPU time consumed in seconds.",
+                &cgroup,
+                cpuacct.usage()? as f64 / NANOS_PER_SEC,
+            );
+            if let Some(user) = optional(cpuacct.usage_user())? {
+                self.counter(
+                    "cgroup_cpu_user_seconds_total",
+                    "CPU time consumed in the user mode in seconds.",
+                    &cgroup,
+                    user as f64 / NANOS_PER_SEC,
+                );
+            }
+            if let Some(sys) = optional(cpuacct.usage_sys())? {
+                self.counter(
+                    "cgroup_cpu_system_seconds_total",
+                    "CPU time consumed in the system (kernel) mode in seconds.",
+                    &cgroup,
+                    sys as f64 / NANOS_PER_SEC,
+                );
+            }
+            for (cpu, usage) in cpuacct.usage_percpu()?.into_iter().enumerate() {
+                self.counter(
+                    "cgroup_cpu_usage_per_cpu_seconds_total",
+                    "CPU time consumed on each CPU in seconds.",
+                    &format!("{},{}", cgroup, label("cpu", &cpu.to_string())),
+                    usage as f64 / NANOS_PER_SEC,
+                );
+            }
+        }
+
+        if let Some(cpu) = cgroups.cpu() {
+            let stat = cpu.stat()?;
+            self.counter(
+                "cgroup_cpu_periods_total",
+                "Number of elapsed enforcement periods.",
+                &cgroup,
+                stat.nr_periods,
+            );
+            self.counter(
+                "cgroup_cpu_throttled_periods_total",
+                "Number of throttled periods.",
+                &cgroup,
+                stat.nr_throttled,
+            );
+            self.counter(
+                "cgroup_cpu_throttled_seconds_total",
+                "Total time duration for which the cgroup has been throttled in seconds.",
+                &cgroup,
+                stat.throttled_time as f64 / NANOS_PER_SEC,
+            );
+        }
+
+        if let Some(memory) = cgroups.memory() {
+            self.gauge(
+                "cgroup_memory_usage_bytes",
+                "Current memory usage in bytes.",
+                &cgroup,
+                memory.usage_in_bytes()?,
+            );
+            self.gauge(
+                "cgroup_memory_max_usage_bytes",
+                "Maximum recorded memory usage in bytes.",
+                &cgroup,
+                memory.max_usage_in_bytes()?,
+            );
+            self.gauge(
+                "cgroup_memory_limit_bytes",
+                "Limit of memory usage in bytes.",
+                &cgroup,
+                memory.limit_in_bytes()?,
+            );
+            self.counter(
+                "cgroup_memory_failures_total",
+                "Number of times the memory usage hit the limit.",
+                &cgroup,
+                memory.failcnt()?,
+            );
+
+            let stat = memory.stat()?;
+
+            macro_rules! stat_bytes {
+                ($( ($field: ident, $name: literal, $help: literal) ),* $(, )?) => { $(
+                    self.gauge($name, $help, &cgroup, stat.$field);
+                )* };
+            }
+
+            stat_bytes! {
+                (cache, "cgroup_memory_cache_bytes", "Page cache memory in bytes."),
+                (rss, "cgroup_memory_rss_bytes", "Anonymous and swap cache memory in bytes."),
+                (rss_huge, "cgroup_memory_rss_huge_bytes", "Anonymous transparent hugepages in bytes."),
+                (shmem, "cgroup_memory_shmem_bytes", "Shared memory in bytes."),
+                (mapped_file, "cgroup_memory_mapped_file_bytes", "Memory-mapped files in bytes."),
+                (dirty, "cgroup_memory_dirty_bytes", "Memory waiting to be written back in bytes."),
+                (writeback, "cgroup_memory_writeback_bytes", "Memory being written back in bytes."),
+            }
+
+            if let Some(swap) = stat.swap {
+                self.gauge(
+                    "cgroup_memory_swap_bytes",
+                    "Swap usage in bytes.",
+                    &cgroup,
+                    swap,
+                );
+            }
+
+            self.counter(
+                "cgroup_memory_page_faults_total",
+                "Number of page faults.",
+                &cgroup,
+                stat.pgfault,
+            );
+            self.counter(
+                "cgroup_memory_major_page_faults_total",
+                "Number of major page faults.",
+                &cgroup,
+                stat.pgmajfault,
+            );
+        }
+
+        if let Some(hugetlb) = cgroups.hugetlb() {
+            for &size in hugetlb::SIZES.iter() {
+                if !hugetlb.size_supported(size) {
+                    continue;
+                }
+
+                let labels = format!("{},{}", cgroup, label("size", &size.to_string()));
+                self.gauge(
+                    "cgroup_hugetlb_usage_bytes",
+                    "Current hugepage TLB usage in bytes.",
+                    &labels,
+                    hugetlb.usage_in_bytes(size)?,
+                );
+                self.gauge(
+                    "cgroup_hugetlb_max_usage_bytes",
+                    "Maximum recorded hugepage TLB usage in bytes.",
+                    &labels,
+                    hugetlb.max_usage_in_bytes(size)?,
+                );
+                self.counter(
+                    "cgroup_hugetlb_failures_total",
+                    "Number of times the hugepage TLB usage hit the limit.",
+                    &labels,
+                    hugetlb.failcnt(size)?,
+                );
+            }
+        }
+
+        if let Some(blkio) = cgroups.blkio() {
+            if let Some(service) = optional(blkio.io_service_bytes())? {
+                self.io_service(
+                    "cgroup_blkio_io_service_bytes_total",
+                    "I/O service transferred in bytes.",
+                    &cgroup,
+                    &service,
+                );
+            }
+            if let Some(service) = optional(blkio.io_serviced())? {
+                self.io_service(
+                    "cgroup_blkio_io_serviced_total",
+                    "I/O service transferred in operation count.",
+                    &cgroup,
+                    &service,
+                );
+            }
+        }
+
+        if let Some(pids) = cgroups.pids() {
+            self.gauge(
+                "cgroup_pids_current",
+                "Current number of processes.",
+                &cgroup,
+                pids.current()?,
+            );
+            if let Some((_, fork_failures)) = optional(pids.events())? {
+                self.counter(
+                    "cgroup_pids_fork_failures_total",
+                    "Number of times fork failed due to the limit.",
+                    &cgroup,
+                    fork_failures,
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads statistics of `cgroups` and all their descendants, and adds them to this exporter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to read the directories of the cgroups, or statistics files.
+    /// See [`add`] for more information.
+    ///
+    /// [`add`]: #method.add
+    pub fn add_recursive(&mut self, cgroups: &UnifiedRepr) -> Result<()> {
+        for cgroups in cgroups.walk() {
+            self.add(&cgroups?)?;
+        }
+        Ok(())
+    }
+
+    fn io_service(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        cgroup: &str,
+        service: &blkio::IoService,
+    ) {
+        let mut devices = service.devices.iter().collect::<Vec<_>>();
+        devices.sort_by_key(|(device, _)| device.to_string());
+
+        for (device, ops) in devices {
+            for &(op, value) in &[
+                ("read", ops.read),
+                ("write", ops.write),
+                ("sync", ops.sync),
+                ("async", ops.async_),
+            ] {
+                let labels = format!(
+                    "{},{},{}",
+                    cgroup,
+                    label("device", &device.to_string()),
+                    label("operation", op)
+                );
+                self.counter(name, help, &labels, value);
+            }
+        }
+    }
+
+    fn counter(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        labels: &str,
+        value: impl fmt::Display,
+    ) {
+        self.push(name, help, Kind::Counter, labels, value);
+    }
+
+    fn gauge(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        labels: &str,
+        value: impl fmt::Display,
+    ) {
+        self.push(name, help, Kind::Gauge, labels, value);
+    }
+
+    fn push(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        kind: Kind,
+        labels: &str,
+        value: impl fmt::Display,
+    ) {
+        self.families
+            .entry(name)
+            .or_insert_with(|| Family {
+                help,
+                kind,
+                samples: Vec::new(),
+            })
+            .samples
+            .push((labels.to_string(), value.to_string()));
+    }
+}
+
+impl fmt::Display for Exporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, family) in &self.families {
+            writeln!(f, "# HELP {} {}", name, family.help)?;
+            writeln!(f, "# TYPE {} {}", name, family.kind)?;
+            for (labels, value) in &family.samples {
+                writeln!(f, "{}{{{}}} {}", name, labels, value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Counter => write!(f, "counter"),
+            Self::Gauge => write!(f, "gauge"),
+        }
+    }
+}
+
+fn label(name: &str, value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+
+    format!("{}=\"{}\"", name, escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::SubsystemKind;
+
+    #[test]
+    fn test_label() {
+        assert_eq!(label("cgroup", "/"), r#"cgroup="/""#);
+        assert_eq!(label("cgroup", "/a\"b\\c\nd"), r#"cgroup="/a\"b\\c\nd""#);
+    }
+
+    #[test]
+    fn test_exporter_render() {
+        let mut exporter = Exporter::new();
+        exporter.gauge("b_gauge", "Gauge.", &label("cgroup", "/x"), 1);
+        exporter.counter("a_total", "Counter.", &label("cgroup", "/x"), 2.5);
+        exporter.gauge("b_gauge", "Gauge.", &label("cgroup", "/y"), 3);
+
+        assert_eq!(
+            exporter.to_string(),
+            r#"# HELP a_total Counter.
+# TYPE a_total counter
+a_total{cgroup="/x"} 2.5
+# HELP b_gauge Gauge.
+# TYPE b_gauge gauge
+b_gauge{cgroup="/x"} 1
+b_gauge{cgroup="/y"} 3
+"#
+        );
+    }
+
+    #[test]
+    fn test_exporter_add_recursive() -> Result<()> {
+        let name = gen_cgroup_name!();
+        let subsystems = [SubsystemKind::Cpuacct, SubsystemKind::Pids];
+
+        let mut parent = UnifiedRepr::with_subsystems(name.clone(), &subsystems);
+        let mut child = UnifiedRepr::with_subsystems(name.join("child"), &subsystems);
+        parent.create()?;
+        child.create()?;
+
+        let mut exporter = Exporter::new();
+        exporter.add_recursive(&parent)?;
+        let text = exporter.to_string();
+
+        let parent_label = label("cgroup", &format!("/{}", name.display()));
+        let child_label = label("cgroup", &format!("/{}/child", name.display()));
+
+        assert!(text.contains("# TYPE cgroup_cpu_usage_seconds_total counter\n"));
+        assert!(text.contains(&format!(
+            "cgroup_cpu_usage_seconds_total{{{}}} 0\n",
+            parent_label
+        )));
+        assert!(text.contains(&format!(
+            "cgroup_cpu_usage_per_cpu_seconds_total{{{},cpu=\"0\"}} 0\n",
+            child_label
+        )));
+        assert!(text.contains(&format!("cgroup_pids_current{{{}}} 0\n", child_label)));
+        assert!(!text.contains("cgroup_memory_"));
+
+        child.delete()?;
+        parent.delete()
+    }
+}
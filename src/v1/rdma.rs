@@ -61,8 +61,9 @@
 use std::{collections::HashMap, fmt, path::PathBuf};
 
 use crate::{
+    file,
     parse::parse_next,
-    v1::{self, Cgroup, CgroupPath},
+    v1::{self, cgroup::CgroupHelper, Cgroup, CgroupPath},
     Error, ErrorKind, Max, Result,
 };
 
@@ -76,6 +77,8 @@ pub struct Subsystem {
 ///
 /// See the kernel's documentation for more information about the fields.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Resources {
     /// How much this cgroup can use each RDMA/IB device. The key is the device name, and the value
     /// is limit for the device.
@@ -86,6 +89,7 @@ pub struct Resources {
 
 /// Limit or usage of an RDMA/IB device.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Limit {
     /// Max number or usage of HCA handles.
     pub hca_handle: Max,
@@ -106,6 +110,11 @@ impl_cgroup! {
             self.set_max(max.iter())
         }
     }
+
+    /// Reads `resources.rdma.max` from this cgroup.
+    fn resources(&self) -> Result<v1::Resources> {
+        Ok(self::Resources { max: self.max()? }.into())
+    }
 }
 
 impl Subsystem {
@@ -143,14 +152,14 @@ impl Subsystem {
             T: crate::RefKv<K, Limit>,
             K: fmt::Display,
         {
-            use std::io::Write;
-
-            let mut file = self.open_file_write("rdma.max")?;
+            // one line per write
             for lim in limits {
                 let (device, limit) = lim.ref_kv();
-
-                // write!(file, "{} {}", interface, prio)?; // not work
-                file.write_all(format!("{} {}", device, limit).as_bytes())?;
+                file::write(
+                    self.backend(),
+                    self.path().join("rdma.max"),
+                    format!("{} {}", device, limit),
+                )?;
             }
 
             Ok(())
@@ -158,7 +167,7 @@ impl Subsystem {
     }
 }
 
-fn parse_limits(reader: impl std::io::Read) -> Result<HashMap<String, Limit>> {
+pub(crate) fn parse_limits(reader: impl std::io::Read) -> Result<HashMap<String, Limit>> {
     use std::io::{BufRead, BufReader};
 
     let mut result = HashMap::new();
@@ -0,0 +1,258 @@
+//! Release agent that notifies a daemon when cgroups become empty.
+//!
+//! When `notify_on_release` is enabled for a cgroup, the kernel executes the program written in
+//! `release_agent` file of the root cgroup, each time the cgroup becomes empty, with the path to
+//! the cgroup relative to the root as the first argument.
+//!
+//! This module provides [`run`], an entry point of such a program that forwards the path to a
+//! local Unix domain socket, and [`Listener`] that receives the paths on the socket as
+//! [`CgroupPath`]s. This crate also provides `controlgroup-release-agent` binary, which calls
+//! [`run`] with [`DEFAULT_SOCKET_PATH`].
+//!
+//! Note that the kernel executes the release agent with an almost empty environment, and does
+//! not pass any argument other than the path. So the socket path must be fixed in the agent
+//! program. If you need another socket path (e.g. a socket for each hierarchy), write your own
+//! tiny program that calls [`run`] with the path.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # fn main() -> controlgroup::Result<()> {
+//! use std::path::PathBuf;
+//! use controlgroup::v1::{
+//!     cpu, release_agent::{self, Listener}, Cgroup, CgroupPath, SubsystemKind,
+//! };
+//!
+//! let root = CgroupPath::new(SubsystemKind::Cpu, PathBuf::new());
+//! let listener = Listener::bind(release_agent::DEFAULT_SOCKET_PATH, root.clone())?;
+//!
+//! // Register the agent to the hierarchy.
+//! cpu::Subsystem::new(root).set_release_agent(b"/usr/local/bin/controlgroup-release-agent")?;
+//!
+//! let mut cgroup = cpu::Subsystem::new(
+//!     CgroupPath::new(SubsystemKind::Cpu, PathBuf::from("students/charlie")));
+//! cgroup.create()?;
+//! cgroup.set_notify_on_release(true)?;
+//!
+//! // Do something ...
+//!
+//! // Blocks until a cgroup in the hierarchy becomes empty.
+//! let emptied = listener.recv()?;
+//! println!("{:?} became empty", emptied);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`run`]: fn.run.html
+//! [`Listener`]: struct.Listener.html
+//! [`CgroupPath`]: ../struct.CgroupPath.html
+//! [`DEFAULT_SOCKET_PATH`]: constant.DEFAULT_SOCKET_PATH.html
+
+use std::{
+    ffi::OsStr,
+    fs, io,
+    os::unix::{
+        ffi::OsStrExt,
+        io::{AsRawFd, RawFd},
+        net::UnixDatagram,
+    },
+    path::{Path, PathBuf},
+};
+
+use crate::{v1::CgroupPath, Error, ErrorKind, Result};
+
+/// Path to the socket used by `controlgroup-release-agent` binary.
+pub const DEFAULT_SOCKET_PATH: &str = "/run/controlgroup-release-agent.sock";
+
+const MAX_MESSAGE_LEN: usize = 4096; // PATH_MAX
+
+/// Entry point of a release agent program. Sends the path passed by the kernel as the first
+/// argument of this process to a [`Listener`] bound to `socket_path`.
+///
+/// # Errors
+///
+/// Returns an error with kind [`ErrorKind::InvalidArgument`] if the first argument is missing.
+/// Returns an error if failed to send the path, with kind [`ErrorKind::Io`].
+///
+/// # Examples
+///
+/// ```no_run
+/// // In `main` of the agent program
+/// let socket_path = "/run/my-daemon/release-agent-cpu.sock";
+///
+/// if let Err(e) = controlgroup::v1::release_agent::run(socket_path) {
+///     eprintln!("{}", e);
+///     std::process::exit(1);
+/// }
+/// ```
+///
+/// [`Listener`]: struct.Listener.html
+/// [`ErrorKind::InvalidArgument`]: ../../enum.ErrorKind.html#variant.InvalidArgument
+/// [`ErrorKind::Io`]: ../../enum.ErrorKind.html#variant.Io
+pub fn run(socket_path: impl AsRef<Path>) -> Result<()> {
+    match std::env::args_os().nth(1) {
+        Some(path) => notify(socket_path, &path),
+        None => Err(Error::new(ErrorKind::InvalidArgument)),
+    }
+}
+
+/// Sends a path to a cgroup relative to the root of a hierarchy (e.g. `/students/charlie`) to a
+/// [`Listener`] bound to `socket_path`, as a release agent does.
+///
+/// # Errors
+///
+/// Returns an error if failed to send the path, with kind [`ErrorKind::Io`].
+///
+/// [`Listener`]: struct.Listener.html
+/// [`ErrorKind::Io`]: ../../enum.ErrorKind.html#variant.Io
+pub fn notify(socket_path: impl AsRef<Path>, cgroup_path: impl AsRef<OsStr>) -> Result<()> {
+    let socket = UnixDatagram::unbound()?;
+    socket.send_to(cgroup_path.as_ref().as_bytes(), socket_path)?;
+    Ok(())
+}
+
+/// Listener of paths to emptied cgroups, sent by a release agent.
+///
+/// The socket file is created by [`bind`], and removed when the listener is dropped.
+///
+/// `Listener` implements [`AsRawFd`], so that the socket can be integrated into your own event
+/// loop, e.g. using `epoll`. The socket becomes readable when a path is received.
+///
+/// See the [module-level documentation] for an example.
+///
+/// [`bind`]: #method.bind
+/// [`AsRawFd`]: https://doc.rust-lang.org/std/os/unix/io/trait.AsRawFd.html
+/// [module-level documentation]: index.html
+#[derive(Debug)]
+pub struct Listener {
+    socket: UnixDatagram,
+    socket_path: PathBuf,
+    root: CgroupPath,
+}
+
+impl Listener {
+    /// Binds a new listener to `socket_path`. Received paths are interpreted as relative to the
+    /// root of the hierarchy to which `root` belongs.
+    ///
+    /// An existing file at `socket_path` is removed before binding, e.g. a socket left by a
+    /// previous process.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to remove an existing file or to bind the socket, with kind
+    /// [`ErrorKind::Io`].
+    ///
+    /// [`ErrorKind::Io`]: ../../enum.ErrorKind.html#variant.Io
+    pub fn bind(socket_path: impl AsRef<Path>, root: CgroupPath) -> Result<Self> {
+        let socket_path = socket_path.as_ref().to_path_buf();
+
+        match fs::remove_file(&socket_path) {
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => {}
+            result => result?,
+        }
+
+        Ok(Self {
+            socket: UnixDatagram::bind(&socket_path)?,
+            socket_path,
+            root: root.subsystem_root(),
+        })
+    }
+
+    /// Blocks until a path is received, and returns the path to the emptied cgroup.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to receive a path, with kind [`ErrorKind::Io`].
+    ///
+    /// [`ErrorKind::Io`]: ../../enum.ErrorKind.html#variant.Io
+    pub fn recv(&self) -> Result<CgroupPath> {
+        let mut buf = [0; MAX_MESSAGE_LEN];
+        let len = self.socket.recv(&mut buf)?;
+
+        let path = Path::new(OsStr::from_bytes(&buf[..len]));
+        let name = path.strip_prefix("/").unwrap_or(path);
+        Ok(self.root.with_name(name.to_path_buf()))
+    }
+
+    /// Returns the path to an emptied cgroup if a path has been received, or `None` otherwise.
+    /// This method does not block.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to poll the socket or to receive a path, with kind
+    /// [`ErrorKind::Io`].
+    ///
+    /// [`ErrorKind::Io`]: ../../enum.ErrorKind.html#variant.Io
+    pub fn try_recv(&self) -> Result<Option<CgroupPath>> {
+        let mut pollfd = libc::pollfd {
+            fd: self.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+
+        match unsafe { libc::poll(&mut pollfd, 1, 0) } {
+            -1 => Err(io::Error::last_os_error().into()),
+            0 => Ok(None),
+            _ => self.recv().map(Some),
+        }
+    }
+}
+
+impl AsRawFd for Listener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.socket_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::SubsystemKind;
+
+    #[test]
+    fn test_listener() -> Result<()> {
+        let socket_path = std::env::temp_dir().join(format!(
+            "controlgroup_rs-release_agent-{}.sock",
+            std::process::id()
+        ));
+
+        let root = CgroupPath::new(SubsystemKind::Cpu, PathBuf::from("students"));
+        let listener = Listener::bind(&socket_path, root)?;
+        assert_eq!(listener.try_recv()?, None);
+
+        notify(&socket_path, "/students/charlie")?;
+        assert_eq!(
+            listener.try_recv()?,
+            Some(CgroupPath::new(
+                SubsystemKind::Cpu,
+                PathBuf::from("students/charlie")
+            ))
+        );
+
+        notify(&socket_path, "/")?;
+        assert_eq!(
+            listener.recv()?,
+            CgroupPath::new(SubsystemKind::Cpu, PathBuf::new())
+        );
+
+        drop(listener);
+        assert!(!socket_path.exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn err_notify() {
+        let socket_path = std::env::temp_dir().join("controlgroup_rs-release_agent-none.sock");
+        assert_eq!(
+            notify(&socket_path, "/students").unwrap_err().kind(),
+            ErrorKind::Io
+        );
+    }
+}
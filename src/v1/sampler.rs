@@ -0,0 +1,409 @@
+//! Periodic sampling of statistics and derived metrics.
+//!
+//! Many statistics of cgroups are cumulative counters, e.g. the total CPU time consumed by a
+//! cgroup, and are useful only as deltas over time. [`Sampler`] takes [`Snapshot`]s of the
+//! counters of a [`UnifiedRepr`], and computes [`Metrics`], such as CPU cores used and I/O bytes
+//! per second, from the deltas between consecutive snapshots.
+//!
+//! A counter that decreases between two snapshots is considered to have been reset (e.g. by
+//! [`cpuacct::Subsystem::reset`] or [`blkio::Subsystem::reset_stats`]), and its delta is
+//! computed as if it had counted up from zero.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # fn main() -> controlgroup::Result<()> {
+//! use std::{path::PathBuf, time::Duration};
+//! use controlgroup::v1::{sampler::Sampler, UnifiedRepr};
+//!
+//! let cgroups = UnifiedRepr::new(PathBuf::from("students/charlie"));
+//! let mut sampler = Sampler::new(cgroups);
+//!
+//! for metrics in sampler.iter(Duration::from_secs(1)).take(10) {
+//!     let metrics = metrics?;
+//!     println!("{:?} cores used", metrics.cpu_cores);
+//!     println!("{:?} major page faults/s", metrics.pgmajfault_per_sec);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Sampler`]: struct.Sampler.html
+//! [`Snapshot`]: struct.Snapshot.html
+//! [`Metrics`]: struct.Metrics.html
+//! [`UnifiedRepr`]: ../struct.UnifiedRepr.html
+//! [`cpuacct::Subsystem::reset`]: ../cpuacct/struct.Subsystem.html#method.reset
+//! [`blkio::Subsystem::reset_stats`]: ../blkio/struct.Subsystem.html#method.reset_stats
+
+use std::{
+    collections::HashMap,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    v1::{blkio, cgroup::optional, cpu, UnifiedRepr},
+    Device, Result,
+};
+
+/// Sampler of statistics of a set of cgroups.
+///
+/// See the [module-level documentation] for more information.
+///
+/// [module-level documentation]: index.html
+#[derive(Debug)]
+pub struct Sampler {
+    cgroups: UnifiedRepr,
+    last: Option<Snapshot>,
+}
+
+/// Raw counters of a set of cgroups at a point in time.
+///
+/// A field is `None` if the corresponding subsystem is not supported by the `UnifiedRepr`, or
+/// the file is not present on the system.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    /// When this snapshot was taken.
+    pub time: Instant,
+    /// Total CPU time consumed (in nanoseconds), as in `cpuacct.usage` file.
+    pub cpu_usage: Option<u64>,
+    /// Throttling statistics, as in `cpu.stat` file.
+    pub cpu_stat: Option<cpu::Stat>,
+    /// I/O service transferred (in bytes), as in `blkio.io_service_bytes` file.
+    pub io_service_bytes: Option<blkio::IoService>,
+    /// Number of page faults, as in `memory.stat` file.
+    pub pgfault: Option<u64>,
+    /// Number of major page faults, as in `memory.stat` file.
+    pub pgmajfault: Option<u64>,
+}
+
+/// Metrics derived from two consecutive snapshots.
+///
+/// A field is `None` if the corresponding counter is missing in either of the snapshots.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metrics {
+    /// Time elapsed between the snapshots.
+    pub interval: Duration,
+    /// Number of CPU cores used on average, e.g. `1.5` if the cgroup consumed 1.5 seconds of CPU
+    /// time per second.
+    pub cpu_cores: Option<f64>,
+    /// Ratio of the number of throttled periods to the number of elapsed periods, in `[0, 1]`.
+    pub throttled_ratio: Option<f64>,
+    /// Bytes read per second for each device.
+    pub read_bytes_per_sec: HashMap<Device, f64>,
+    /// Bytes written per second for each device.
+    pub write_bytes_per_sec: HashMap<Device, f64>,
+    /// Page faults per second.
+    pub pgfault_per_sec: Option<f64>,
+    /// Major page faults per second.
+    pub pgmajfault_per_sec: Option<f64>,
+}
+
+/// Iterator that samples metrics periodically, returned by [`Sampler::iter`].
+///
+/// [`Sampler::iter`]: struct.Sampler.html#method.iter
+#[derive(Debug)]
+pub struct Samples<'a> {
+    sampler: &'a mut Sampler,
+    interval: Duration,
+}
+
+impl Sampler {
+    /// Creates a new sampler of `cgroups`.
+    pub fn new(cgroups: UnifiedRepr) -> Self {
+        Self {
+            cgroups,
+            last: None,
+        }
+    }
+
+    /// Returns a reference to the sampled `UnifiedRepr`.
+    pub fn cgroups(&self) -> &UnifiedRepr {
+        &self.cgroups
+    }
+
+    /// Consumes this sampler and returns the sampled `UnifiedRepr`.
+    pub fn into_inner(self) -> UnifiedRepr {
+        self.cgroups
+    }
+
+    /// Returns the last snapshot taken by this sampler, if any.
+    pub fn last_snapshot(&self) -> Option<&Snapshot> {
+        self.last.as_ref()
+    }
+
+    /// Takes a new snapshot, and returns the metrics derived from the last and new snapshots.
+    /// Returns `None` on the first call, as there is no snapshot to compare with.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to take a snapshot. See [`Snapshot::take`].
+    ///
+    /// [`Snapshot::take`]: struct.Snapshot.html#method.take
+    pub fn sample(&mut self) -> Result<Option<Metrics>> {
+        let snapshot = Snapshot::take(&self.cgroups)?;
+        let metrics = self
+            .last
+            .as_ref()
+            .map(|last| Metrics::between(last, &snapshot));
+        self.last = Some(snapshot);
+        Ok(metrics)
+    }
+
+    /// Returns an iterator that samples metrics every `interval`, blocking the current thread
+    /// between samples. The iterator never ends; errors are yielded as items.
+    ///
+    /// If this sampler has not taken any snapshot yet, the first snapshot is taken on the first
+    /// call to `next`.
+    pub fn iter(&mut self, interval: Duration) -> Samples<'_> {
+        Samples {
+            sampler: self,
+            interval,
+        }
+    }
+}
+
+impl Iterator for Samples<'_> {
+    type Item = Result<Metrics>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let last = match self.sampler.last {
+            Some(ref last) => last.time,
+            None => {
+                if let Err(e) = self.sampler.sample() {
+                    return Some(Err(e));
+                }
+                Instant::now()
+            }
+        };
+
+        let elapsed = last.elapsed();
+        if elapsed < self.interval {
+            thread::sleep(self.interval - elapsed);
+        }
+
+        match self.sampler.sample() {
+            Ok(metrics) => metrics.map(Ok),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+impl Snapshot {
+    /// Reads the counters of `cgroups`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to read and parse `cpuacct.usage`, `cpu.stat`,
+    /// `blkio.io_service_bytes`, or `memory.stat` file, with kind [`ErrorKind::Io`] or
+    /// [`ErrorKind::Parse`]. A missing `blkio.io_service_bytes` file is not an error, since it is
+    /// present only with the CFQ I/O scheduler.
+    ///
+    /// [`ErrorKind::Io`]: ../../enum.ErrorKind.html#variant.Io
+    /// [`ErrorKind::Parse`]: ../../enum.ErrorKind.html#variant.Parse
+    pub fn take(cgroups: &UnifiedRepr) -> Result<Self> {
+        let memory_stat = cgroups.memory().map(|m| m.stat()).transpose()?;
+
+        Ok(Self {
+            time: Instant::now(),
+            cpu_usage: cgroups.cpuacct().map(|c| c.usage()).transpose()?,
+            cpu_stat: cgroups.cpu().map(|c| c.stat()).transpose()?,
+            io_service_bytes: match cgroups.blkio() {
+                Some(b) => optional(b.io_service_bytes())?,
+                None => None,
+            },
+            pgfault: memory_stat.as_ref().map(|s| s.pgfault),
+            pgmajfault: memory_stat.as_ref().map(|s| s.pgmajfault),
+        })
+    }
+}
+
+impl Metrics {
+    /// Computes the metrics from the deltas between `prev` and `curr` snapshots.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `curr` was taken earlier than `prev`.
+    pub fn between(prev: &Snapshot, curr: &Snapshot) -> Self {
+        let interval = curr.time.duration_since(prev.time);
+        let secs = interval.as_secs() as f64 + f64::from(interval.subsec_nanos()) * 1e-9;
+
+        let per_sec = |delta: u64| {
+            if secs > 0.0 {
+                delta as f64 / secs
+            } else {
+                0.0
+            }
+        };
+
+        let counter = |prev: Option<u64>, curr: Option<u64>| match (prev, curr) {
+            (Some(p), Some(c)) => Some(delta(p, c)),
+            _ => None,
+        };
+
+        let throttled_ratio = match (&prev.cpu_stat, &curr.cpu_stat) {
+            (Some(p), Some(c)) => {
+                let periods = delta(p.nr_periods, c.nr_periods);
+                let throttled = delta(p.nr_throttled, c.nr_throttled);
+                Some(if periods > 0 {
+                    throttled as f64 / periods as f64
+                } else {
+                    0.0
+                })
+            }
+            _ => None,
+        };
+
+        let mut read_bytes_per_sec = HashMap::new();
+        let mut write_bytes_per_sec = HashMap::new();
+        if let (Some(p), Some(c)) = (&prev.io_service_bytes, &curr.io_service_bytes) {
+            for (device, ops) in &c.devices {
+                let (read, write) = match p.devices.get(device) {
+                    Some(prev_ops) => (
+                        delta(prev_ops.read, ops.read),
+                        delta(prev_ops.write, ops.write),
+                    ),
+                    None => (ops.read, ops.write),
+                };
+                read_bytes_per_sec.insert(*device, per_sec(read));
+                write_bytes_per_sec.insert(*device, per_sec(write));
+            }
+        }
+
+        Self {
+            interval,
+            cpu_cores: counter(prev.cpu_usage, curr.cpu_usage).map(|usage| per_sec(usage) / 1e9),
+            throttled_ratio,
+            read_bytes_per_sec,
+            write_bytes_per_sec,
+            pgfault_per_sec: counter(prev.pgfault, curr.pgfault).map(per_sec),
+            pgmajfault_per_sec: counter(prev.pgmajfault, curr.pgmajfault).map(per_sec),
+        }
+    }
+}
+
+// Delta of a counter, assuming it has been reset to zero if it decreased.
+fn delta(prev: u64, curr: u64) -> u64 {
+    if curr >= prev {
+        curr - prev
+    } else {
+        curr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::{blkio::Operations, SubsystemKind};
+    use std::collections::BTreeMap;
+
+    fn snapshot(time: Instant) -> Snapshot {
+        Snapshot {
+            time,
+            cpu_usage: None,
+            cpu_stat: None,
+            io_service_bytes: None,
+            pgfault: None,
+            pgmajfault: None,
+        }
+    }
+
+    fn io_service(device: Device, read: u64, write: u64) -> blkio::IoService {
+        blkio::IoService {
+            devices: hashmap! {(device, Operations {
+                read,
+                write,
+                sync: 0,
+                async_: 0,
+                total: read + write,
+                extra: BTreeMap::new(),
+            })},
+            total: read + write,
+        }
+    }
+
+    #[test]
+    fn test_metrics_between() {
+        let sda = Device {
+            major: 8.into(),
+            minor: 0.into(),
+        };
+
+        let now = Instant::now();
+
+        let mut prev = snapshot(now);
+        prev.cpu_usage = Some(1_000_000_000);
+        prev.cpu_stat = Some(cpu::Stat {
+            nr_periods: 10,
+            nr_throttled: 1,
+            throttled_time: 0,
+            extra: BTreeMap::new(),
+        });
+        prev.io_service_bytes = Some(io_service(sda, 1000, 2000));
+        prev.pgfault = Some(100);
+
+        let mut curr = snapshot(now + Duration::from_secs(2));
+        curr.cpu_usage = Some(4_000_000_000);
+        curr.cpu_stat = Some(cpu::Stat {
+            nr_periods: 30,
+            nr_throttled: 6,
+            throttled_time: 0,
+            extra: BTreeMap::new(),
+        });
+        curr.io_service_bytes = Some(io_service(sda, 3000, 2000));
+        curr.pgfault = Some(300);
+
+        let metrics = Metrics::between(&prev, &curr);
+        assert_eq!(metrics.interval, Duration::from_secs(2));
+        assert_eq!(metrics.cpu_cores, Some(1.5));
+        assert_eq!(metrics.throttled_ratio, Some(0.25));
+        assert_eq!(metrics.read_bytes_per_sec, hashmap! {(sda, 1000.0)});
+        assert_eq!(metrics.write_bytes_per_sec, hashmap! {(sda, 0.0)});
+        assert_eq!(metrics.pgfault_per_sec, Some(100.0));
+        assert_eq!(metrics.pgmajfault_per_sec, None);
+    }
+
+    #[test]
+    fn test_metrics_between_reset() {
+        let sda = Device {
+            major: 8.into(),
+            minor: 0.into(),
+        };
+
+        let now = Instant::now();
+
+        let mut prev = snapshot(now);
+        prev.cpu_usage = Some(10_000_000_000);
+        prev.io_service_bytes = Some(io_service(sda, 5000, 5000));
+
+        // counters have been reset
+        let mut curr = snapshot(now + Duration::from_secs(1));
+        curr.cpu_usage = Some(500_000_000);
+        curr.io_service_bytes = Some(io_service(sda, 100, 0));
+
+        let metrics = Metrics::between(&prev, &curr);
+        assert_eq!(metrics.cpu_cores, Some(0.5));
+        assert_eq!(metrics.read_bytes_per_sec, hashmap! {(sda, 100.0)});
+        assert_eq!(metrics.write_bytes_per_sec, hashmap! {(sda, 0.0)});
+    }
+
+    #[test]
+    fn test_sampler() -> Result<()> {
+        let mut cgroups =
+            UnifiedRepr::with_subsystems(gen_cgroup_name!(), &[SubsystemKind::Cpuacct]);
+        cgroups.create()?;
+
+        let mut sampler = Sampler::new(cgroups);
+        assert_eq!(sampler.sample()?, None);
+
+        let metrics = sampler.iter(Duration::from_millis(10)).next().unwrap()?;
+        assert!(metrics.interval >= Duration::from_millis(10));
+        assert_eq!(metrics.cpu_cores, Some(0.0));
+        assert_eq!(metrics.throttled_ratio, None);
+        assert!(metrics.read_bytes_per_sec.is_empty());
+        assert_eq!(metrics.pgfault_per_sec, None);
+
+        sampler.into_inner().delete()
+    }
+}
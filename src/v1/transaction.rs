@@ -0,0 +1,361 @@
+use std::{
+    error::Error as StdError,
+    ffi::{OsStr, OsString},
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use crate::{
+    file,
+    v1::{backend::Backend, Cgroup, Resources, SubsystemKind},
+    Error, Result,
+};
+
+/// Failure in applying resource limits and constraints, returned from
+/// [`Cgroup::apply_transactional`] and [`UnifiedRepr::apply_transactional`] as the source of an
+/// [`Error`].
+///
+/// This error holds the error of the failed write, and errors occurred while writing back the
+/// previous values, if any.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> controlgroup::Result<()> {
+/// use std::path::PathBuf;
+/// use controlgroup::v1::{ApplyError, Resources, UnifiedRepr};
+///
+/// let mut cgroups = UnifiedRepr::new(PathBuf::from("students/charlie"));
+///
+/// if let Err(e) = cgroups.apply_transactional(&Resources::default()) {
+///     let source = std::error::Error::source(&e).and_then(|s| s.downcast_ref::<ApplyError>());
+///     let source = source.unwrap();
+///
+///     eprintln!("{}: {}", source.subsystem(), source.error());
+///     for (kind, err) in source.rollback_failures() {
+///         eprintln!("failed to roll back {}: {}", kind, err);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Cgroup::apply_transactional`]: trait.Cgroup.html#method.apply_transactional
+/// [`UnifiedRepr::apply_transactional`]: struct.UnifiedRepr.html#method.apply_transactional
+/// [`Error`]: ../struct.Error.html
+#[derive(Debug)]
+pub struct ApplyError {
+    subsystem: SubsystemKind,
+    error: Error,
+    rollback_failures: Vec<(SubsystemKind, Error)>,
+}
+
+impl ApplyError {
+    /// Returns the subsystem to which resource limits failed to be applied.
+    pub fn subsystem(&self) -> SubsystemKind {
+        self.subsystem
+    }
+
+    /// Returns the error of the failed write.
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+
+    /// Returns the list of subsystems whose previous values failed to be restored, with their
+    /// errors.
+    pub fn rollback_failures(&self) -> &[(SubsystemKind, Error)] {
+        &self.rollback_failures
+    }
+
+    /// Returns an error with the kind of `error` and `ApplyError` as its source.
+    pub(crate) fn wrap(
+        subsystem: SubsystemKind,
+        error: Error,
+        rollback_failures: Vec<(SubsystemKind, Error)>,
+    ) -> Error {
+        let kind = error.kind();
+        Error::with_source(
+            kind,
+            Self {
+                subsystem,
+                error,
+                rollback_failures,
+            },
+        )
+    }
+}
+
+impl StdError for ApplyError {}
+
+impl fmt::Display for ApplyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to apply resources to {} subsystem ({})",
+            self.subsystem, self.error
+        )?;
+
+        for (i, (kind, err)) in self.rollback_failures.iter().enumerate() {
+            f.write_str(if i == 0 {
+                ", and failed to roll back ("
+            } else {
+                "; "
+            })?;
+            write!(f, "{}: {}", kind, err)?;
+        }
+        if !self.rollback_failures.is_empty() {
+            f.write_str(")")?;
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) fn apply_transactional<C: Cgroup>(cgroup: &mut C, resources: &Resources) -> Result<()> {
+    let (mut journaled, journal) = journaled(cgroup);
+
+    journaled.apply(resources).map_err(|error| {
+        let kind = cgroup.subsystem();
+        let rollback_failures = journal.rollback().into_iter().map(|e| (kind, e)).collect();
+        ApplyError::wrap(kind, error, rollback_failures)
+    })
+}
+
+/// Returns a handler of the same cgroup as `cgroup` whose writes are recorded in the returned
+/// journal.
+pub(crate) fn journaled<C: Cgroup>(cgroup: &C) -> (C, Arc<Journal>) {
+    let path = cgroup.cgroup_path();
+    let journal = Arc::new(Journal {
+        backend: path.shared_backend(),
+        entries: Mutex::new(Vec::new()),
+    });
+
+    let journaled = C::new(path.clone().with_backend(journal.clone()));
+    (journaled, journal)
+}
+
+/// Backend that records the previous value of each file before writing to it, so that the writes
+/// actually made can be undone in reverse order.
+#[derive(Debug)]
+pub(crate) struct Journal {
+    backend: Arc<dyn Backend>,
+    entries: Mutex<Vec<(PathBuf, String)>>,
+}
+
+impl Journal {
+    /// Writes back the recorded values in reverse order, and returns the failures.
+    pub(crate) fn rollback(&self) -> Vec<Error> {
+        let entries = self.lock().drain(..).collect::<Vec<_>>();
+        entries
+            .into_iter()
+            .rev()
+            .filter_map(|(path, value)| file::write(self.backend.as_ref(), path, value).err())
+            .collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<(PathBuf, String)>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<String> {
+        let mut content = String::new();
+        self.backend
+            .open(path, OpenOptions::new().read(true))?
+            .read_to_string(&mut content)?;
+        Ok(content)
+    }
+
+    // Entries that restore the current value of the file to be overwritten with `contents`, in
+    // the order to be recorded (i.e. the reverse of the order to be written back).
+    fn previous(&self, path: &Path, contents: &[u8]) -> io::Result<Vec<(PathBuf, String)>> {
+        let name = path.file_name().and_then(OsStr::to_str).unwrap_or("");
+
+        if name == "devices.allow" || name == "devices.deny" {
+            // The whole access list is restored at once, on the first write to the cgroup.
+            let dir = path.parent();
+            let recorded = self.lock().iter().any(|(p, _)| {
+                p.parent() == dir && (p.ends_with("devices.allow") || p.ends_with("devices.deny"))
+            });
+            if recorded {
+                return Ok(vec![]);
+            }
+
+            // written back as: deny all accesses, then allow the listed ones
+            let list = self.read(&path.with_file_name("devices.list"))?;
+            let allow = path.with_file_name("devices.allow");
+            let mut entries = list
+                .lines()
+                .rev()
+                .map(|l| (allow.clone(), l.to_string()))
+                .collect::<Vec<_>>();
+            entries.push((path.with_file_name("devices.deny"), "a".to_string()));
+            return Ok(entries);
+        }
+
+        let current = self.read(path)?;
+        let value = match keyed_default(name) {
+            Some(default) => {
+                let contents = String::from_utf8_lossy(contents);
+                let key = contents.split_whitespace().next().unwrap_or("");
+                current
+                    .lines()
+                    .map(|l| l.split_whitespace().collect::<Vec<_>>())
+                    .find(|l| l.first() == Some(&key))
+                    .map(|l| l.join(" "))
+                    .unwrap_or_else(|| format!("{} {}", key, default))
+            }
+            // `FREEZING` cannot be written, and the cgroup is becoming `FROZEN`
+            None if name == "freezer.state" && current.trim() == "FREEZING" => "FROZEN".to_string(),
+            None => current.trim_end().to_string(),
+        };
+
+        Ok(vec![(path.to_path_buf(), value)])
+    }
+}
+
+// Value that removes the setting for a key, for files in which each line sets a value for a key
+// (e.g. a device). Returns `None` for other files.
+fn keyed_default(name: &str) -> Option<&'static str> {
+    match name {
+        "net_prio.ifpriomap" => Some("0"),
+        "rdma.max" => Some("hca_handle=max hca_object=max"),
+        // writing 0 removes the weight or the throttle
+        _ if name.starts_with("blkio.") && name.ends_with("_device") => Some("0"),
+        _ => None,
+    }
+}
+
+impl Backend for Journal {
+    fn open(&self, path: &Path, options: &OpenOptions) -> io::Result<File> {
+        self.backend.open(path, options)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let previous = self.previous(path, contents)?;
+        self.backend.write(path, contents)?;
+        self.lock().extend(previous);
+        Ok(())
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        self.backend.create_dir(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        self.backend.remove_dir(path)
+    }
+
+    fn list_dirs(&self, path: &Path) -> io::Result<Vec<OsString>> {
+        self.backend.list_dirs(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.backend.exists(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        v1::{backend::TempFake, blkio, cpu, devices, memory, CgroupPath},
+        ErrorKind,
+    };
+
+    #[test]
+    fn test_journal() -> Result<()> {
+        let fake = TempFake::new(
+            gen_cgroup_name!(),
+            &[SubsystemKind::BlkIo, SubsystemKind::Devices],
+        )?;
+
+        // keyed files
+        let blkio = blkio::Subsystem::new(
+            CgroupPath::new(SubsystemKind::BlkIo, PathBuf::new()).with_backend(fake.backend()),
+        );
+        let throttle = blkio.path().join("blkio.throttle.read_bps_device");
+        fake.set_contents(&throttle, "8:0 1000\n")?;
+
+        let (journaled_blkio, journal) = journaled(&blkio);
+        journaled_blkio.backend().write(&throttle, b"8:0 2000")?;
+        journaled_blkio.backend().write(&throttle, b"8:16 500")?;
+        assert_eq!(
+            *journal.lock(),
+            vec![
+                (throttle.clone(), "8:0 1000".to_string()),
+                (throttle.clone(), "8:16 0".to_string())
+            ]
+        );
+        assert!(journal.rollback().is_empty());
+        assert!(journal.lock().is_empty());
+
+        // devices
+        let devices = devices::Subsystem::new(
+            CgroupPath::new(SubsystemKind::Devices, PathBuf::new()).with_backend(fake.backend()),
+        );
+        let (mut journaled_devices, journal) = journaled(&devices);
+        journaled_devices.deny(&"a".parse()?)?;
+        journaled_devices.allow(&"c 1:3 rwm".parse()?)?;
+        assert_eq!(journal.lock().len(), 2);
+        assert_eq!(devices.list()?, vec!["c 1:3 rwm".parse()?]);
+
+        assert!(journal.rollback().is_empty());
+        assert_eq!(devices.list()?, vec!["a *:* rwm".parse()?]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_transactional() -> Result<()> {
+        let mut cgroup =
+            cpu::Subsystem::new(CgroupPath::new(SubsystemKind::Cpu, gen_cgroup_name!()));
+        cgroup.create()?;
+        cgroup.set_shares(1024)?;
+
+        let mut resources = Resources::default();
+        resources.cpu.shares = Some(2048);
+        resources.cpu.cfs_quota_us = Some(1); // invalid
+
+        let err = cgroup.apply_transactional(&resources).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Io);
+
+        let source = err.source().unwrap().downcast_ref::<ApplyError>().unwrap();
+        assert_eq!(source.subsystem(), SubsystemKind::Cpu);
+        assert!(source.rollback_failures().is_empty());
+
+        assert_eq!(cgroup.shares()?, 1024);
+
+        resources.cpu.cfs_quota_us = None;
+        cgroup.apply_transactional(&resources)?;
+        assert_eq!(cgroup.shares()?, 2048);
+
+        cgroup.delete()
+    }
+
+    #[test]
+    fn test_apply_transactional_reverse() -> Result<()> {
+        let mut cgroup =
+            memory::Subsystem::new(CgroupPath::new(SubsystemKind::Memory, gen_cgroup_name!()));
+        cgroup.create()?;
+        cgroup.set_limit_in_bytes(1 << 30)?;
+        cgroup.set_memsw_limit_in_bytes(1 << 30)?;
+
+        let mut resources = Resources::default();
+        resources.memory.limit_in_bytes = Some(512 << 20);
+        resources.memory.memsw_limit_in_bytes = Some(768 << 20);
+        resources.memory.swappiness = Some(201); // invalid
+
+        // Writing back `limit_in_bytes` before `memsw_limit_in_bytes` would fail
+        let err = cgroup.apply_transactional(&resources).unwrap_err();
+        let source = err.source().unwrap().downcast_ref::<ApplyError>().unwrap();
+        assert!(source.rollback_failures().is_empty());
+
+        assert_eq!(cgroup.limit_in_bytes()?, 1 << 30);
+        assert_eq!(cgroup.memsw_limit_in_bytes()?, 1 << 30);
+
+        cgroup.delete()
+    }
+}
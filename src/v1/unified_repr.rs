@@ -1,10 +1,47 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ffi::OsStr,
+    mem,
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use crate::{
-    v1::{self, Cgroup, CgroupPath, SubsystemKind},
-    Pid, Result,
+    v1::{
+        self, backend::Backend, delete, mount::MountInfo, proc_cgroup::ProcCgroup, transaction,
+        ApplyError, Cgroup, CgroupPath, DeleteError, DeleteOptions, Drain, SubsystemKind, Walk,
+    },
+    Error, Pid, Result,
 };
 
+// Subsystems co-mounted on a hierarchy with another subsystem, except for the first one.
+fn co_mounted_subsystems(mount_info: &MountInfo) -> Vec<SubsystemKind> {
+    mount_info
+        .hierarchies()
+        .iter()
+        .flat_map(|h| h.subsystems().iter().skip(1).copied())
+        .collect()
+}
+
+// Moves the fields for a subsystem from `from` into `into`.
+fn merge_resources(into: &mut v1::Resources, from: v1::Resources, kind: SubsystemKind) {
+    match kind {
+        SubsystemKind::Cpu => into.cpu = from.cpu,
+        SubsystemKind::Cpuset => into.cpuset = from.cpuset,
+        SubsystemKind::Memory => into.memory = from.memory,
+        SubsystemKind::HugeTlb => into.hugetlb = from.hugetlb,
+        SubsystemKind::Devices => into.devices = from.devices,
+        SubsystemKind::BlkIo => into.blkio = from.blkio,
+        SubsystemKind::Rdma => into.rdma = from.rdma,
+        SubsystemKind::NetPrio => into.net_prio = from.net_prio,
+        SubsystemKind::NetCls => into.net_cls = from.net_cls,
+        SubsystemKind::Pids => into.pids = from.pids,
+        SubsystemKind::Freezer => into.freezer = from.freezer,
+        SubsystemKind::Cpuacct | SubsystemKind::PerfEvent => {}
+    }
+}
+
 macro_rules! gen_unified_repr {
     ( $( ($subsystem: ident, $subsystem_mut: ident, $kind: ident, $name: literal) ),* $(, )? ) => {
 
@@ -151,6 +188,102 @@ impl UnifiedRepr {
         Self { $( $subsystem ),* }
     }
 
+    /// Defines a new unified representation of a set of cgroups with all subsystems mounted on the
+    /// system, as found in `mount_info`.
+    ///
+    /// Each cgroup is placed under the real mount point of its hierarchy. If multiple subsystems
+    /// are co-mounted on a single hierarchy (e.g. `cpu,cpuacct`), creating and deleting the
+    /// directory is skipped for all but the first subsystem. See [`skip_create`] for more
+    /// information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{mount::MountInfo, SubsystemKind, UnifiedRepr};
+    ///
+    /// let content = "33 25 0:29 / /cgroup/cpu,cpuacct rw - cgroup cgroup rw,cpu,cpuacct\n";
+    /// let mount_info = MountInfo::parse(content.as_bytes())?;
+    ///
+    /// let cgroups = UnifiedRepr::with_mount_info(PathBuf::from("students/charlie"), &mount_info);
+    /// assert!(cgroups.supports(SubsystemKind::Cpu));
+    /// assert!(cgroups.supports(SubsystemKind::Cpuacct));
+    /// assert!(!cgroups.supports(SubsystemKind::Memory));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`skip_create`]: #method.skip_create
+    pub fn with_mount_info(name: PathBuf, mount_info: &MountInfo) -> Self {
+        let mut subsystems = Vec::new();
+        for hierarchy in mount_info.hierarchies() {
+            for &kind in hierarchy.subsystems() {
+                subsystems.push((
+                    kind,
+                    CgroupPath::with_root(hierarchy.mount_point().to_path_buf(), name.clone()),
+                ));
+            }
+        }
+
+        let mut cgroups = Self::with_custom_name_subsystems(subsystems);
+        cgroups.skip_create(&co_mounted_subsystems(mount_info));
+        cgroups
+    }
+
+    /// Defines a new unified representation of a set of cgroups with all subsystems mounted on the
+    /// system, finding their mount points from `/proc/self/mountinfo`.
+    ///
+    /// See [`with_mount_info`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to read and parse `/proc/self/mountinfo`.
+    ///
+    /// [`with_mount_info`]: #method.with_mount_info
+    pub fn discover(name: PathBuf) -> Result<Self> {
+        let mount_info = MountInfo::load()?;
+        Ok(Self::with_mount_info(name, &mount_info))
+    }
+
+    /// Defines a new unified representation of the set of cgroups a process belongs to, by parsing
+    /// `/proc/<pid>/cgroup` and `/proc/self/mountinfo`.
+    ///
+    /// Only subsystems mounted on the system are included. Creating and deleting the directories
+    /// are skipped for co-mounted subsystems except for the first one, as in [`with_mount_info`].
+    ///
+    /// See [`proc_cgroup::ProcCgroup`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to read and parse `/proc/<pid>/cgroup` or
+    /// `/proc/self/mountinfo`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use controlgroup::{Pid, v1::{Cgroup, UnifiedRepr}};
+    ///
+    /// let cgroups = UnifiedRepr::from_pid(Pid::from(std::process::id()))?;
+    /// if let Some(cpu) = cgroups.cpu() {
+    ///     println!("{}", cpu.path().display());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`with_mount_info`]: #method.with_mount_info
+    /// [`proc_cgroup::ProcCgroup`]: proc_cgroup/struct.ProcCgroup.html
+    pub fn from_pid(pid: Pid) -> Result<Self> {
+        let mount_info = MountInfo::load()?;
+        let paths = ProcCgroup::load(pid)?.cgroup_paths(&mount_info);
+
+        let mut cgroups = Self::with_custom_name_subsystems(paths);
+        cgroups.skip_create(&co_mounted_subsystems(&mount_info));
+        Ok(cgroups)
+    }
+
     /// Returns whether a subsystem is supported by this unified representation, i.e. included in
     /// this set of cgroups.
     ///
@@ -172,6 +305,41 @@ impl UnifiedRepr {
         }
     }
 
+    /// Attaches a backend through which the files of all cgroups in this set are accessed, as
+    /// [`CgroupPath::with_backend`] does for each of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::{path::PathBuf, sync::Arc};
+    /// use controlgroup::v1::{backend::Fake, SubsystemKind, UnifiedRepr};
+    ///
+    /// let root = std::env::temp_dir().join("controlgroup_unified_repr_with_backend");
+    /// let fake = Arc::new(Fake::new(root.clone())?);
+    /// fake.mount(SubsystemKind::Pids)?;
+    ///
+    /// let mut cgroups = UnifiedRepr::with_subsystems(
+    ///     PathBuf::from("charlie"), &[SubsystemKind::Pids]).with_backend(fake);
+    ///
+    /// cgroups.create()?;  // Creates a directory under `root`
+    /// cgroups.delete()?;
+    /// # std::fs::remove_dir_all(root)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`CgroupPath::with_backend`]: struct.CgroupPath.html#method.with_backend
+    pub fn with_backend(mut self, backend: Arc<dyn Backend>) -> Self {
+        $(
+            if let Some(ref mut s) = self.$subsystem {
+                let path = s.subsystem.cgroup_path().clone().with_backend(backend.clone());
+                s.subsystem = $subsystem::Subsystem::new(path);
+            }
+        )*
+        self
+    }
+
     /// Skips creating and deleting the directories for some subsystems.
     ///
     /// This method is useful when multiple subsystems share the same hierarchy (including via
@@ -211,6 +379,124 @@ impl UnifiedRepr {
         }
     }
 
+    /// Returns the definition of the parent of this set of cgroups, or `None` if this set includes
+    /// the root cgroup of a subsystem.
+    ///
+    /// Subsystems skipped by [`skip_create`] method are also skipped in the parent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{Cgroup, UnifiedRepr};
+    ///
+    /// let cgroups = UnifiedRepr::new(PathBuf::from("students/charlie"));
+    ///
+    /// let parent = cgroups.parent().unwrap();
+    /// assert_eq!(parent.cpu().unwrap().path(), PathBuf::from("/sys/fs/cgroup/cpu/students"));
+    ///
+    /// assert!(parent.parent().unwrap().parent().is_none());
+    /// ```
+    ///
+    /// [`skip_create`]: #method.skip_create
+    pub fn parent(&self) -> Option<Self> {
+        $(
+            let $subsystem = match self.$subsystem {
+                Some(ref s) => Some(Subsys {
+                    subsystem: *s.subsystem.parent()?,
+                    create: s.create,
+                }),
+                None => None,
+            };
+        )*
+        Some(Self { $( $subsystem ),* })
+    }
+
+    /// Reads the list of child cgroup sets of this set of cgroups.
+    ///
+    /// Child cgroups with the same name in different subsystems are unified into a single
+    /// `UnifiedRepr`, which supports only the subsystems in which the child exists. The children
+    /// are sorted by their names.
+    ///
+    /// Subsystems skipped by [`skip_create`] method are also skipped in the children.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to read the directory of a cgroup, with kind [`ErrorKind::Io`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{SubsystemKind, UnifiedRepr};
+    ///
+    /// let cgroups = UnifiedRepr::new(PathBuf::from("students"));
+    ///
+    /// for child in cgroups.children()? {
+    ///     println!("{:?}", child.procs()?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`skip_create`]: #method.skip_create
+    /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
+    pub fn children(&self) -> Result<Vec<Self>> {
+        let mut children = BTreeMap::new();
+
+        $(
+            if let Some(ref s) = self.$subsystem {
+                for child in s.subsystem.children()? {
+                    let name = child.path().file_name().map(OsStr::to_os_string);
+                    children.entry(name).or_insert_with(Self::empty).$subsystem = Some(Subsys {
+                        subsystem: child,
+                        create: s.create,
+                    });
+                }
+            }
+        )*
+
+        Ok(children.into_iter().map(|(_, c)| c).collect())
+    }
+
+    /// Returns an iterator visiting this set of cgroups and all its descendants recursively.
+    ///
+    /// The cgroup sets are visited in pre-order, i.e. a cgroup set is always visited before its
+    /// children. See [`children`] and [`Walk`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::UnifiedRepr;
+    ///
+    /// let cgroups = UnifiedRepr::new(PathBuf::from("students"));
+    ///
+    /// for descendant in cgroups.walk() {
+    ///     println!("{:?}", descendant?.procs()?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`children`]: #method.children
+    /// [`Walk`]: struct.Walk.html
+    pub fn walk(&self) -> Walk<Self> {
+        $(
+            let $subsystem = self.$subsystem.as_ref().map(|s| Subsys {
+                subsystem: $subsystem::Subsystem::new(s.subsystem.cgroup_path().clone()),
+                create: s.create,
+            });
+        )*
+        Walk::new(Self { $( $subsystem ),* }, Self::children)
+    }
+
+    fn empty() -> Self {
+        Self { $( $subsystem: None ),* }
+    }
+
     /// Creates new directories for each cgroup of the all supported subsystems except for ones that
     /// was skipped by [`skip_create`] method.
     ///
@@ -229,6 +515,69 @@ impl UnifiedRepr {
         Ok(())
     }
 
+    /// Creates new directories for each cgroup as [`create`] method does, and returns a [`Guard`]
+    /// that deletes them when dropped.
+    ///
+    /// If failed to create a directory, the directories created so far are deleted before this
+    /// method returns.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to create a directory, with kind [`ErrorKind::Io`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::{Pid, v1::UnifiedRepr};
+    ///
+    /// let mut cgroups = UnifiedRepr::new(PathBuf::from("students/charlie")).create_guarded()?;
+    /// cgroups.add_proc(Pid::from(std::process::id()))?;
+    ///
+    /// // Do something ...
+    ///
+    /// // The self process is moved to the parent, and the cgroups are deleted here.
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`create`]: #method.create
+    /// [`Guard`]: struct.Guard.html
+    /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
+    pub fn create_guarded(mut self) -> Result<Guard> {
+        let mut created = Vec::new();
+        $(
+            if let Some(ref mut s) = self.$subsystem {
+                if s.create {
+                    if let Err(e) = s.subsystem.create() {
+                        let _ = self.delete_created(&created);
+                        return Err(e);
+                    }
+                    created.push(SubsystemKind::$kind);
+                }
+            }
+        )*
+        Ok(Guard { cgroups: self, created })
+    }
+
+    // Deletes directories only of the `created` subsystems, moving the remaining processes to the
+    // parents. A cgroup with children is not deleted, since the children were not created by us.
+    fn delete_created(&mut self, created: &[SubsystemKind]) -> Result<()> {
+        let options = DeleteOptions::default();
+        let mut failures = Vec::new();
+        $(
+            if let Some(ref mut s) = self.$subsystem {
+                if created.contains(&SubsystemKind::$kind) {
+                    if let Err(e) = delete::delete_leaf(&mut s.subsystem, &options) {
+                        failures.push((SubsystemKind::$kind, e));
+                    }
+                }
+            }
+        )*
+        DeleteError::check(failures)
+    }
+
     /// Applies resource limits and constraints to all cgroups of the all supported subsystems.
     pub fn apply(&mut self, resources: &v1::Resources) -> Result<()> {
         $(
@@ -239,6 +588,100 @@ impl UnifiedRepr {
         Ok(())
     }
 
+    /// Applies only the changes from `old` to `new` to all cgroups of the all supported subsystems.
+    ///
+    /// See [`Cgroup::apply_diff`] for more information.
+    ///
+    /// [`Cgroup::apply_diff`]: trait.Cgroup.html#method.apply_diff
+    pub fn apply_diff(&mut self, old: &v1::Resources, new: &v1::Resources) -> Result<()> {
+        self.apply(&old.diff(new))
+    }
+
+    /// Applies resource limits and constraints to all cgroups of the all supported subsystems like
+    /// [`apply`], but restores the previous values if any read or write fails.
+    ///
+    /// Before each file is written, its current value is recorded. If a later read or write fails,
+    /// the recorded values are written back in reverse order, across the subsystems.
+    ///
+    /// See [`Cgroup::apply_transactional`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to read or write a value. The kind of the error is that of the
+    /// failure, and its source is an [`ApplyError`] holding the failure and all failures in rolling
+    /// back.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::{Max, v1::{Resources, UnifiedRepr}};
+    ///
+    /// let mut cgroups = UnifiedRepr::new(PathBuf::from("students/charlie"));
+    /// cgroups.create()?;
+    ///
+    /// let mut resources = Resources::default();
+    /// resources.cpu.shares = Some(1000);
+    /// resources.pids.max = Some(Max::Limit(42));
+    ///
+    /// // If writing `pids.max` fails, `cpu.shares` is restored.
+    /// cgroups.apply_transactional(&resources)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`apply`]: #method.apply
+    /// [`Cgroup::apply_transactional`]: trait.Cgroup.html#method.apply_transactional
+    /// [`ApplyError`]: struct.ApplyError.html
+    pub fn apply_transactional(&mut self, resources: &v1::Resources) -> Result<()> {
+        let mut journals = Vec::new();
+
+        if let Err((kind, error)) = self.apply_journaled(resources, &mut journals) {
+            let mut rollback_failures = Vec::new();
+            for (k, journal) in journals.into_iter().rev() {
+                rollback_failures.extend(journal.rollback().into_iter().map(|e| (k, e)));
+            }
+
+            return Err(ApplyError::wrap(kind, error, rollback_failures));
+        }
+
+        Ok(())
+    }
+
+    // Applies to each subsystem, recording the writes into a journal of the subsystem.
+    fn apply_journaled(
+        &mut self,
+        resources: &v1::Resources,
+        journals: &mut Vec<(SubsystemKind, Arc<transaction::Journal>)>,
+    ) -> std::result::Result<(), (SubsystemKind, Error)> {
+        $(
+            if let Some(ref s) = self.$subsystem {
+                let kind = SubsystemKind::$kind;
+                let (mut journaled, journal) = transaction::journaled(&s.subsystem);
+                journals.push((kind, journal));
+                journaled.apply(resources).map_err(|e| (kind, e))?;
+            }
+        )*
+        Ok(())
+    }
+
+    /// Reads the current resource limits and constraints of all cgroups of the all supported
+    /// subsystems.
+    ///
+    /// See [`Cgroup::resources`] for more information.
+    ///
+    /// [`Cgroup::resources`]: trait.Cgroup.html#tymethod.resources
+    pub fn resources(&self) -> Result<v1::Resources> {
+        let mut resources = v1::Resources::default();
+        $(
+            if let Some(ref s) = self.$subsystem {
+                merge_resources(&mut resources, s.subsystem.resources()?, SubsystemKind::$kind);
+            }
+        )*
+        Ok(resources)
+    }
+
     /// Deletes directories for each cgroup of the all supported subsystems except for ones that
     /// was skipped by [`skip_create`] method.
     ///
@@ -257,6 +700,65 @@ impl UnifiedRepr {
         Ok(())
     }
 
+    /// Deletes directories for each cgroup and all its descendants of the all supported subsystems
+    /// except for ones that was skipped by [`skip_create`] method.
+    ///
+    /// Unlike [`delete`], a failure in a subsystem does not abort deleting the cgroups of the other
+    /// subsystems. If `options.drain` is `Drain::Kill`, the freezer subsystem is processed first so
+    /// that the remaining processes are frozen before being killed.
+    ///
+    /// See [`Cgroup::delete_recursive`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to delete the cgroups of some subsystems. The kind of the error is
+    /// that of the first failure, and its source is a [`DeleteError`] holding all the failures.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{DeleteOptions, Drain, UnifiedRepr};
+    ///
+    /// let mut cgroups = UnifiedRepr::new(PathBuf::from("students"));
+    ///
+    /// cgroups.delete_recursive(&DeleteOptions { drain: Drain::Kill, ..DeleteOptions::default() })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`skip_create`]: #method.skip_create
+    /// [`delete`]: #method.delete
+    /// [`Cgroup::delete_recursive`]: trait.Cgroup.html#method.delete_recursive
+    /// [`DeleteError`]: struct.DeleteError.html
+    pub fn delete_recursive(&mut self, options: &DeleteOptions) -> Result<()> {
+        let kill = options.drain == Drain::Kill;
+        let mut failures = Vec::new();
+
+        if kill {
+            if let Some(ref mut s) = self.freezer {
+                if s.create {
+                    if let Err(e) = s.subsystem.delete_recursive(options) {
+                        failures.push((SubsystemKind::Freezer, e));
+                    }
+                }
+            }
+        }
+
+        $(
+            if let Some(ref mut s) = self.$subsystem {
+                if s.create && !(kill && SubsystemKind::$kind == SubsystemKind::Freezer) {
+                    if let Err(e) = s.subsystem.delete_recursive(options) {
+                        failures.push((SubsystemKind::$kind, e));
+                    }
+                }
+            }
+        )*
+
+        DeleteError::check(failures)
+    }
+
     /// Reads a list of tasks attached to each cgroup of the all supported subsystems.
     ///
     /// See [`Cgroup::tasks`] for more information.
@@ -343,6 +845,30 @@ impl UnifiedRepr {
         Ok(())
     }
 
+    /// Returns the name of this cgroup set relative to the root cgroups (e.g.
+    /// `students/charlie`), taken from the first supported subsystem. Returns `None` if no
+    /// subsystem is supported.
+    ///
+    /// ```
+    /// use std::path::{Path, PathBuf};
+    /// use controlgroup::v1::{SubsystemKind, UnifiedRepr};
+    ///
+    /// let cgroups = UnifiedRepr::with_subsystems(
+    ///     PathBuf::from("students/charlie"), &[SubsystemKind::Cpu]);
+    /// assert_eq!(cgroups.name(), Some(Path::new("students/charlie")));
+    ///
+    /// let cgroups = UnifiedRepr::with_subsystems(PathBuf::from("students/charlie"), &[]);
+    /// assert_eq!(cgroups.name(), None);
+    /// ```
+    pub fn name(&self) -> Option<&Path> {
+        $(
+            if let Some(ref s) = self.$subsystem {
+                return Some(s.subsystem.cgroup_path().name());
+            }
+        )*
+        None
+    }
+
     $(
         with_doc!(
             concat!("Returns a reference to the ", $name, " subsystem."),
@@ -378,6 +904,96 @@ gen_unified_repr! {
     (perf_event, perf_event_mut, PerfEvent, "perf_event"),
 }
 
+/// Guard of a set of cgroups that deletes the cgroups when dropped.
+///
+/// This struct is created by [`UnifiedRepr::create_guarded`] and [`Builder::build_guarded`]
+/// methods, and dereferences to [`UnifiedRepr`]. When a guard is dropped, e.g. on a panic or an
+/// early return, processes remaining in the cgroups are moved to their parents, and the cgroups
+/// are deleted. Errors in the deletion are ignored.
+///
+/// Only directories created by this guard are deleted, i.e. directories of subsystems skipped by
+/// [`skip_create`] method are left as they are. A cgroup that has child cgroups (e.g. created by
+/// another process) is not deleted either, and neither are the children. To keep the cgroups, call
+/// [`keep`] method.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> controlgroup::Result<()> {
+/// use std::path::PathBuf;
+/// use controlgroup::v1::Builder;
+///
+/// let cgroups = Builder::new(PathBuf::from("students/charlie"))
+///     .cpu()
+///         .shares(1000)
+///         .done()
+///     .build_guarded()?;
+///
+/// // Do something that may fail ...
+///
+/// // Keep the cgroups after all.
+/// let cgroups = cgroups.keep();
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`UnifiedRepr::create_guarded`]: struct.UnifiedRepr.html#method.create_guarded
+/// [`Builder::build_guarded`]: builder/struct.Builder.html#method.build_guarded
+/// [`UnifiedRepr`]: struct.UnifiedRepr.html
+/// [`skip_create`]: struct.UnifiedRepr.html#method.skip_create
+/// [`keep`]: #method.keep
+#[derive(Debug)]
+pub struct Guard {
+    cgroups: UnifiedRepr,
+    created: Vec<SubsystemKind>,
+}
+
+impl Guard {
+    /// Releases the cgroups from this guard, so that they will not be deleted.
+    pub fn keep(mut self) -> UnifiedRepr {
+        self.created.clear();
+        mem::replace(&mut self.cgroups, UnifiedRepr::empty())
+    }
+
+    /// Deletes the cgroups now, as this guard does when dropped, but returns an error if failed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`ErrorKind::InvalidOperation`] if a cgroup has child cgroups,
+    /// or an error with kind [`ErrorKind::Io`] if failed to delete a cgroup. A failure in a
+    /// subsystem does not abort deleting the cgroups of the other subsystems, and the source of the
+    /// error is a [`DeleteError`] holding all the failures.
+    ///
+    /// [`ErrorKind::InvalidOperation`]: ../enum.ErrorKind.html#variant.InvalidOperation
+    /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
+    /// [`DeleteError`]: struct.DeleteError.html
+    pub fn delete(mut self) -> Result<()> {
+        let result = self.cgroups.delete_created(&self.created);
+        self.created.clear();
+        result
+    }
+}
+
+impl Deref for Guard {
+    type Target = UnifiedRepr;
+
+    fn deref(&self) -> &Self::Target {
+        &self.cgroups
+    }
+}
+
+impl DerefMut for Guard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.cgroups
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        let _ = self.cgroups.delete_created(&self.created);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -491,6 +1107,235 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_unified_repr_parent_children_walk() -> Result<()> {
+        let name = gen_cgroup_name!();
+        let mut cgroups =
+            UnifiedRepr::with_subsystems(name.clone(), &[SubsystemKind::Cpu, SubsystemKind::Pids]);
+        cgroups.create()?;
+
+        // "a" exists in both subsystems, "b" exists only in the CPU subsystem
+        let mut a = UnifiedRepr::with_subsystems(
+            name.join("a"),
+            &[SubsystemKind::Cpu, SubsystemKind::Pids],
+        );
+        a.create()?;
+        let mut b = UnifiedRepr::with_subsystems(name.join("b"), &[SubsystemKind::Cpu]);
+        b.create()?;
+
+        let children = cgroups.children()?;
+        assert_eq!(children.len(), 2);
+        assert!(children[0].supports(SubsystemKind::Cpu));
+        assert!(children[0].supports(SubsystemKind::Pids));
+        assert!(children[1].supports(SubsystemKind::Cpu));
+        assert!(!children[1].supports(SubsystemKind::Pids));
+        assert_eq!(children[1].cpu().unwrap().path(), b.cpu().unwrap().path());
+
+        assert_eq!(
+            a.parent().unwrap().pids().unwrap().path(),
+            cgroups.pids().unwrap().path()
+        );
+
+        assert_eq!(cgroups.walk().collect::<Result<Vec<_>>>()?.len(), 3);
+
+        b.delete()?;
+        a.delete()?;
+        cgroups.delete()
+    }
+
+    #[test]
+    fn test_unified_repr_delete_recursive() -> Result<()> {
+        use std::process::Command;
+
+        let name = gen_cgroup_name!();
+        let subsystems = [SubsystemKind::Cpu, SubsystemKind::Freezer];
+
+        let mut cgroups = UnifiedRepr::with_subsystems(name.clone(), &subsystems);
+        cgroups.create()?;
+        let mut child = UnifiedRepr::with_subsystems(name.join("child"), &subsystems);
+        child.create()?;
+
+        let mut sleep = Command::new("sleep").arg("10").spawn().unwrap();
+        child.add_proc(Pid::from(&sleep)).unwrap();
+
+        cgroups
+            .delete_recursive(&DeleteOptions {
+                drain: Drain::Kill,
+                ..DeleteOptions::default()
+            })
+            .unwrap();
+        assert!(!cgroups.cpu().unwrap().path().exists());
+        assert!(!cgroups.freezer().unwrap().path().exists());
+        assert!(!sleep.wait()?.success());
+
+        // nothing to delete
+        let err = cgroups
+            .delete_recursive(&DeleteOptions::default())
+            .unwrap_err();
+        let source = std::error::Error::source(&err)
+            .and_then(|e| e.downcast_ref::<DeleteError>())
+            .unwrap();
+        assert_eq!(source.failures().len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unified_repr_resources() -> Result<()> {
+        let mut cgroups = UnifiedRepr::with_subsystems(
+            gen_cgroup_name!(),
+            &[
+                SubsystemKind::Cpu,
+                SubsystemKind::Cpuacct,
+                SubsystemKind::Pids,
+            ],
+        );
+        cgroups.create()?;
+
+        let mut resources = cgroups.resources()?;
+        assert_eq!(resources.cpu, cgroups.cpu().unwrap().resources()?.cpu);
+        assert_eq!(resources.memory, v1::memory::Resources::default());
+
+        resources.cpu.shares = Some(42);
+        resources.pids.max = Some(crate::Max::Limit(42));
+        cgroups.apply(&resources)?;
+        assert_eq!(cgroups.resources()?, resources);
+
+        cgroups.delete()
+    }
+
+    #[test]
+    fn test_unified_repr_apply_transactional() -> Result<()> {
+        use crate::{ErrorKind, Max};
+        use std::error::Error as _;
+
+        let mut cgroups = UnifiedRepr::with_subsystems(
+            gen_cgroup_name!(),
+            &[
+                SubsystemKind::Cpu,
+                SubsystemKind::Pids,
+                SubsystemKind::Freezer,
+            ],
+        );
+        cgroups.create()?;
+
+        let mut resources = v1::Resources::default();
+        resources.cpu.shares = Some(42);
+        resources.pids.max = Some(Max::Limit(42));
+        resources.freezer.state = Some(v1::freezer::State::Freezing); // invalid
+
+        let err = cgroups.apply_transactional(&resources).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidArgument);
+
+        let source = err.source().unwrap().downcast_ref::<ApplyError>().unwrap();
+        assert_eq!(source.subsystem(), SubsystemKind::Freezer);
+        assert!(source.rollback_failures().is_empty());
+
+        assert_eq!(cgroups.cpu().unwrap().shares()?, 1024);
+        assert_eq!(cgroups.pids().unwrap().max()?, Max::Max);
+
+        resources.freezer.state = None;
+        cgroups.apply_transactional(&resources)?;
+        assert_eq!(cgroups.cpu().unwrap().shares()?, 42);
+        assert_eq!(cgroups.pids().unwrap().max()?, Max::Limit(42));
+
+        cgroups.delete()
+    }
+
+    #[test]
+    fn test_unified_repr_guard() -> Result<()> {
+        use std::process::Command;
+
+        let name = gen_cgroup_name!();
+        let mut cgroups =
+            UnifiedRepr::with_subsystems(name.clone(), &[SubsystemKind::Cpu, SubsystemKind::Pids]);
+        cgroups.skip_create(&[SubsystemKind::Pids]);
+
+        let mut pids = UnifiedRepr::with_subsystems(name.clone(), &[SubsystemKind::Pids]);
+        pids.create()?;
+
+        let mut sleep = Command::new("sleep").arg("10").spawn().unwrap();
+        let pid = Pid::from(&sleep);
+        {
+            let mut guard = cgroups.create_guarded().unwrap();
+            assert!(guard.cpu().unwrap().path().exists());
+
+            guard.cpu_mut().unwrap().add_proc(pid).unwrap();
+        }
+
+        let cpu = cpu::Subsystem::new(CgroupPath::new(SubsystemKind::Cpu, name.clone()));
+        assert!(!cpu.path().exists());
+        assert!(pids.pids().unwrap().path().exists()); // skipped
+        assert!(cpu.root_cgroup().procs().unwrap().contains(&pid));
+
+        sleep.kill().unwrap();
+        sleep.wait()?;
+
+        // a cgroup with a child not created by the guard is left as is
+        let guard =
+            UnifiedRepr::with_subsystems(name.clone(), &[SubsystemKind::Cpu]).create_guarded()?;
+        let mut child =
+            cpu::Subsystem::new(CgroupPath::new(SubsystemKind::Cpu, name.join("child")));
+        child.create()?;
+        assert_eq!(
+            guard.delete().unwrap_err().kind(),
+            crate::ErrorKind::InvalidOperation
+        );
+        assert!(child.path().exists());
+
+        {
+            let _guard = UnifiedRepr::with_subsystems(name.join("other"), &[SubsystemKind::Cpu])
+                .create_guarded()?;
+        }
+        assert!(child.path().exists());
+        assert!(!cpu.path().join("other").exists());
+
+        child.delete()?;
+        let mut cgroups = UnifiedRepr::with_subsystems(name.clone(), &[SubsystemKind::Cpu]);
+        cgroups.delete()?;
+
+        // keep
+        let mut cgroups = UnifiedRepr::with_subsystems(name, &[SubsystemKind::Cpu])
+            .create_guarded()?
+            .keep();
+        assert!(cpu.path().exists());
+        cgroups.delete()?;
+
+        pids.delete()
+    }
+
+    #[test]
+    fn err_unified_repr_create_guarded() -> Result<()> {
+        let name = gen_cgroup_name!();
+        let mut pids = UnifiedRepr::with_subsystems(name.clone(), &[SubsystemKind::Pids]);
+        pids.create()?;
+
+        // fails to create the directory for the pids subsystem, which already exists
+        let cgroups =
+            UnifiedRepr::with_subsystems(name.clone(), &[SubsystemKind::Cpu, SubsystemKind::Pids]);
+        assert_eq!(
+            cgroups.create_guarded().unwrap_err().kind(),
+            crate::ErrorKind::Io
+        );
+
+        let cpu = cpu::Subsystem::new(CgroupPath::new(SubsystemKind::Cpu, name));
+        assert!(!cpu.path().exists());
+        assert!(pids.pids().unwrap().path().exists());
+
+        pids.delete()
+    }
+
+    #[test]
+    fn test_unified_repr_from_pid() -> Result<()> {
+        let pid = Pid::from(std::process::id());
+        let cgroups = UnifiedRepr::from_pid(pid)?;
+
+        assert!(cgroups.supports(SubsystemKind::Cpu));
+        assert!(cgroups.cpu().unwrap().procs()?.contains(&pid));
+
+        Ok(())
+    }
+
     #[test]
     #[ignore] // must not be executed in parallel
     fn test_unified_repr_add_get_remove_tasks() -> Result<()> {
@@ -0,0 +1,751 @@
+use std::{error::Error as StdError, fmt};
+
+use crate::{
+    v1::{self, blkio, cpu, cpuset, hugetlb, memory, Cgroup, Resources},
+    Error, ErrorKind, Result,
+};
+
+const SHARES_MIN: u64 = 2;
+const SHARES_MAX: u64 = 1 << 18;
+const CFS_QUOTA_MIN: i64 = 1000;
+const CFS_PERIOD_MIN: u64 = 1000;
+const CFS_PERIOD_MAX: u64 = 1_000_000;
+const SWAPPINESS_MAX: u64 = 100;
+
+/// Violations of constraints on resource limits and constraints, returned from
+/// [`Resources::validate`] and the `validate` methods of subsystems as the source of an [`Error`].
+///
+/// This error holds all violations found, so that they can be fixed at once.
+///
+/// # Examples
+///
+/// ```
+/// use controlgroup::v1::{Resources, ValidationError};
+///
+/// let mut resources = Resources::default();
+/// resources.cpu.shares = Some(1);
+/// resources.memory.swappiness = Some(200);
+///
+/// let err = resources.validate().unwrap_err();
+/// let source = std::error::Error::source(&err).and_then(|s| s.downcast_ref::<ValidationError>());
+/// let fields = source
+///     .unwrap()
+///     .violations()
+///     .iter()
+///     .map(|v| v.field())
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(fields, ["cpu.shares", "memory.swappiness"]);
+/// ```
+///
+/// [`Resources::validate`]: struct.Resources.html#method.validate
+/// [`Error`]: ../struct.Error.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    violations: Vec<Violation>,
+}
+
+/// Violation of a constraint on a field of [`Resources`].
+///
+/// [`Resources`]: struct.Resources.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    field: String,
+    reason: String,
+}
+
+impl ValidationError {
+    /// Returns the list of violations, in the order of the fields in `Resources`.
+    pub fn violations(&self) -> &[Violation] {
+        &self.violations
+    }
+}
+
+impl StdError for ValidationError {}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid resources (")?;
+        for (i, violation) in self.violations.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}", violation)?;
+        }
+        f.write_str(")")
+    }
+}
+
+impl Violation {
+    /// Returns the path to the violating field from `Resources`, e.g. `cpu.shares` or
+    /// `blkio.weight_device[8:0]`.
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// Returns why the value of the field is invalid.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.reason)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Validator {
+    violations: Vec<Violation>,
+}
+
+impl Validator {
+    fn check(&mut self, valid: bool, field: impl fmt::Display, reason: impl fmt::Display) {
+        if !valid {
+            self.violations.push(Violation {
+                field: field.to_string(),
+                reason: reason.to_string(),
+            });
+        }
+    }
+
+    fn range<T>(&mut self, field: impl fmt::Display, val: T, min: T, max: T)
+    where
+        T: PartialOrd + fmt::Display,
+    {
+        self.check(
+            min <= val && val <= max,
+            field,
+            format_args!(
+                "must be between {} and {} (inclusive), got {}",
+                min, max, val
+            ),
+        );
+    }
+
+    fn finish(self) -> Result<()> {
+        if self.violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::with_source(
+                ErrorKind::InvalidArgument,
+                ValidationError {
+                    violations: self.violations,
+                },
+            ))
+        }
+    }
+}
+
+fn validate(check: impl FnOnce(&mut Validator)) -> Result<()> {
+    let mut validator = Validator::default();
+    check(&mut validator);
+    validator.finish()
+}
+
+impl Resources {
+    /// Validates resource limits and constraints without writing anything, so that invalid values
+    /// are reported before being partially applied to a cgroup.
+    ///
+    /// This method validates the fields of [`cpu`], [`memory`], and [`blkio`] subsystems by their
+    /// `validate` methods. See them for the constraints. Constraints depending on a cgroup, e.g. on
+    /// the CPUs available to the parent cgroup, are validated by [`cpuset::Subsystem::validate`],
+    /// [`hugetlb::Subsystem::validate`], and [`UnifiedRepr::validate`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`ErrorKind::InvalidArgument`] if any field violates a
+    /// constraint. Its source is a [`ValidationError`] holding all violations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use controlgroup::{ErrorKind, v1::Resources};
+    ///
+    /// let mut resources = Resources::default();
+    /// resources.cpu.shares = Some(1024);
+    /// assert!(resources.validate().is_ok());
+    ///
+    /// resources.cpu.cfs_quota_us = Some(500);
+    /// assert_eq!(resources.validate().unwrap_err().kind(), ErrorKind::InvalidArgument);
+    /// ```
+    ///
+    /// [`cpu`]: cpu/struct.Resources.html#method.validate
+    /// [`memory`]: memory/struct.Resources.html#method.validate
+    /// [`blkio`]: blkio/struct.Resources.html#method.validate
+    /// [`cpuset::Subsystem::validate`]: cpuset/struct.Subsystem.html#method.validate
+    /// [`hugetlb::Subsystem::validate`]: hugetlb/struct.Subsystem.html#method.validate
+    /// [`UnifiedRepr::validate`]: struct.UnifiedRepr.html#method.validate
+    /// [`ErrorKind::InvalidArgument`]: ../enum.ErrorKind.html#variant.InvalidArgument
+    /// [`ValidationError`]: struct.ValidationError.html
+    pub fn validate(&self) -> Result<()> {
+        validate(|v| self.check(v))
+    }
+
+    fn check(&self, v: &mut Validator) {
+        self.cpu.check(v);
+        self.memory.check(v);
+        self.blkio.check(v);
+    }
+}
+
+impl v1::UnifiedRepr {
+    /// Validates resource limits and constraints for the cgroups of this `UnifiedRepr` without
+    /// writing anything.
+    ///
+    /// In addition to [`Resources::validate`], this method validates `resources.cpuset` against the
+    /// CPUs and memory nodes available to the parent cgroup by [`cpuset::Subsystem::validate`], if
+    /// this `UnifiedRepr` supports cpuset subsystem, and the hugepage sizes in
+    /// `resources.hugetlb` by [`hugetlb::Subsystem::validate`], if it supports hugetlb subsystem.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to read the CPUs and memory nodes available to the parent cgroup,
+    /// with kind [`ErrorKind::Io`] or [`ErrorKind::Parse`].
+    ///
+    /// Returns an error with kind [`ErrorKind::InvalidArgument`] if any field violates a
+    /// constraint. Its source is a [`ValidationError`] holding all violations.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{Resources, UnifiedRepr};
+    ///
+    /// let mut cgroups = UnifiedRepr::new(PathBuf::from("students/charlie"));
+    ///
+    /// let mut resources = Resources::default();
+    /// resources.cpuset.cpus = Some("0-3".parse()?);
+    ///
+    /// cgroups.validate(&resources)?;
+    /// cgroups.apply(&resources)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Resources::validate`]: struct.Resources.html#method.validate
+    /// [`cpuset::Subsystem::validate`]: cpuset/struct.Subsystem.html#method.validate
+    /// [`hugetlb::Subsystem::validate`]: hugetlb/struct.Subsystem.html#method.validate
+    /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
+    /// [`ErrorKind::Parse`]: ../enum.ErrorKind.html#variant.Parse
+    /// [`ErrorKind::InvalidArgument`]: ../enum.ErrorKind.html#variant.InvalidArgument
+    /// [`ValidationError`]: struct.ValidationError.html
+    pub fn validate(&self, resources: &Resources) -> Result<()> {
+        let mut v = Validator::default();
+        resources.check(&mut v);
+        if let Some(cpuset) = self.cpuset() {
+            cpuset.check(&resources.cpuset, &mut v)?;
+        }
+        if let Some(hugetlb) = self.hugetlb() {
+            hugetlb.check(&resources.hugetlb, &mut v);
+        }
+        v.finish()
+    }
+}
+
+impl cpu::Resources {
+    /// Validates the fields without writing anything.
+    ///
+    /// - `shares` must be between 2 and 262,144 (inclusive).
+    /// - `cfs_quota_us` must be negative (unlimited) or at least 1,000.
+    /// - `cfs_period_us` must be between 1,000 and 1,000,000 (inclusive).
+    /// - `rt_runtime_us` must not exceed `rt_period_us`, if both are set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`ErrorKind::InvalidArgument`] if any field violates a
+    /// constraint. Its source is a [`ValidationError`] holding all violations.
+    ///
+    /// [`ErrorKind::InvalidArgument`]: ../../enum.ErrorKind.html#variant.InvalidArgument
+    /// [`ValidationError`]: ../struct.ValidationError.html
+    pub fn validate(&self) -> Result<()> {
+        validate(|v| self.check(v))
+    }
+
+    fn check(&self, v: &mut Validator) {
+        if let Some(shares) = self.shares {
+            v.range("cpu.shares", shares, SHARES_MIN, SHARES_MAX);
+        }
+
+        if let Some(quota) = self.cfs_quota_us {
+            v.check(
+                !(0..CFS_QUOTA_MIN).contains(&quota),
+                "cpu.cfs_quota_us",
+                format_args!("must be -1 or at least {}, got {}", CFS_QUOTA_MIN, quota),
+            );
+        }
+
+        if let Some(period) = self.cfs_period_us {
+            v.range("cpu.cfs_period_us", period, CFS_PERIOD_MIN, CFS_PERIOD_MAX);
+        }
+
+        if let (Some(runtime), Some(period)) = (self.rt_runtime_us, self.rt_period_us) {
+            v.check(
+                runtime < 0 || runtime as u64 <= period,
+                "cpu.rt_runtime_us",
+                format_args!("must not exceed rt_period_us {}, got {}", period, runtime),
+            );
+        }
+    }
+}
+
+impl memory::Resources {
+    /// Validates the fields without writing anything.
+    ///
+    /// - Limits in bytes must be -1 (unlimited) or non-negative.
+    /// - `memsw_limit_in_bytes` must not be less than `limit_in_bytes`, if both are set.
+    /// - `swappiness` must not exceed 100.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`ErrorKind::InvalidArgument`] if any field violates a
+    /// constraint. Its source is a [`ValidationError`] holding all violations.
+    ///
+    /// [`ErrorKind::InvalidArgument`]: ../../enum.ErrorKind.html#variant.InvalidArgument
+    /// [`ValidationError`]: ../struct.ValidationError.html
+    pub fn validate(&self) -> Result<()> {
+        validate(|v| self.check(v))
+    }
+
+    fn check(&self, v: &mut Validator) {
+        macro_rules! limit {
+            ($( $field: ident ),*) => { $(
+                if let Some(limit) = self.$field {
+                    v.check(
+                        limit >= -1,
+                        concat!("memory.", stringify!($field)),
+                        format_args!("must be -1 or non-negative, got {}", limit),
+                    );
+                }
+            )* };
+        }
+
+        limit!(
+            limit_in_bytes,
+            memsw_limit_in_bytes,
+            kmem_limit_in_bytes,
+            kmem_tcp_limit_in_bytes,
+            soft_limit_in_bytes
+        );
+
+        if let (Some(limit), Some(memsw)) = (self.limit_in_bytes, self.memsw_limit_in_bytes) {
+            let valid = match (limit, memsw) {
+                (_, -1) => true,
+                (-1, _) => false,
+                (limit, memsw) => memsw >= limit,
+            };
+            v.check(
+                valid,
+                "memory.memsw_limit_in_bytes",
+                format_args!(
+                    "must not be less than limit_in_bytes {}, got {}",
+                    limit, memsw
+                ),
+            );
+        }
+
+        if let Some(swappiness) = self.swappiness {
+            v.check(
+                swappiness <= SWAPPINESS_MAX,
+                "memory.swappiness",
+                format_args!("must be at most {}, got {}", SWAPPINESS_MAX, swappiness),
+            );
+        }
+    }
+}
+
+impl blkio::Resources {
+    /// Validates the fields without writing anything.
+    ///
+    /// - `weight`, `weight_device`, `leaf_weight`, and `leaf_weight_device` must be between 10 and
+    ///   1,000 (inclusive). A weight for a device can also be 0, which removes the override.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`ErrorKind::InvalidArgument`] if any field violates a
+    /// constraint. Its source is a [`ValidationError`] holding all violations.
+    ///
+    /// [`ErrorKind::InvalidArgument`]: ../../enum.ErrorKind.html#variant.InvalidArgument
+    /// [`ValidationError`]: ../struct.ValidationError.html
+    pub fn validate(&self) -> Result<()> {
+        validate(|v| self.check(v))
+    }
+
+    fn check(&self, v: &mut Validator) {
+        macro_rules! weight {
+            ($weight: ident, $weight_device: ident) => {
+                if let Some(weight) = self.$weight {
+                    v.range(
+                        concat!("blkio.", stringify!($weight)),
+                        weight,
+                        blkio::WEIGHT_MIN,
+                        blkio::WEIGHT_MAX,
+                    );
+                }
+
+                let mut devices = self.$weight_device.iter().collect::<Vec<_>>();
+                devices.sort_by_key(|&(device, _)| device.to_string());
+                // 0 removes the override
+                for (device, &weight) in devices.into_iter().filter(|&(_, &w)| w != 0) {
+                    v.range(
+                        format_args!(
+                            concat!("blkio.", stringify!($weight_device), "[{}]"),
+                            device
+                        ),
+                        weight,
+                        blkio::WEIGHT_MIN,
+                        blkio::WEIGHT_MAX,
+                    );
+                }
+            };
+        }
+
+        weight!(weight, weight_device);
+        weight!(leaf_weight, leaf_weight_device);
+    }
+}
+
+impl cpuset::Subsystem {
+    /// Validates that `resources.cpus` and `resources.mems` are available to the parent cgroup, or
+    /// this cgroup if it is the root, without writing anything.
+    ///
+    /// The available CPUs and memory nodes are read from `cpuset.effective_cpus` and
+    /// `cpuset.effective_mems` files of the parent cgroup.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to read and parse `cpuset.effective_cpus` or
+    /// `cpuset.effective_mems` file of the parent cgroup, with kind [`ErrorKind::Io`] or
+    /// [`ErrorKind::Parse`].
+    ///
+    /// Returns an error with kind [`ErrorKind::InvalidArgument`] if a CPU or memory node is not
+    /// available. Its source is a [`ValidationError`] holding all violations.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{cpuset, Cgroup, CgroupPath, SubsystemKind};
+    ///
+    /// let mut cgroup = cpuset::Subsystem::new(
+    ///     CgroupPath::new(SubsystemKind::Cpuset, PathBuf::from("students/charlie")));
+    /// cgroup.create()?;
+    ///
+    /// let resources = cpuset::Resources {
+    ///     cpus: Some("0-3".parse()?),
+    ///     ..cpuset::Resources::default()
+    /// };
+    ///
+    /// cgroup.validate(&resources)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ErrorKind::Io`]: ../../enum.ErrorKind.html#variant.Io
+    /// [`ErrorKind::Parse`]: ../../enum.ErrorKind.html#variant.Parse
+    /// [`ErrorKind::InvalidArgument`]: ../../enum.ErrorKind.html#variant.InvalidArgument
+    /// [`ValidationError`]: ../struct.ValidationError.html
+    pub fn validate(&self, resources: &cpuset::Resources) -> Result<()> {
+        let mut v = Validator::default();
+        self.check(resources, &mut v)?;
+        v.finish()
+    }
+
+    fn check(&self, resources: &cpuset::Resources, v: &mut Validator) -> Result<()> {
+        fn subset(v: &mut Validator, field: &str, ids: &cpuset::IdSet, available: &cpuset::IdSet) {
+            let missing = ids
+                .to_hash_set()
+                .difference(&available.to_hash_set())
+                .copied()
+                .collect::<cpuset::IdSet>();
+            v.check(
+                missing.to_hash_set().is_empty(),
+                field,
+                format_args!(
+                    "{} not available in the parent cgroup ({})",
+                    missing, available
+                ),
+            );
+        }
+
+        let parent = self.parent().unwrap_or_else(|| self.root_cgroup());
+
+        if let Some(ref cpus) = resources.cpus {
+            subset(v, "cpuset.cpus", cpus, &parent.effective_cpus()?);
+        }
+        if let Some(ref mems) = resources.mems {
+            subset(v, "cpuset.mems", mems, &parent.effective_mems()?);
+        }
+
+        Ok(())
+    }
+}
+
+impl hugetlb::Subsystem {
+    /// Validates that all hugepage sizes in `resources.limits` are supported by the system, without
+    /// writing anything.
+    ///
+    /// A size is supported if the root cgroup has the files for it, e.g.
+    /// `hugetlb.2MB.limit_in_bytes`. See [`size_supported`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`ErrorKind::InvalidArgument`] if any size is not supported. Its
+    /// source is a [`ValidationError`] holding all violations.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> controlgroup::Result<()> {
+    /// use std::path::PathBuf;
+    /// use controlgroup::v1::{
+    ///     hugetlb::{self, HugepageSize, Limit}, Cgroup, CgroupPath, SubsystemKind};
+    ///
+    /// let cgroup = hugetlb::Subsystem::new(
+    ///     CgroupPath::new(SubsystemKind::HugeTlb, PathBuf::from("students/charlie")));
+    ///
+    /// let mut resources = hugetlb::Resources::default();
+    /// resources.limits.insert(HugepageSize::Mb2, Limit::Pages(4));
+    ///
+    /// cgroup.validate(&resources)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`size_supported`]: #method.size_supported
+    /// [`ErrorKind::InvalidArgument`]: ../../enum.ErrorKind.html#variant.InvalidArgument
+    /// [`ValidationError`]: ../struct.ValidationError.html
+    pub fn validate(&self, resources: &hugetlb::Resources) -> Result<()> {
+        validate(|v| self.check(resources, v))
+    }
+
+    fn check(&self, resources: &hugetlb::Resources, v: &mut Validator) {
+        let mut sizes = resources.limits.keys().copied().collect::<Vec<_>>();
+        sizes.sort_by_key(|size| size.pages_to_bytes(1));
+
+        let root = self.root_cgroup();
+        for size in sizes {
+            v.check(
+                root.size_supported(size),
+                format_args!("hugetlb.limits[{}]", size),
+                "hugepage size is not supported by this host",
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::{backend::TempFake, CgroupPath, SubsystemKind};
+    use std::path::PathBuf;
+
+    fn violations(result: Result<()>) -> Vec<(String, String)> {
+        let err = result.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidArgument);
+
+        StdError::source(&err)
+            .and_then(|e| e.downcast_ref::<ValidationError>())
+            .unwrap()
+            .violations()
+            .iter()
+            .map(|v| (v.field().to_string(), v.reason().to_string()))
+            .collect()
+    }
+
+    fn fields(result: Result<()>) -> Vec<String> {
+        violations(result).into_iter().map(|(f, _)| f).collect()
+    }
+
+    #[test]
+    fn test_resources_validate() {
+        let mut resources = Resources::default();
+        assert!(resources.validate().is_ok());
+
+        resources.cpu.shares = Some(1);
+        resources.cpu.cfs_quota_us = Some(999);
+        resources.memory.swappiness = Some(101);
+        resources.blkio.weight = Some(5);
+        assert_eq!(
+            fields(resources.validate()),
+            [
+                "cpu.shares",
+                "cpu.cfs_quota_us",
+                "memory.swappiness",
+                "blkio.weight"
+            ]
+        );
+
+        let err = resources.validate().unwrap_err();
+        assert_eq!(
+            StdError::source(&err).unwrap().to_string(),
+            "invalid resources (\
+             cpu.shares: must be between 2 and 262144 (inclusive), got 1; \
+             cpu.cfs_quota_us: must be -1 or at least 1000, got 999; \
+             memory.swappiness: must be at most 100, got 101; \
+             blkio.weight: must be between 10 and 1000 (inclusive), got 5)"
+        );
+    }
+
+    #[test]
+    fn test_cpu_validate() {
+        let mut cpu = cpu::Resources {
+            shares: Some(2),
+            cfs_quota_us: Some(-1),
+            cfs_period_us: Some(100_000),
+            rt_runtime_us: Some(-1),
+            rt_period_us: Some(1_000_000),
+        };
+        assert!(cpu.validate().is_ok());
+
+        cpu.cfs_quota_us = Some(0);
+        assert_eq!(fields(cpu.validate()), ["cpu.cfs_quota_us"]);
+        cpu.cfs_quota_us = Some(1000);
+        assert!(cpu.validate().is_ok());
+
+        cpu.shares = Some(262_145);
+        cpu.cfs_period_us = Some(999);
+        cpu.rt_runtime_us = Some(1_000_001);
+        assert_eq!(
+            fields(cpu.validate()),
+            ["cpu.shares", "cpu.cfs_period_us", "cpu.rt_runtime_us"]
+        );
+    }
+
+    #[test]
+    fn test_memory_validate() {
+        let mut memory = memory::Resources {
+            limit_in_bytes: Some(-1),
+            memsw_limit_in_bytes: Some(-1),
+            swappiness: Some(100),
+            ..memory::Resources::default()
+        };
+        assert!(memory.validate().is_ok());
+
+        memory.memsw_limit_in_bytes = Some(1 << 30);
+        assert_eq!(
+            violations(memory.validate()),
+            [(
+                "memory.memsw_limit_in_bytes".to_string(),
+                "must not be less than limit_in_bytes -1, got 1073741824".to_string()
+            )]
+        );
+
+        memory.limit_in_bytes = Some(1 << 30);
+        assert!(memory.validate().is_ok());
+        memory.limit_in_bytes = Some((1 << 30) + 1);
+        assert_eq!(fields(memory.validate()), ["memory.memsw_limit_in_bytes"]);
+
+        memory.limit_in_bytes = Some(-2);
+        memory.memsw_limit_in_bytes = None;
+        memory.kmem_limit_in_bytes = Some(-3);
+        assert_eq!(
+            fields(memory.validate()),
+            ["memory.limit_in_bytes", "memory.kmem_limit_in_bytes"]
+        );
+    }
+
+    #[test]
+    fn test_hugetlb_validate() -> Result<()> {
+        use hugetlb::{HugepageSize, Limit};
+
+        let fake = TempFake::new(gen_cgroup_name!(), &[SubsystemKind::HugeTlb])?;
+
+        let cgroup = hugetlb::Subsystem::new(
+            CgroupPath::new(SubsystemKind::HugeTlb, PathBuf::from("a"))
+                .with_backend(fake.backend()),
+        );
+        let root = cgroup.root_cgroup().path();
+        for file in &[
+            "limit_in_bytes",
+            "usage_in_bytes",
+            "max_usage_in_bytes",
+            "failcnt",
+        ] {
+            fake.set_contents(&root.join(format!("hugetlb.2MB.{}", file)), "0\n")?;
+        }
+
+        let mut hugetlb = hugetlb::Resources::default();
+        hugetlb.limits.insert(HugepageSize::Mb2, Limit::Pages(1));
+        assert!(cgroup.validate(&hugetlb).is_ok());
+
+        hugetlb.limits.insert(HugepageSize::Gb1, Limit::Pages(1));
+        hugetlb.limits.insert(HugepageSize::Kb64, Limit::Pages(1));
+        assert_eq!(
+            fields(cgroup.validate(&hugetlb)),
+            ["hugetlb.limits[64KB]", "hugetlb.limits[1GB]"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blkio_validate() {
+        let mut blkio = blkio::Resources {
+            weight: Some(10),
+            leaf_weight: Some(100),
+            ..blkio::Resources::default()
+        };
+        blkio.weight_device.insert([8, 0].into(), 1000);
+        blkio.leaf_weight_device.insert([8, 48].into(), 0);
+        assert!(blkio.validate().is_ok());
+
+        blkio.leaf_weight = Some(10);
+        assert!(blkio.validate().is_ok());
+
+        blkio.weight = Some(1001);
+        blkio.leaf_weight = Some(9);
+        blkio.weight_device.insert([8, 16].into(), 9);
+        blkio.weight_device.insert([8, 32].into(), 10);
+        blkio.leaf_weight_device.insert([8, 0].into(), 5);
+        assert_eq!(
+            fields(blkio.validate()),
+            [
+                "blkio.weight",
+                "blkio.weight_device[8:16]",
+                "blkio.leaf_weight",
+                "blkio.leaf_weight_device[8:0]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cpuset_validate() -> Result<()> {
+        let fake = TempFake::new(gen_cgroup_name!(), &[SubsystemKind::Cpuset])?;
+
+        let cgroup = cpuset::Subsystem::new(
+            CgroupPath::new(SubsystemKind::Cpuset, PathBuf::from("a")).with_backend(fake.backend()),
+        );
+        let root = cgroup.root_cgroup().path();
+        fake.set_contents(&root.join("cpuset.effective_cpus"), "0-3\n")?;
+        fake.set_contents(&root.join("cpuset.effective_mems"), "0\n")?;
+
+        let mut resources = cpuset::Resources {
+            cpus: Some("1-3".parse()?),
+            mems: Some("0".parse()?),
+            ..cpuset::Resources::default()
+        };
+        cgroup.validate(&resources)?;
+
+        resources.cpus = Some("2-5".parse()?);
+        resources.mems = Some("0,1".parse()?);
+        assert_eq!(
+            violations(cgroup.validate(&resources)),
+            [
+                (
+                    "cpuset.cpus".to_string(),
+                    "4,5 not available in the parent cgroup (0-3)".to_string()
+                ),
+                (
+                    "cpuset.mems".to_string(),
+                    "1 not available in the parent cgroup (0)".to_string()
+                ),
+            ]
+        );
+
+        Ok(())
+    }
+}
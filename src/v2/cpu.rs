@@ -0,0 +1,303 @@
+//! Operations on a CPU controller in a v2 hierarchy.
+//!
+//! The operations are implemented as methods of [`Cgroup`], prefixed with `cpu_`.
+//!
+//! For more information about this controller, see the kernel's documentation
+//! [Documentation/admin-guide/cgroup-v2.rst].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # fn main() -> controlgroup::Result<()> {
+//! use std::path::PathBuf;
+//! use controlgroup::v2::{cpu, Cgroup, Max64};
+//!
+//! let mut cgroup = Cgroup::new(PathBuf::from("students/charlie"));
+//! cgroup.create()?;
+//!
+//! // Set a relative weight of CPU time this cgroup can use.
+//! cgroup.set_cpu_weight(200)?;
+//!
+//! // Limit CPU time this cgroup can use to 500 ms per 1 sec.
+//! cgroup.set_cpu_max(cpu::Bandwidth { max: Max64::Limit(500_000), period: 1_000_000 })?;
+//!
+//! // Do something ...
+//!
+//! println!("{:?}", cgroup.cpu_stat()?);
+//!
+//! cgroup.delete()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Cgroup`]: ../struct.Cgroup.html
+//!
+//! [Documentation/admin-guide/cgroup-v2.rst]: https://www.kernel.org/doc/Documentation/admin-guide/cgroup-v2.rst
+
+use std::{fmt, str::FromStr};
+
+use crate::{
+    parse::{parse, parse_next},
+    v2::{parse_flat_keyed, take_key, Cgroup, Max64},
+    Error, Result,
+};
+
+/// Resource limit on how much CPU time a cgroup can use.
+///
+/// See the kernel's documentation for more information about the fields.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Resources {
+    /// Weight of how much CPU time this cgroup can use relative to its siblings, in the range of
+    /// [1, 10000].
+    pub weight: Option<u64>,
+    /// Maximum bandwidth of CPU time this cgroup can use.
+    pub max: Option<Bandwidth>,
+}
+
+/// Maximum bandwidth of CPU time, as in `cpu.max` file.
+///
+/// `Bandwidth` implements [`FromStr`] and [`Display`]. The string representation is a pair of the
+/// maximum and the period, separated by a whitespace.
+///
+/// ```
+/// use controlgroup::v2::{cpu::Bandwidth, Max64};
+///
+/// let bw = "max 100000".parse::<Bandwidth>().unwrap();
+/// assert_eq!(bw, Bandwidth { max: Max64::Max, period: 100_000 });
+/// assert_eq!(bw.to_string(), "max 100000");
+/// ```
+///
+/// [`FromStr`]: https://doc.rust-lang.org/std/str/trait.FromStr.html
+/// [`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bandwidth {
+    /// This cgroup can use CPU time up to this value in microseconds per `period`.
+    pub max: Max64,
+    /// Length of a period in microseconds.
+    pub period: u64,
+}
+
+/// Statistics of CPU usage of a cgroup, as in `cpu.stat` file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stat {
+    /// Total CPU time consumed by tasks in this cgroup in microseconds.
+    pub usage_usec: u64,
+    /// CPU time consumed in user mode in microseconds.
+    pub user_usec: u64,
+    /// CPU time consumed in system (kernel) mode in microseconds.
+    pub system_usec: u64,
+    /// Number of periods that have elapsed. `None` if the CPU controller is not enabled.
+    pub nr_periods: Option<u64>,
+    /// Number of times this cgroup has been throttled. `None` if the CPU controller is not enabled.
+    pub nr_throttled: Option<u64>,
+    /// Total time duration for which tasks in this cgroup have been throttled in microseconds.
+    /// `None` if the CPU controller is not enabled.
+    pub throttled_usec: Option<u64>,
+}
+
+impl Cgroup {
+    pub(crate) fn apply_cpu(&mut self, resources: &Resources) -> Result<()> {
+        if let Some(weight) = resources.weight {
+            self.set_cpu_weight(weight)?;
+        }
+        if let Some(max) = resources.max {
+            self.set_cpu_max(max)?;
+        }
+
+        Ok(())
+    }
+
+    gen_getter!(
+        "cpu.stat",
+        "the statistics of CPU usage of this cgroup",
+        cpu_stat,
+        Stat,
+        parse_stat
+    );
+
+    gen_getter!(
+        "cpu.weight",
+        "the relative weight of CPU time this cgroup can use",
+        cpu_weight: cpu.weight,
+        u64,
+        parse
+    );
+
+    gen_setter!(
+        "cpu.weight",
+        "a relative weight of CPU time this cgroup can use",
+        set_cpu_weight: cpu.weight,
+        weight: u64,
+        200
+    );
+
+    gen_getter!(
+        "cpu.weight.nice",
+        "the relative weight of CPU time this cgroup can use, in the nice value scale",
+        cpu_weight_nice,
+        i8,
+        parse
+    );
+
+    gen_setter!(
+        "cpu.weight.nice",
+        "a relative weight of CPU time this cgroup can use, in the nice value scale",
+        set_cpu_weight_nice,
+        nice: i8,
+        -5
+    );
+
+    gen_getter!(
+        "cpu.max",
+        "the maximum bandwidth of CPU time this cgroup can use",
+        cpu_max: cpu.max,
+        Bandwidth,
+        parse
+    );
+
+    gen_setter!(
+        "cpu.max",
+        "a maximum bandwidth of CPU time this cgroup can use",
+        set_cpu_max: cpu.max,
+        max: Bandwidth,
+        v2::cpu::Bandwidth { max: v2::Max64::Limit(500_000), period: 1_000_000 }
+    );
+}
+
+impl From<Resources> for super::Resources {
+    fn from(resources: Resources) -> Self {
+        Self {
+            cpu: resources,
+            ..Self::default()
+        }
+    }
+}
+
+impl FromStr for Bandwidth {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut entry = s.split_whitespace();
+        let max = parse_next(&mut entry)?;
+        let period = parse_next(&mut entry)?;
+
+        if entry.next().is_some() {
+            bail_parse!();
+        }
+
+        Ok(Self { max, period })
+    }
+}
+
+impl fmt::Display for Bandwidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.max, self.period)
+    }
+}
+
+fn parse_stat(reader: impl std::io::Read) -> Result<Stat> {
+    let mut map = parse_flat_keyed(reader)?;
+
+    Ok(Stat {
+        usage_usec: take_key(&mut map, "usage_usec")?,
+        user_usec: take_key(&mut map, "user_usec")?,
+        system_usec: take_key(&mut map, "system_usec")?,
+        nr_periods: map.remove("nr_periods"),
+        nr_throttled: map.remove("nr_throttled"),
+        throttled_usec: map.remove("throttled_usec"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+
+    #[test]
+    fn test_bandwidth() -> Result<()> {
+        assert_eq!(
+            "max 100000".parse::<Bandwidth>()?,
+            Bandwidth {
+                max: Max64::Max,
+                period: 100_000
+            }
+        );
+        assert_eq!(
+            "50000 100000".parse::<Bandwidth>()?,
+            Bandwidth {
+                max: Max64::Limit(50_000),
+                period: 100_000
+            }
+        );
+
+        for case in &["", "max", "max 100000 0", "invalid 100000"] {
+            assert_eq!(
+                case.parse::<Bandwidth>().unwrap_err().kind(),
+                ErrorKind::Parse
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_stat() -> Result<()> {
+        const CONTENT_OK: &str = "\
+usage_usec 42
+user_usec 30
+system_usec 12
+nr_periods 7
+nr_throttled 2
+throttled_usec 100
+";
+        assert_eq!(
+            parse_stat(CONTENT_OK.as_bytes())?,
+            Stat {
+                usage_usec: 42,
+                user_usec: 30,
+                system_usec: 12,
+                nr_periods: Some(7),
+                nr_throttled: Some(2),
+                throttled_usec: Some(100),
+            }
+        );
+
+        const CONTENT_OK_CORE: &str = "usage_usec 42\nuser_usec 30\nsystem_usec 12\n";
+        assert_eq!(
+            parse_stat(CONTENT_OK_CORE.as_bytes())?,
+            Stat {
+                usage_usec: 42,
+                user_usec: 30,
+                system_usec: 12,
+                nr_periods: None,
+                nr_throttled: None,
+                throttled_usec: None,
+            }
+        );
+
+        const CONTENT_NG_MISSING: &str = "usage_usec 42\nuser_usec 30\n";
+        assert_eq!(
+            parse_stat(CONTENT_NG_MISSING.as_bytes())
+                .unwrap_err()
+                .kind(),
+            ErrorKind::Parse
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cgroup_cpu_stat() -> Result<()> {
+        let mut cgroup = gen_v2_cgroup!();
+        cgroup.create()?;
+
+        let stat = cgroup.cpu_stat()?;
+        assert_eq!(stat.usage_usec, 0);
+
+        cgroup.delete()
+    }
+}
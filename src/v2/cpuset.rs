@@ -0,0 +1,118 @@
+//! Operations on a cpuset controller in a v2 hierarchy.
+//!
+//! The operations are implemented as methods of [`Cgroup`], prefixed with `cpuset_`.
+//!
+//! For more information about this controller, see the kernel's documentation
+//! [Documentation/admin-guide/cgroup-v2.rst].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # fn main() -> controlgroup::Result<()> {
+//! use std::path::PathBuf;
+//! use controlgroup::v2::Cgroup;
+//!
+//! let mut cgroup = Cgroup::new(PathBuf::from("students/charlie"));
+//! cgroup.create()?;
+//!
+//! // Set CPUs and memory nodes this cgroup can use.
+//! cgroup.set_cpuset_cpus(&"0,1".parse()?)?;
+//! cgroup.set_cpuset_mems(&"0".parse()?)?;
+//!
+//! // Do something ...
+//!
+//! println!("{}", cgroup.cpuset_cpus_effective()?);
+//!
+//! cgroup.delete()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Cgroup`]: ../struct.Cgroup.html
+//!
+//! [Documentation/admin-guide/cgroup-v2.rst]: https://www.kernel.org/doc/Documentation/admin-guide/cgroup-v2.rst
+
+use crate::{parse::parse, v1::cpuset::IdSet, v2::Cgroup, Result};
+
+/// Resource limit on which CPUs and memory nodes a cgroup can use.
+///
+/// See the kernel's documentation for more information about the fields.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Resources {
+    /// Set of CPUs this cgroup can use.
+    pub cpus: Option<IdSet>,
+    /// Set of memory nodes this cgroup can use.
+    pub mems: Option<IdSet>,
+}
+
+impl Cgroup {
+    pub(crate) fn apply_cpuset(&mut self, resources: &Resources) -> Result<()> {
+        if let Some(ref cpus) = resources.cpus {
+            self.set_cpuset_cpus(cpus)?;
+        }
+        if let Some(ref mems) = resources.mems {
+            self.set_cpuset_mems(mems)?;
+        }
+
+        Ok(())
+    }
+
+    gen_getter!(
+        "cpuset.cpus",
+        "the set of CPUs this cgroup requested to use",
+        cpuset_cpus: cpuset.cpus,
+        IdSet,
+        parse
+    );
+
+    gen_setter!(
+        "cpuset.cpus",
+        "a set of CPUs this cgroup can use",
+        set_cpuset_cpus: cpuset.cpus,
+        cpus: &IdSet,
+        &"0,1".parse::<controlgroup::v1::cpuset::IdSet>()?
+    );
+
+    gen_getter!(
+        "cpuset.cpus.effective",
+        "the set of CPUs this cgroup can actually use",
+        cpuset_cpus_effective,
+        IdSet,
+        parse
+    );
+
+    gen_getter!(
+        "cpuset.mems",
+        "the set of memory nodes this cgroup requested to use",
+        cpuset_mems: cpuset.mems,
+        IdSet,
+        parse
+    );
+
+    gen_setter!(
+        "cpuset.mems",
+        "a set of memory nodes this cgroup can use",
+        set_cpuset_mems: cpuset.mems,
+        mems: &IdSet,
+        &"0".parse::<controlgroup::v1::cpuset::IdSet>()?
+    );
+
+    gen_getter!(
+        "cpuset.mems.effective",
+        "the set of memory nodes this cgroup can actually use",
+        cpuset_mems_effective,
+        IdSet,
+        parse
+    );
+}
+
+impl From<Resources> for super::Resources {
+    fn from(resources: Resources) -> Self {
+        Self {
+            cpuset: resources,
+            ..Self::default()
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Operations on a HugeTLB controller in a v2 hierarchy.
+//!
+//! The operations are implemented as methods of [`Cgroup`], prefixed with `hugetlb_`.
+//!
+//! For more information about this controller, see the kernel's documentation
+//! [Documentation/admin-guide/cgroup-v2.rst].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # fn main() -> controlgroup::Result<()> {
+//! use std::path::PathBuf;
+//! use controlgroup::v2::{Cgroup, HugepageSize, Max64};
+//!
+//! let mut cgroup = Cgroup::new(PathBuf::from("students/charlie"));
+//! cgroup.create()?;
+//!
+//! // Limit hugepage TLB usage of 2 MB hugepages to 4 pages.
+//! cgroup.set_hugetlb_max(HugepageSize::Mb2, Max64::Limit(4 * (2 << 20)))?;
+//!
+//! // Do something ...
+//!
+//! println!("{} bytes used", cgroup.hugetlb_current(HugepageSize::Mb2)?);
+//!
+//! cgroup.delete()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Cgroup`]: ../struct.Cgroup.html
+//!
+//! [Documentation/admin-guide/cgroup-v2.rst]: https://www.kernel.org/doc/Documentation/admin-guide/cgroup-v2.rst
+
+use std::collections::HashMap;
+
+use crate::{
+    parse::parse,
+    v1::hugetlb::HugepageSize,
+    v2::{parse_flat_keyed, take_key, Cgroup, Max64},
+    Result,
+};
+
+/// Resource limit on how many hugepage TLBs a cgroup can use.
+///
+/// See the kernel's documentation for more information about the fields.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Resources {
+    /// How many hugepage TLBs in bytes this cgroup can use for each hugepage size.
+    pub max: HashMap<HugepageSize, Max64>,
+}
+
+impl Cgroup {
+    pub(crate) fn apply_hugetlb(&mut self, resources: &Resources) -> Result<()> {
+        for (&size, &max) in &resources.max {
+            self.set_hugetlb_max(size, max)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the system supports hugepage in `size`.
+    ///
+    /// Note that this method returns `false` if the directory of this cgroup is not created yet.
+    pub fn hugetlb_size_supported(&self, size: HugepageSize) -> bool {
+        self.file_exists(&format!("hugetlb.{}.max", size))
+    }
+
+    with_doc! { concat!(
+        gen_doc!(
+            reads; "hugetlb.<hugepage size>.max",
+            "the limit of hugepage TLB usage in bytes"
+        ),
+        gen_doc!(see; hugetlb.max),
+        gen_doc!(err_read; "hugetlb.<hugepage size>.max"),
+        gen_doc!(eg_read; hugetlb_max, v2::HugepageSize::Mb2)),
+        pub fn hugetlb_max(&self, size: HugepageSize) -> Result<Max64> {
+            self.read_file(&format!("hugetlb.{}.max", size), parse)
+        }
+    }
+
+    with_doc! { concat!(
+        gen_doc!(
+            sets; "hugetlb.<hugepage size>.max",
+            "a limit of hugepage TLB usage in bytes"
+        ),
+        gen_doc!(see; hugetlb.max),
+        gen_doc!(err_write; "hugetlb.<hugepage size>.max"),
+        gen_doc!(eg_write; set_hugetlb_max, v2::HugepageSize::Mb2, v2::Max64::Limit(4 << 20))),
+        pub fn set_hugetlb_max(&mut self, size: HugepageSize, max: Max64) -> Result<()> {
+            self.write_file(&format!("hugetlb.{}.max", size), max)
+        }
+    }
+
+    with_doc! { concat!(
+        gen_doc!(
+            reads; "hugetlb.<hugepage size>.current",
+            "the current usage of hugepage TLB in bytes"
+        ),
+        gen_doc!(see),
+        gen_doc!(err_read; "hugetlb.<hugepage size>.current"),
+        gen_doc!(eg_read; hugetlb_current, v2::HugepageSize::Mb2)),
+        pub fn hugetlb_current(&self, size: HugepageSize) -> Result<u64> {
+            self.read_file(&format!("hugetlb.{}.current", size), parse)
+        }
+    }
+
+    with_doc! { concat!(
+        gen_doc!(
+            reads; "hugetlb.<hugepage size>.events",
+            "the number of allocation failure due to the limit"
+        ),
+        gen_doc!(see),
+        gen_doc!(err_read; "hugetlb.<hugepage size>.events"),
+        gen_doc!(eg_read; hugetlb_events, v2::HugepageSize::Mb2)),
+        pub fn hugetlb_events(&self, size: HugepageSize) -> Result<u64> {
+            self.read_file(&format!("hugetlb.{}.events", size), parse_events)
+        }
+    }
+}
+
+impl From<Resources> for super::Resources {
+    fn from(resources: Resources) -> Self {
+        Self {
+            hugetlb: resources,
+            ..Self::default()
+        }
+    }
+}
+
+fn parse_events(reader: impl std::io::Read) -> Result<u64> {
+    take_key(&mut parse_flat_keyed(reader)?, "max")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+
+    #[test]
+    fn test_parse_events() -> Result<()> {
+        assert_eq!(parse_events("max 3\n".as_bytes())?, 3);
+        assert_eq!(
+            parse_events("".as_bytes()).unwrap_err().kind(),
+            ErrorKind::Parse
+        );
+
+        Ok(())
+    }
+}
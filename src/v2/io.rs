@@ -0,0 +1,381 @@
+//! Operations on an IO controller in a v2 hierarchy.
+//!
+//! The operations are implemented as methods of [`Cgroup`], prefixed with `io_`.
+//!
+//! For more information about this controller, see the kernel's documentation
+//! [Documentation/admin-guide/cgroup-v2.rst].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # fn main() -> controlgroup::Result<()> {
+//! use std::path::PathBuf;
+//! use controlgroup::{Device, v2::{io, Cgroup, Max64}};
+//!
+//! let mut cgroup = Cgroup::new(PathBuf::from("students/charlie"));
+//! cgroup.create()?;
+//!
+//! // Set a relative weight of block I/O performed by this cgroup.
+//! cgroup.set_io_weight(1000)?;
+//!
+//! // Limit the bytes per second this cgroup can read from a device.
+//! let device = Device::from([8, 0]);
+//! cgroup.set_io_max(device, io::Limit { rbps: Max64::Limit(1 << 20), ..io::Limit::default() })?;
+//!
+//! // Do something ...
+//!
+//! println!("{:?}", cgroup.io_stat()?.get(&device));
+//!
+//! cgroup.delete()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Cgroup`]: ../struct.Cgroup.html
+//!
+//! [Documentation/admin-guide/cgroup-v2.rst]: https://www.kernel.org/doc/Documentation/admin-guide/cgroup-v2.rst
+
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, BufRead},
+};
+
+use crate::{
+    parse::parse_next,
+    v2::{parse_nested_keyed, take_key, Cgroup, Max64},
+    Device, Error, ErrorKind, Result,
+};
+
+/// Resource limit on how much block I/O a cgroup can perform.
+///
+/// See the kernel's documentation for more information about the fields.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Resources {
+    /// Default weight of block I/O performed by this cgroup, in the range of [1, 10000].
+    pub weight: Option<u16>,
+    /// Overrides `weight` for specific devices.
+    pub weight_device: HashMap<Device, u16>,
+    /// Bandwidth and IOPS limits for specific devices.
+    pub max: HashMap<Device, Limit>,
+}
+
+/// Bandwidth and IOPS limits of a device, as in `io.max` file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Limit {
+    /// Read bytes per second.
+    pub rbps: Max64,
+    /// Write bytes per second.
+    pub wbps: Max64,
+    /// Read I/O operations per second.
+    pub riops: Max64,
+    /// Write I/O operations per second.
+    pub wiops: Max64,
+}
+
+/// Statistics of block I/O performed on a device, as in `io.stat` file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stat {
+    /// Bytes read.
+    pub rbytes: u64,
+    /// Bytes written.
+    pub wbytes: u64,
+    /// Number of read I/O operations.
+    pub rios: u64,
+    /// Number of write I/O operations.
+    pub wios: u64,
+    /// Bytes discarded. `None` if the kernel does not report discards.
+    pub dbytes: Option<u64>,
+    /// Number of discard I/O operations. `None` if the kernel does not report discards.
+    pub dios: Option<u64>,
+}
+
+impl Cgroup {
+    pub(crate) fn apply_io(&mut self, resources: &Resources) -> Result<()> {
+        if let Some(weight) = resources.weight {
+            self.set_io_weight(weight)?;
+        }
+        for (&device, &weight) in &resources.weight_device {
+            self.set_io_weight_device(device, weight)?;
+        }
+        for (&device, &limit) in &resources.max {
+            self.set_io_max(device, limit)?;
+        }
+
+        Ok(())
+    }
+
+    gen_getter!(
+        "io.stat",
+        "the statistics of block I/O performed by this cgroup,"
+            : "for each device.",
+        io_stat,
+        HashMap<Device, Stat>,
+        parse_stat
+    );
+
+    gen_getter!(
+        "io.weight",
+        "the default weight and the per-device weights of block I/O performed by this cgroup",
+        io_weight: io.weight,
+        (u16, HashMap<Device, u16>),
+        parse_weight
+    );
+
+    gen_setter!(
+        "io.weight",
+        "a default weight of block I/O performed by this cgroup",
+        set_io_weight: io.weight,
+        weight: u16,
+        1000
+    );
+
+    with_doc! { concat!(
+        gen_doc!(
+            sets; "io.weight",
+            "a weight of block I/O performed by this cgroup for a device,"
+                : "overriding the default weight."
+        ),
+        gen_doc!(see; io.weight_device),
+        gen_doc!(err_write; "io.weight"),
+        gen_doc!(eg_write; set_io_weight_device, [8, 0].into(), 1000)),
+        pub fn set_io_weight_device(&mut self, device: Device, weight: u16) -> Result<()> {
+            self.write_file("io.weight", format!("{} {}", device, weight))
+        }
+    }
+
+    gen_getter!(
+        "io.max",
+        "the bandwidth and IOPS limits of block I/O performed by this cgroup",
+        io_max: io.max,
+        HashMap<Device, Limit>,
+        parse_max
+    );
+
+    with_doc! { concat!(
+        gen_doc!(
+            sets; "io.max",
+            "bandwidth and IOPS limits of block I/O performed by this cgroup for a device"
+        ),
+        gen_doc!(see; io.max),
+        gen_doc!(err_write; "io.max"),
+        gen_doc!(eg_write; set_io_max, [8, 0].into(), v2::io::Limit::default())),
+        pub fn set_io_max(&mut self, device: Device, limit: Limit) -> Result<()> {
+            self.write_file("io.max", format!("{} {}", device, limit))
+        }
+    }
+}
+
+impl From<Resources> for super::Resources {
+    fn from(resources: Resources) -> Self {
+        Self {
+            io: resources,
+            ..Self::default()
+        }
+    }
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rbps={} wbps={} riops={} wiops={}",
+            self.rbps, self.wbps, self.riops, self.wiops
+        )
+    }
+}
+
+fn parse_stat(reader: impl io::Read) -> Result<HashMap<Device, Stat>> {
+    let mut result = HashMap::new();
+
+    for (device, mut map) in parse_nested_keyed(reader)? {
+        result.insert(
+            device,
+            Stat {
+                rbytes: take_key(&mut map, "rbytes")?,
+                wbytes: take_key(&mut map, "wbytes")?,
+                rios: take_key(&mut map, "rios")?,
+                wios: take_key(&mut map, "wios")?,
+                dbytes: map.remove("dbytes"),
+                dios: map.remove("dios"),
+            },
+        );
+    }
+
+    Ok(result)
+}
+
+fn parse_weight(reader: impl io::Read) -> Result<(u16, HashMap<Device, u16>)> {
+    let mut default = None;
+    let mut devices = HashMap::new();
+
+    for line in io::BufReader::new(reader).lines() {
+        let line = line?;
+        let mut entry = line.split_whitespace();
+
+        match entry.next() {
+            Some("default") => {
+                if default.is_some() {
+                    bail_parse!();
+                }
+                default = Some(parse_next(&mut entry)?);
+            }
+            Some(device) => {
+                let device = device.parse::<Device>()?;
+                if devices.insert(device, parse_next(&mut entry)?).is_some() {
+                    bail_parse!();
+                }
+            }
+            None => {
+                bail_parse!();
+            }
+        }
+
+        if entry.next().is_some() {
+            bail_parse!();
+        }
+    }
+
+    default
+        .map(|d| (d, devices))
+        .ok_or_else(|| Error::new(ErrorKind::Parse))
+}
+
+fn parse_max(reader: impl io::Read) -> Result<HashMap<Device, Limit>> {
+    let mut result = HashMap::new();
+
+    for (device, mut map) in parse_nested_keyed(reader)? {
+        result.insert(
+            device,
+            Limit {
+                rbps: take_key(&mut map, "rbps")?,
+                wbps: take_key(&mut map, "wbps")?,
+                riops: take_key(&mut map, "riops")?,
+                wiops: take_key(&mut map, "wiops")?,
+            },
+        );
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stat() -> Result<()> {
+        const CONTENT_OK: &str = "\
+8:16 rbytes=1459200 wbytes=314773504 rios=192 wios=353 dbytes=0 dios=0
+8:0 rbytes=90430464 wbytes=299008000 rios=8950 wios=1252
+";
+        assert_eq!(
+            parse_stat(CONTENT_OK.as_bytes())?,
+            hashmap! {
+                (
+                    [8, 16].into(),
+                    Stat {
+                        rbytes: 1459200,
+                        wbytes: 314773504,
+                        rios: 192,
+                        wios: 353,
+                        dbytes: Some(0),
+                        dios: Some(0),
+                    }
+                ),
+                (
+                    [8, 0].into(),
+                    Stat {
+                        rbytes: 90430464,
+                        wbytes: 299008000,
+                        rios: 8950,
+                        wios: 1252,
+                        dbytes: None,
+                        dios: None,
+                    }
+                ),
+            }
+        );
+
+        assert!(parse_stat("".as_bytes())?.is_empty());
+
+        const CONTENT_NG_MISSING: &str = "8:0 rbytes=1 wbytes=2 rios=3\n";
+        assert_eq!(
+            parse_stat(CONTENT_NG_MISSING.as_bytes())
+                .unwrap_err()
+                .kind(),
+            ErrorKind::Parse
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_weight() -> Result<()> {
+        assert_eq!(
+            parse_weight("default 100\n8:0 200\n".as_bytes())?,
+            (100, hashmap! { ([8, 0].into(), 200) })
+        );
+        assert_eq!(
+            parse_weight("default 100\n".as_bytes())?,
+            (100, HashMap::new())
+        );
+
+        for case in &[
+            "",
+            "8:0 200\n",
+            "default 100 200\n",
+            "default 1\ndefault 2\n",
+        ] {
+            assert_eq!(
+                parse_weight(case.as_bytes()).unwrap_err().kind(),
+                ErrorKind::Parse
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_max() -> Result<()> {
+        const CONTENT_OK: &str = "8:16 rbps=2097152 wbps=max riops=max wiops=120\n";
+        assert_eq!(
+            parse_max(CONTENT_OK.as_bytes())?,
+            hashmap! {
+                (
+                    [8, 16].into(),
+                    Limit {
+                        rbps: Max64::Limit(2097152),
+                        wbps: Max64::Max,
+                        riops: Max64::Max,
+                        wiops: Max64::Limit(120),
+                    }
+                ),
+            }
+        );
+
+        const CONTENT_NG_MISSING: &str = "8:16 rbps=2097152 wbps=max riops=max\n";
+        assert_eq!(
+            parse_max(CONTENT_NG_MISSING.as_bytes()).unwrap_err().kind(),
+            ErrorKind::Parse
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_limit_display() {
+        let limit = Limit {
+            rbps: Max64::Limit(1 << 20),
+            ..Limit::default()
+        };
+        assert_eq!(
+            limit.to_string(),
+            "rbps=1048576 wbps=max riops=max wiops=max"
+        );
+    }
+}
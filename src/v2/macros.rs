@@ -0,0 +1,104 @@
+macro_rules! gen_doc {
+    (reads; $file: expr, $desc: literal $( : $detail: literal )?) => { concat!(
+        "Reads ", $desc, " from `", $file, "` file.",
+        $( " ", $detail, )? "\n\n",
+    ) };
+
+    (sets; $file: expr, $desc: literal $( : $detail: literal )?) => { concat!(
+        "Sets ", $desc, " by writing to `", $file, "` file.",
+        $( " ", $detail, )? "\n\n",
+    ) };
+
+    (see $(; $res: ident . $field: ident )?)  => { concat!(
+        "See"
+        $(, " [`", stringify!($res), "::Resources.", stringify!($field), "`]",
+            "(", stringify!($res), "/struct.Resources.html#structfield.", stringify!($field), ")",
+            " and" )?,
+        " the kernel's documentation for more information about this field.\n\n"
+    ) };
+
+    (err_read; $file: expr) => { concat!(
+        "# Errors\n\n",
+        "Returns an error if failed to read and parse `", $file, "` file of this cgroup.\n\n"
+    ) };
+    (err_write; $file: expr) => { concat!(
+        "# Errors\n\n",
+        "Returns an error if failed to write to `", $file, "` file of this cgroup.\n\n"
+    ) };
+
+    (eg_read; $getter: ident $(, $val: expr )*) => { concat!(
+"# Examples
+
+```no_run
+# fn main() -> controlgroup::Result<()> {
+use std::path::PathBuf;
+use controlgroup::v2;
+
+let cgroup = v2::Cgroup::new(PathBuf::from(\"students/charlie\"));
+
+let ", stringify!($getter), " = cgroup.", stringify!($getter), "(", stringify!($( $val ),* ), ")?;
+# Ok(())
+# }
+```") };
+
+    (eg_write; $setter: ident $(, $val: expr )*) => { concat!(
+"# Examples
+
+```no_run
+# fn main() -> controlgroup::Result<()> {
+use std::path::PathBuf;
+use controlgroup::v2;
+
+let mut cgroup = v2::Cgroup::new(PathBuf::from(\"students/charlie\"));
+
+cgroup.", stringify!($setter), "(", stringify!($( $val ),* ), ")?;
+# Ok(())
+# }
+```") };
+}
+
+macro_rules! gen_getter {
+    (
+        $file: expr,
+        $desc: literal $( : $detail: literal )?,
+        $getter: ident $( : $res: ident . $field: ident )?,
+        $ty: ty,
+        $parser: path
+    ) => { with_doc! { concat!(
+        gen_doc!(reads; $file, $desc $( : $detail )?),
+        gen_doc!(see $(; $res . $field )?),
+        gen_doc!(err_read; $file),
+        gen_doc!(eg_read; $getter)),
+        pub fn $getter(&self) -> Result<$ty> {
+            self.read_file($file, $parser)
+        }
+    } };
+}
+
+macro_rules! gen_setter {
+    (
+        $file: expr,
+        $desc: literal $( : $detail: literal )?,
+        $setter: ident $( : $res: ident . $field: ident )?,
+        $arg: ident : $ty: ty $( as $as: ty )?,
+        $( $val: expr ),*
+    ) => { with_doc! { concat!(
+        gen_doc!(sets; $file, $desc $( : $detail )?),
+        gen_doc!(see $(; $res . $field )?),
+        gen_doc!(err_write; $file),
+        gen_doc!(eg_write; $setter, $( $val ),*)),
+        pub fn $setter(&mut self, $arg: $ty) -> Result<()> {
+            self.write_file($file, $arg $( as $as )?)
+        }
+    } };
+}
+
+#[cfg(test)]
+macro_rules! gen_v2_cgroup {
+    () => {
+        crate::v2::Cgroup::with_root(
+            std::path::PathBuf::from(crate::v2::tests::TEST_ROOT),
+            gen_cgroup_name!(),
+        )
+    };
+}
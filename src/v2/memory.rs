@@ -0,0 +1,275 @@
+//! Operations on a memory controller in a v2 hierarchy.
+//!
+//! The operations are implemented as methods of [`Cgroup`], prefixed with `memory_`.
+//!
+//! For more information about this controller, see the kernel's documentation
+//! [Documentation/admin-guide/cgroup-v2.rst].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # fn main() -> controlgroup::Result<()> {
+//! use std::path::PathBuf;
+//! use controlgroup::v2::{Cgroup, Max64};
+//!
+//! let mut cgroup = Cgroup::new(PathBuf::from("students/charlie"));
+//! cgroup.create()?;
+//!
+//! // Throttle memory usage of this cgroup over 3 GiB, and kill it over 4 GiB.
+//! cgroup.set_memory_high(Max64::Limit(3 * (1 << 30)))?;
+//! cgroup.set_memory_max(Max64::Limit(4 * (1 << 30)))?;
+//!
+//! // Do something ...
+//!
+//! println!("{} bytes used", cgroup.memory_current()?);
+//! println!("{:?}", cgroup.memory_events()?);
+//!
+//! cgroup.delete()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Cgroup`]: ../struct.Cgroup.html
+//!
+//! [Documentation/admin-guide/cgroup-v2.rst]: https://www.kernel.org/doc/Documentation/admin-guide/cgroup-v2.rst
+
+use std::collections::HashMap;
+
+use crate::{
+    parse::parse,
+    v2::{parse_flat_keyed, take_key, Cgroup, Max64},
+    Result,
+};
+
+/// Resource limit on what amount of memory a cgroup can use.
+///
+/// See the kernel's documentation for more information about the fields.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Resources {
+    /// Hard memory protection in bytes. Memory usage of this cgroup within this value will not be
+    /// reclaimed under any conditions.
+    pub min: Option<Max64>,
+    /// Best-effort memory protection in bytes.
+    pub low: Option<Max64>,
+    /// Memory usage throttle limit in bytes.
+    pub high: Option<Max64>,
+    /// Memory usage hard limit in bytes. If this cgroup exceeds the limit, the OOM killer is
+    /// invoked.
+    pub max: Option<Max64>,
+    /// Swap usage hard limit in bytes.
+    pub swap_max: Option<Max64>,
+}
+
+/// Events of memory usage of a cgroup, as in `memory.events` file.
+///
+/// Each field is the number of times the event has occurred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Events {
+    /// Memory is reclaimed despite usage under the low boundary.
+    pub low: u64,
+    /// Processes are throttled because memory usage exceeded the high boundary.
+    pub high: u64,
+    /// Memory usage was about to go over the max boundary.
+    pub max: u64,
+    /// Memory allocation failed because memory usage reached the limit.
+    pub oom: u64,
+    /// Processes were killed by the OOM killer.
+    pub oom_kill: u64,
+}
+
+impl Cgroup {
+    pub(crate) fn apply_memory(&mut self, resources: &Resources) -> Result<()> {
+        macro_rules! a {
+            ($field: ident, $setter: ident) => {
+                if let Some(r) = resources.$field {
+                    self.$setter(r)?;
+                }
+            };
+        }
+
+        a!(min, set_memory_min);
+        a!(low, set_memory_low);
+        a!(high, set_memory_high);
+        a!(max, set_memory_max);
+        a!(swap_max, set_memory_swap_max);
+
+        Ok(())
+    }
+
+    gen_getter!(
+        "memory.current",
+        "the total amount of memory currently used by this cgroup and its descendants,"
+            : "in bytes.",
+        memory_current,
+        u64,
+        parse
+    );
+
+    gen_getter!(
+        "memory.min",
+        "the hard memory protection of this cgroup",
+        memory_min: memory.min,
+        Max64,
+        parse
+    );
+
+    gen_setter!(
+        "memory.min",
+        "a hard memory protection of this cgroup",
+        set_memory_min: memory.min,
+        min: Max64,
+        v2::Max64::Limit(1 << 30)
+    );
+
+    gen_getter!(
+        "memory.low",
+        "the best-effort memory protection of this cgroup",
+        memory_low: memory.low,
+        Max64,
+        parse
+    );
+
+    gen_setter!(
+        "memory.low",
+        "a best-effort memory protection of this cgroup",
+        set_memory_low: memory.low,
+        low: Max64,
+        v2::Max64::Limit(1 << 30)
+    );
+
+    gen_getter!(
+        "memory.high",
+        "the memory usage throttle limit of this cgroup",
+        memory_high: memory.high,
+        Max64,
+        parse
+    );
+
+    gen_setter!(
+        "memory.high",
+        "a memory usage throttle limit of this cgroup",
+        set_memory_high: memory.high,
+        high: Max64,
+        v2::Max64::Limit(3 * (1 << 30))
+    );
+
+    gen_getter!(
+        "memory.max",
+        "the memory usage hard limit of this cgroup",
+        memory_max: memory.max,
+        Max64,
+        parse
+    );
+
+    gen_setter!(
+        "memory.max",
+        "a memory usage hard limit of this cgroup",
+        set_memory_max: memory.max,
+        max: Max64,
+        v2::Max64::Limit(4 * (1 << 30))
+    );
+
+    gen_getter!(
+        "memory.events",
+        "the events of memory usage of this cgroup",
+        memory_events,
+        Events,
+        parse_events
+    );
+
+    gen_getter!(
+        "memory.stat",
+        "the statistics of memory usage of this cgroup,"
+            : "as a map from the key to the value in bytes or counts.",
+        memory_stat,
+        HashMap<String, u64>,
+        parse_flat_keyed
+    );
+
+    gen_getter!(
+        "memory.swap.current",
+        "the total amount of swap currently used by this cgroup and its descendants,"
+            : "in bytes.",
+        memory_swap_current,
+        u64,
+        parse
+    );
+
+    gen_getter!(
+        "memory.swap.max",
+        "the swap usage hard limit of this cgroup",
+        memory_swap_max: memory.swap_max,
+        Max64,
+        parse
+    );
+
+    gen_setter!(
+        "memory.swap.max",
+        "a swap usage hard limit of this cgroup",
+        set_memory_swap_max: memory.swap_max,
+        max: Max64,
+        v2::Max64::Limit(1 << 30)
+    );
+}
+
+impl From<Resources> for super::Resources {
+    fn from(resources: Resources) -> Self {
+        Self {
+            memory: resources,
+            ..Self::default()
+        }
+    }
+}
+
+fn parse_events(reader: impl std::io::Read) -> Result<Events> {
+    let mut map = parse_flat_keyed(reader)?;
+
+    Ok(Events {
+        low: take_key(&mut map, "low")?,
+        high: take_key(&mut map, "high")?,
+        max: take_key(&mut map, "max")?,
+        oom: take_key(&mut map, "oom")?,
+        oom_kill: take_key(&mut map, "oom_kill")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+
+    #[test]
+    fn test_parse_events() -> Result<()> {
+        const CONTENT_OK: &str = "\
+low 0
+high 1
+max 2
+oom 3
+oom_kill 4
+oom_group_kill 0
+";
+        assert_eq!(
+            parse_events(CONTENT_OK.as_bytes())?,
+            Events {
+                low: 0,
+                high: 1,
+                max: 2,
+                oom: 3,
+                oom_kill: 4,
+            }
+        );
+
+        const CONTENT_NG_MISSING: &str = "low 0\nhigh 1\nmax 2\noom 3\n";
+        assert_eq!(
+            parse_events(CONTENT_NG_MISSING.as_bytes())
+                .unwrap_err()
+                .kind(),
+            ErrorKind::Parse
+        );
+
+        Ok(())
+    }
+}
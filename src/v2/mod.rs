@@ -0,0 +1,1012 @@
+//! Operations on cgroups in a v2 (unified) hierarchy.
+//!
+//! In cgroup v2, a system has only a single unified hierarchy, and controllers are enabled for
+//! each cgroup via `cgroup.subtree_control` file of its parent. [`Cgroup`] is a handler of a cgroup
+//! in the unified hierarchy. It implements the operations on the core interface files
+//! (`cgroup.procs`, `cgroup.subtree_control`, etc.), and the operations for each controller are
+//! implemented in each module. Currently this crate supports [CPU], [cpuset], [memory], [io],
+//! [hugetlb], [RDMA], and [pids] controllers.
+//!
+//! For more information about cgroup v2, see the kernel's documentation
+//! [Documentation/admin-guide/cgroup-v2.rst].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # fn main() -> controlgroup::Result<()> {
+//! use std::path::PathBuf;
+//! use controlgroup::{Pid, v2::{self, cpu, memory, Cgroup, ControllerKind, Max64}};
+//!
+//! // Enable the CPU and memory controllers for the children of the root cgroup.
+//! let mut root = Cgroup::new(PathBuf::new());
+//! root.enable_subtree_control(&[ControllerKind::Cpu, ControllerKind::Memory])?;
+//!
+//! // Define and create a new cgroup.
+//! let mut cgroup = Cgroup::new(PathBuf::from("students-charlie"));
+//! cgroup.create()?;
+//!
+//! // Define resource limits and constraints for this cgroup.
+//! let resources = v2::Resources {
+//!     cpu: cpu::Resources {
+//!         weight: Some(200),
+//!         ..cpu::Resources::default()
+//!     },
+//!     memory: memory::Resources {
+//!         max: Some(Max64::Limit(4 * (1 << 30))),
+//!         ..memory::Resources::default()
+//!     },
+//!     ..v2::Resources::default()
+//! };
+//!
+//! // Apply the resource limits.
+//! cgroup.apply(&resources)?;
+//!
+//! // Attach the self process to the cgroup.
+//! let pid = Pid::from(std::process::id());
+//! cgroup.add_proc(pid)?;
+//!
+//! // Do something ...
+//!
+//! println!("{:?}", cgroup.cpu_stat()?);
+//!
+//! // Now, move self process back to the root cgroup ...
+//! cgroup.remove_proc(pid)?;
+//!
+//! // ... and delete the cgroup.
+//! cgroup.delete()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Cgroup`]: struct.Cgroup.html
+//! [CPU]: cpu/index.html
+//! [cpuset]: cpuset/index.html
+//! [memory]: memory/index.html
+//! [io]: io/index.html
+//! [hugetlb]: hugetlb/index.html
+//! [RDMA]: rdma/index.html
+//! [pids]: pids/index.html
+//!
+//! [Documentation/admin-guide/cgroup-v2.rst]: https://www.kernel.org/doc/Documentation/admin-guide/cgroup-v2.rst
+
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::{self, File},
+    io::{BufRead, BufReader, Read},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use crate::{
+    file,
+    parse::{parse, parse_01_bool, parse_next},
+    v1::backend::Sysfs,
+    Error, ErrorKind, Max, Pid, Result,
+};
+
+#[macro_use]
+mod macros;
+
+pub mod cpu;
+pub mod cpuset;
+pub mod hugetlb;
+pub mod io;
+pub mod memory;
+pub mod pids;
+pub mod rdma;
+
+pub use crate::v1::{cpuset::IdSet, hugetlb::HugepageSize, rdma::Limit as RdmaLimit};
+
+const CGROUPFS_MOUNT_POINT: &str = "/sys/fs/cgroup";
+
+/// Handler of a cgroup in a v2 (unified) hierarchy.
+///
+/// A `Cgroup` is defined by the mount point of the unified hierarchy and a cgroup name. Note that
+/// defining a `Cgroup` does not create a new cgroup. [`create`] method creates the new directory
+/// for the defined cgroup.
+///
+/// Also note that `Cgroup` does not implement `Drop`, and therefore when the handler is dropped,
+/// the cgroup will stay around.
+///
+/// [`create`]: #method.create
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cgroup {
+    root: PathBuf,         // e.g. /sys/fs/cgroup
+    name: Option<PathBuf>, // e.g. students/charlie
+}
+
+/// Kinds of controllers in a v2 hierarchy.
+///
+/// `ControllerKind` implements [`FromStr`] and [`Display`]. The string representation is the
+/// controller name as listed in `cgroup.controllers` file (e.g. `ControllerKind::Cpu` => `cpu`).
+/// Controllers not listed here (e.g. `dmem`) are skipped when reading `cgroup.controllers` and
+/// `cgroup.subtree_control` files.
+///
+/// ```
+/// use controlgroup::v2::ControllerKind;
+///
+/// assert_eq!("memory".parse::<ControllerKind>().unwrap(), ControllerKind::Memory);
+/// assert_eq!(ControllerKind::HugeTlb.to_string(), "hugetlb");
+/// ```
+///
+/// [`FromStr`]: https://doc.rust-lang.org/std/str/trait.FromStr.html
+/// [`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ControllerKind {
+    /// CPU controller.
+    Cpu,
+    /// Cpuset controller.
+    Cpuset,
+    /// Memory controller.
+    Memory,
+    /// IO controller.
+    Io,
+    /// HugeTLB controller.
+    HugeTlb,
+    /// RDMA controller.
+    Rdma,
+    /// Pids controller.
+    Pids,
+    /// perf_event controller.
+    PerfEvent,
+    /// Misc controller.
+    Misc,
+}
+
+/// Type of a cgroup, as in `cgroup.type` file.
+///
+/// `CgroupType` implements [`FromStr`] and [`Display`].
+///
+/// ```
+/// use controlgroup::v2::CgroupType;
+///
+/// assert_eq!("domain threaded".parse::<CgroupType>().unwrap(), CgroupType::DomainThreaded);
+/// assert_eq!(CgroupType::Threaded.to_string(), "threaded");
+/// ```
+///
+/// [`FromStr`]: https://doc.rust-lang.org/std/str/trait.FromStr.html
+/// [`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CgroupType {
+    /// A normal valid domain cgroup.
+    Domain,
+    /// A threaded domain cgroup which is serving as the root of a threaded subtree.
+    DomainThreaded,
+    /// A cgroup which is in an invalid state. It can't be populated or have controllers enabled.
+    DomainInvalid,
+    /// A threaded cgroup which is a member of a threaded subtree.
+    Threaded,
+}
+
+/// Events of a cgroup, as in `cgroup.events` file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Events {
+    /// Whether this cgroup or its descendants contain any live processes.
+    pub populated: bool,
+    /// Whether this cgroup is frozen. Only present on kernels supporting `cgroup.freeze` file.
+    pub frozen: Option<bool>,
+}
+
+/// Limits the maximum amount of a resource, or not limits.
+///
+/// This is the 64-bit counterpart of [`Max`], used for limits in bytes or bytes/s.
+///
+/// `Max64` implements [`FromStr`] and [`Display`]. You can convert a string into a `Max64` and
+/// vice versa. [`parse`] returns an error with kind [`ErrorKind::Parse`] if failed.
+///
+/// ```
+/// use controlgroup::v2::Max64;
+///
+/// assert_eq!("max".parse::<Max64>().unwrap(), Max64::Max);
+/// assert_eq!("4294967296".parse::<Max64>().unwrap(), Max64::Limit(1 << 32));
+///
+/// assert_eq!(Max64::Max.to_string(), "max");
+/// assert_eq!(Max64::Limit(42).to_string(), "42");
+/// ```
+///
+/// [`Max`]: ../enum.Max.html
+/// [`FromStr`]: https://doc.rust-lang.org/std/str/trait.FromStr.html
+/// [`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+/// [`parse`]: https://doc.rust-lang.org/std/primitive.str.html#method.parse
+/// [`ErrorKind::Parse`]: ../enum.ErrorKind.html#variant.Parse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Max64 {
+    /// Not limit the maximum amount of a resource.
+    Max,
+    /// Limits the maximum amount of a resource to this value.
+    Limit(u64),
+}
+
+/// Compound of resource limits and constraints for all controllers.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Resources {
+    /// Resource limit on how much CPU time this cgroup can use.
+    pub cpu: cpu::Resources,
+    /// Resource limit on which CPUs and memory nodes this cgroup can use.
+    pub cpuset: cpuset::Resources,
+    /// Resource limit on what amount of memory this cgroup can use.
+    pub memory: memory::Resources,
+    /// Resource limit on how much block I/O this cgroup can perform.
+    pub io: io::Resources,
+    /// Resource limit on how many hugepage TLBs this cgroup can use.
+    pub hugetlb: hugetlb::Resources,
+    /// Resource limit on how much this cgroup can use RDMA/IB devices.
+    pub rdma: rdma::Resources,
+    /// Resource limit on how many processes this cgroup can have.
+    pub pids: pids::Resources,
+}
+
+impl Cgroup {
+    /// Defines a new cgroup with a name, in the unified hierarchy mounted at `/sys/fs/cgroup`.
+    ///
+    /// If the name is empty, the resulting cgroup is the root cgroup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use controlgroup::v2::Cgroup;
+    ///
+    /// let cgroup = Cgroup::new(PathBuf::from("students/charlie"));
+    /// assert_eq!(cgroup.path(), PathBuf::from("/sys/fs/cgroup/students/charlie"));
+    /// ```
+    pub fn new(name: PathBuf) -> Self {
+        Self::with_root(PathBuf::from(CGROUPFS_MOUNT_POINT), name)
+    }
+
+    /// Defines a new cgroup with a name, in the unified hierarchy mounted at `root`.
+    ///
+    /// This is useful on hosts which mount the unified hierarchy on a non-standard location (e.g.
+    /// `/sys/fs/cgroup/unified` in the "hybrid" mode of systemd).
+    ///
+    /// If the name is empty, the resulting cgroup is the root cgroup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use controlgroup::v2::Cgroup;
+    ///
+    /// let cgroup = Cgroup::with_root(
+    ///     PathBuf::from("/sys/fs/cgroup/unified"), PathBuf::from("students/charlie"));
+    /// assert_eq!(cgroup.path(), PathBuf::from("/sys/fs/cgroup/unified/students/charlie"));
+    /// ```
+    pub fn with_root(root: PathBuf, name: PathBuf) -> Self {
+        Self {
+            root,
+            name: if name.as_os_str().is_empty() {
+                None
+            } else {
+                Some(name)
+            },
+        }
+    }
+
+    /// Returns the absolute path to this cgroup.
+    pub fn path(&self) -> PathBuf {
+        if let Some(ref name) = self.name {
+            self.root.join(name)
+        } else {
+            self.root.clone()
+        }
+    }
+
+    /// Returns whether this cgroup is the root cgroup of the unified hierarchy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use controlgroup::v2::Cgroup;
+    ///
+    /// assert!(Cgroup::new(PathBuf::new()).is_root());
+    /// assert!(!Cgroup::new(PathBuf::from("students/charlie")).is_root());
+    /// ```
+    pub fn is_root(&self) -> bool {
+        self.name.is_none()
+    }
+
+    /// Returns the definition of the root cgroup of the unified hierarchy.
+    pub fn root_cgroup(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            name: None,
+        }
+    }
+
+    /// Creates a new directory for this cgroup.
+    ///
+    /// Note that this method does not create directories recursively; If a parent of the path does
+    /// not exist, an error will be returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to create the directory, with kind [`ErrorKind::Io`].
+    ///
+    /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
+    pub fn create(&mut self) -> Result<()> {
+        fs::create_dir(self.path()).map_err(Into::into)
+    }
+
+    /// Applies a set of resource limits and constraints to this cgroup.
+    ///
+    /// Only the `Some` fields and non-empty maps are applied. Controllers must be enabled for this
+    /// cgroup via `cgroup.subtree_control` file of its parent before applying their resources.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to apply the resource configuration.
+    pub fn apply(&mut self, resources: &Resources) -> Result<()> {
+        self.apply_cpu(&resources.cpu)?;
+        self.apply_cpuset(&resources.cpuset)?;
+        self.apply_memory(&resources.memory)?;
+        self.apply_io(&resources.io)?;
+        self.apply_hugetlb(&resources.hugetlb)?;
+        self.apply_rdma(&resources.rdma)?;
+        self.apply_pids(&resources.pids)
+    }
+
+    /// Deletes the directory of this cgroup.
+    ///
+    /// Deleting the directory will fail if this cgroup has a live process or a child cgroup.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to delete the directory, with kind [`ErrorKind::Io`].
+    ///
+    /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
+    pub fn delete(&mut self) -> Result<()> {
+        fs::remove_dir(self.path()).map_err(Into::into)
+    }
+
+    gen_getter!(
+        "cgroup.controllers",
+        "the list of known controllers available to this cgroup",
+        controllers,
+        Vec<ControllerKind>,
+        parse_controllers
+    );
+
+    gen_getter!(
+        "cgroup.subtree_control",
+        "the list of known controllers enabled for the children of this cgroup",
+        subtree_control,
+        Vec<ControllerKind>,
+        parse_controllers
+    );
+
+    with_doc! { concat!(
+        "Enables controllers for the children of this cgroup, ",
+        "by writing to `cgroup.subtree_control` file.\n\n",
+        gen_doc!(see),
+        gen_doc!(err_write; "cgroup.subtree_control"),
+        gen_doc!(eg_write; enable_subtree_control, &[v2::ControllerKind::Cpu])),
+        pub fn enable_subtree_control(&mut self, controllers: &[ControllerKind]) -> Result<()> {
+            self.write_subtree_control('+', controllers)
+        }
+    }
+
+    with_doc! { concat!(
+        "Disables controllers for the children of this cgroup, ",
+        "by writing to `cgroup.subtree_control` file.\n\n",
+        gen_doc!(see),
+        gen_doc!(err_write; "cgroup.subtree_control"),
+        gen_doc!(eg_write; disable_subtree_control, &[v2::ControllerKind::Cpu])),
+        pub fn disable_subtree_control(&mut self, controllers: &[ControllerKind]) -> Result<()> {
+            self.write_subtree_control('-', controllers)
+        }
+    }
+
+    gen_getter!(
+        "cgroup.procs",
+        "a list of processes attached to this cgroup,"
+            : "The resulting processes are represented by their PIDs.",
+        procs,
+        Vec<Pid>,
+        parse_pids
+    );
+
+    with_doc! { concat!(
+        "Attaches a process to this cgroup, with all threads in the same thread group at once,
+         by writing a PID to `cgroup.procs` file.\n\n",
+        gen_doc!(see),
+        gen_doc!(err_write; "cgroup.procs"),
+        gen_doc!(eg_write; add_proc, std::process::id())),
+        pub fn add_proc(&mut self, pid: impl Into<Pid>) -> Result<()> {
+            self.write_file("cgroup.procs", pid.into())
+        }
+    }
+
+    /// Removes a process from this cgroup, by moving it to the root cgroup.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to write to `cgroup.procs` file of the root cgroup.
+    pub fn remove_proc(&mut self, pid: impl Into<Pid>) -> Result<()> {
+        self.root_cgroup().add_proc(pid)
+    }
+
+    gen_getter!(
+        "cgroup.threads",
+        "a list of threads attached to this cgroup,"
+            : "The resulting threads are represented by their thread IDs.",
+        threads,
+        Vec<Pid>,
+        parse_pids
+    );
+
+    with_doc! { concat!(
+        "Attaches a thread to this cgroup by writing a thread ID to `cgroup.threads` file.\n\n",
+        gen_doc!(see),
+        gen_doc!(err_write; "cgroup.threads"),
+        gen_doc!(eg_write; add_thread, std::process::id())),
+        pub fn add_thread(&mut self, tid: impl Into<Pid>) -> Result<()> {
+            self.write_file("cgroup.threads", tid.into())
+        }
+    }
+
+    /// Removes a thread from this cgroup, by moving it to the root cgroup.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to write to `cgroup.threads` file of the root cgroup.
+    pub fn remove_thread(&mut self, tid: impl Into<Pid>) -> Result<()> {
+        self.root_cgroup().add_thread(tid)
+    }
+
+    gen_getter!(
+        "cgroup.type",
+        "the type of this cgroup",
+        cgroup_type,
+        CgroupType,
+        parse
+    );
+
+    with_doc! { concat!(
+        "Turns this cgroup into a threaded cgroup by writing `threaded` to `cgroup.type` file.\n\n",
+        gen_doc!(see),
+        gen_doc!(err_write; "cgroup.type"),
+        gen_doc!(eg_write; set_threaded)),
+        pub fn set_threaded(&mut self) -> Result<()> {
+            self.write_file("cgroup.type", CgroupType::Threaded)
+        }
+    }
+
+    gen_getter!(
+        "cgroup.events",
+        "the events of this cgroup, i.e. whether it is populated and frozen",
+        events,
+        Events,
+        parse_events
+    );
+
+    gen_getter!(
+        "cgroup.max.descendants",
+        "the maximum allowed number of descendant cgroups",
+        max_descendants,
+        Max,
+        parse
+    );
+
+    gen_setter!(
+        "cgroup.max.descendants",
+        "a maximum allowed number of descendant cgroups",
+        set_max_descendants,
+        max: Max,
+        controlgroup::Max::Limit(4)
+    );
+
+    gen_getter!(
+        "cgroup.max.depth",
+        "the maximum allowed descent depth below this cgroup",
+        max_depth,
+        Max,
+        parse
+    );
+
+    gen_setter!(
+        "cgroup.max.depth",
+        "a maximum allowed descent depth below this cgroup",
+        set_max_depth,
+        max: Max,
+        controlgroup::Max::Limit(2)
+    );
+
+    with_doc! { concat!(
+        "Freezes processes in this cgroup and its descendants by writing to `cgroup.freeze` file.\n\n",
+        gen_doc!(see),
+        gen_doc!(err_write; "cgroup.freeze"),
+        gen_doc!(eg_write; freeze)),
+        pub fn freeze(&mut self) -> Result<()> {
+            self.write_file("cgroup.freeze", 1)
+        }
+    }
+
+    with_doc! { concat!(
+        "Thaws, i.e. un-freezes, processes in this cgroup by writing to `cgroup.freeze` file.\n\n",
+        gen_doc!(see),
+        gen_doc!(err_write; "cgroup.freeze"),
+        gen_doc!(eg_write; thaw)),
+        pub fn thaw(&mut self) -> Result<()> {
+            self.write_file("cgroup.freeze", 0)
+        }
+    }
+
+    /// Returns whether a file with the given name exists in this cgroup.
+    pub fn file_exists(&self, name: &str) -> bool {
+        self.path().join(name).exists()
+    }
+
+    /// Low-level API that opens a file with read access.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to open the file, with kind [`ErrorKind::Io`].
+    ///
+    /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
+    pub fn open_file_read(&self, name: &str) -> Result<File> {
+        file::open(
+            &Sysfs,
+            self.path().join(name),
+            fs::OpenOptions::new().read(true),
+        )
+    }
+
+    /// Low-level API that opens a file with write access.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if failed to open the file, with kind [`ErrorKind::Io`].
+    ///
+    /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
+    pub fn open_file_write(&mut self, name: &str) -> Result<File> {
+        file::open(
+            &Sysfs,
+            self.path().join(name),
+            fs::OpenOptions::new().write(true),
+        )
+    }
+
+    fn read_file<T>(&self, name: &str, parser: impl FnOnce(File) -> Result<T>) -> Result<T> {
+        file::read(&Sysfs, self.path().join(name), parser)
+    }
+
+    fn write_file(&mut self, name: &str, val: impl fmt::Display) -> Result<()> {
+        file::write(&Sysfs, self.path().join(name), val.to_string())
+    }
+
+    fn write_subtree_control(&mut self, sign: char, controllers: &[ControllerKind]) -> Result<()> {
+        let content = controllers
+            .iter()
+            .map(|c| format!("{}{}", sign, c))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        self.write_file("cgroup.subtree_control", content)
+    }
+}
+
+impl ControllerKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Cpu => "cpu",
+            Self::Cpuset => "cpuset",
+            Self::Memory => "memory",
+            Self::Io => "io",
+            Self::HugeTlb => "hugetlb",
+            Self::Rdma => "rdma",
+            Self::Pids => "pids",
+            Self::PerfEvent => "perf_event",
+            Self::Misc => "misc",
+        }
+    }
+}
+
+impl FromStr for ControllerKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "cpu" => Ok(Self::Cpu),
+            "cpuset" => Ok(Self::Cpuset),
+            "memory" => Ok(Self::Memory),
+            "io" => Ok(Self::Io),
+            "hugetlb" => Ok(Self::HugeTlb),
+            "rdma" => Ok(Self::Rdma),
+            "pids" => Ok(Self::Pids),
+            "perf_event" => Ok(Self::PerfEvent),
+            "misc" => Ok(Self::Misc),
+            _ => {
+                bail_parse!();
+            }
+        }
+    }
+}
+
+impl fmt::Display for ControllerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CgroupType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "domain" => Ok(Self::Domain),
+            "domain threaded" => Ok(Self::DomainThreaded),
+            "domain invalid" => Ok(Self::DomainInvalid),
+            "threaded" => Ok(Self::Threaded),
+            _ => {
+                bail_parse!();
+            }
+        }
+    }
+}
+
+impl fmt::Display for CgroupType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Domain => "domain",
+            Self::DomainThreaded => "domain threaded",
+            Self::DomainInvalid => "domain invalid",
+            Self::Threaded => "threaded",
+        })
+    }
+}
+
+#[allow(clippy::derivable_impls)] // `#[default]` on enum variants requires Rust 1.62
+impl Default for Max64 {
+    fn default() -> Self {
+        Self::Max
+    }
+}
+
+impl From<u64> for Max64 {
+    fn from(n: u64) -> Self {
+        Self::Limit(n)
+    }
+}
+
+impl FromStr for Max64 {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "max" => Ok(Self::Max),
+            n => Ok(Self::Limit(n.parse()?)),
+        }
+    }
+}
+
+impl fmt::Display for Max64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Max => write!(f, "max"),
+            Self::Limit(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl_serde_str!(ControllerKind, CgroupType, Max64);
+
+fn parse_controllers(mut reader: impl Read) -> Result<Vec<ControllerKind>> {
+    let mut buf = String::new();
+    reader.read_to_string(&mut buf)?;
+
+    // Skip controllers unknown to this crate (e.g. `dmem` on Linux 6.14+), so that newer kernels
+    // do not break reading the known ones.
+    Ok(buf
+        .split_whitespace()
+        .filter_map(|name| name.parse().ok())
+        .collect())
+}
+
+fn parse_pids(reader: impl Read) -> Result<Vec<Pid>> {
+    let mut pids = vec![];
+    for line in BufReader::new(reader).lines() {
+        pids.push(line?.trim().parse()?);
+    }
+
+    Ok(pids)
+}
+
+fn parse_events(reader: impl Read) -> Result<Events> {
+    let (mut populated, mut frozen) = (None, None);
+
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        let mut entry = line.split_whitespace();
+
+        match entry.next() {
+            Some("populated") => {
+                if populated.is_some() {
+                    bail_parse!();
+                }
+                populated = Some(parse_01_bool(
+                    parse_next::<String, _, _>(&mut entry)?.as_bytes(),
+                )?);
+            }
+            Some("frozen") => {
+                if frozen.is_some() {
+                    bail_parse!();
+                }
+                frozen = Some(parse_01_bool(
+                    parse_next::<String, _, _>(&mut entry)?.as_bytes(),
+                )?);
+            }
+            _ => {
+                bail_parse!();
+            }
+        }
+
+        if entry.next().is_some() {
+            bail_parse!();
+        }
+    }
+
+    match populated {
+        Some(populated) => Ok(Events { populated, frozen }),
+        None => {
+            bail_parse!();
+        }
+    }
+}
+
+// Parses a flat keyed file (e.g. `cpu.stat`), in which each line consists of a key and a value.
+fn parse_flat_keyed(reader: impl Read) -> Result<HashMap<String, u64>> {
+    let mut map = HashMap::new();
+
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        let mut entry = line.split_whitespace();
+
+        let key = entry.next().ok_or_else(|| Error::new(ErrorKind::Parse))?;
+        let val = parse_next(&mut entry)?;
+
+        if entry.next().is_some() || map.insert(key.to_string(), val).is_some() {
+            bail_parse!();
+        }
+    }
+
+    Ok(map)
+}
+
+// Parses a nested keyed file (e.g. `io.stat`), in which each line consists of a key and a list
+// of `sub-key=value` pairs.
+fn parse_nested_keyed<K, V>(reader: impl Read) -> Result<HashMap<K, HashMap<String, V>>>
+where
+    K: FromStr + std::hash::Hash + Eq,
+    <K as FromStr>::Err: std::error::Error + Sync + Send + 'static,
+    V: FromStr,
+    <V as FromStr>::Err: std::error::Error + Sync + Send + 'static,
+{
+    let mut map = HashMap::new();
+
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        let mut entry = line.split_whitespace();
+
+        let key = parse_next(&mut entry)?;
+        let mut values = HashMap::new();
+        for kv in entry {
+            let mut kv = kv.split('=');
+            let sub_key = kv.next().ok_or_else(|| Error::new(ErrorKind::Parse))?;
+            let val = parse_next(&mut kv)?;
+
+            if kv.next().is_some() || values.insert(sub_key.to_string(), val).is_some() {
+                bail_parse!();
+            }
+        }
+
+        if map.insert(key, values).is_some() {
+            bail_parse!();
+        }
+    }
+
+    Ok(map)
+}
+
+fn take_key<V>(map: &mut HashMap<String, V>, key: &str) -> Result<V> {
+    map.remove(key).ok_or_else(|| Error::new(ErrorKind::Parse))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Unified hierarchy mount point on hosts in the "hybrid" mode of systemd, on which tests are
+    // executed.
+    pub(crate) const TEST_ROOT: &str = "/sys/fs/cgroup/unified";
+
+    #[test]
+    fn test_cgroup_path() {
+        let cgroup = Cgroup::new(PathBuf::from("students/charlie"));
+        assert_eq!(
+            cgroup.path(),
+            PathBuf::from("/sys/fs/cgroup/students/charlie")
+        );
+        assert!(!cgroup.is_root());
+
+        let root = cgroup.root_cgroup();
+        assert!(root.is_root());
+        assert_eq!(root.path(), PathBuf::from("/sys/fs/cgroup"));
+
+        let cgroup = Cgroup::with_root(PathBuf::from("/tmp/cgroup2"), PathBuf::new());
+        assert!(cgroup.is_root());
+        assert_eq!(cgroup.path(), PathBuf::from("/tmp/cgroup2"));
+    }
+
+    #[test]
+    fn test_cgroup_create_delete() -> Result<()> {
+        let mut cgroup = gen_v2_cgroup!();
+        assert!(!cgroup.path().exists());
+
+        cgroup.create()?;
+        assert!(cgroup.path().exists());
+        assert!([
+            "cgroup.procs",
+            "cgroup.threads",
+            "cgroup.type",
+            "cgroup.events"
+        ]
+        .iter()
+        .all(|f| cgroup.file_exists(f)));
+
+        cgroup.delete()?;
+        assert!(!cgroup.path().exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cgroup_type_events() -> Result<()> {
+        let mut cgroup = gen_v2_cgroup!();
+        cgroup.create()?;
+
+        assert_eq!(cgroup.cgroup_type()?, CgroupType::Domain);
+        assert!(!cgroup.events()?.populated);
+        assert!(cgroup.procs()?.is_empty());
+
+        cgroup.delete()
+    }
+
+    #[test]
+    fn test_cgroup_max_descendants_depth() -> Result<()> {
+        let mut cgroup = gen_v2_cgroup!();
+        cgroup.create()?;
+
+        assert_eq!(cgroup.max_descendants()?, Max::Max);
+        cgroup.set_max_descendants(Max::Limit(4))?;
+        assert_eq!(cgroup.max_descendants()?, Max::Limit(4));
+
+        assert_eq!(cgroup.max_depth()?, Max::Max);
+        cgroup.set_max_depth(Max::Limit(2))?;
+        assert_eq!(cgroup.max_depth()?, Max::Limit(2));
+
+        cgroup.delete()
+    }
+
+    #[test]
+    fn test_parse_controllers() -> Result<()> {
+        use ControllerKind::*;
+
+        assert_eq!(
+            parse_controllers("cpuset cpu io memory hugetlb pids rdma misc\n".as_bytes())?,
+            vec![Cpuset, Cpu, Io, Memory, HugeTlb, Pids, Rdma, Misc]
+        );
+        assert!(parse_controllers("\n".as_bytes())?.is_empty());
+
+        // unknown controllers are skipped
+        assert_eq!(
+            parse_controllers("cpuset cpu io memory dmem pids\n".as_bytes())?,
+            vec![Cpuset, Cpu, Io, Memory, Pids]
+        );
+        assert_eq!(parse_controllers("invalid".as_bytes())?, vec![]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_events() -> Result<()> {
+        assert_eq!(
+            parse_events("populated 1\nfrozen 0\n".as_bytes())?,
+            Events {
+                populated: true,
+                frozen: Some(false)
+            }
+        );
+        assert_eq!(
+            parse_events("populated 0\n".as_bytes())?,
+            Events {
+                populated: false,
+                frozen: None
+            }
+        );
+
+        for case in &[
+            "",
+            "frozen 0\n",
+            "populated 2\n",
+            "populated 1 0\n",
+            "invalid 0\n",
+        ] {
+            assert_eq!(
+                parse_events(case.as_bytes()).unwrap_err().kind(),
+                ErrorKind::Parse
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_flat_keyed() -> Result<()> {
+        assert_eq!(
+            parse_flat_keyed("usage_usec 42\nuser_usec 40\n".as_bytes())?,
+            hashmap! { ("usage_usec".to_string(), 42), ("user_usec".to_string(), 40) }
+        );
+
+        for case in &[
+            "usage_usec\n",
+            "usage_usec 1 2\n",
+            "a 1\na 2\n",
+            "a invalid\n",
+        ] {
+            assert_eq!(
+                parse_flat_keyed(case.as_bytes()).unwrap_err().kind(),
+                ErrorKind::Parse
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_nested_keyed() -> Result<()> {
+        let map = parse_nested_keyed::<crate::Device, u64>(
+            "8:0 rbytes=1 wbytes=2\n8:16 rbytes=3 wbytes=4\n".as_bytes(),
+        )?;
+        assert_eq!(
+            map,
+            hashmap! {
+                ([8, 0].into(), hashmap! { ("rbytes".to_string(), 1), ("wbytes".to_string(), 2) }),
+                ([8, 16].into(), hashmap! { ("rbytes".to_string(), 3), ("wbytes".to_string(), 4) }),
+            }
+        );
+
+        for case in &[
+            "8:0 rbytes\n",
+            "8:0 rbytes=1=2\n",
+            "8:0 a=1 a=2\n",
+            "invalid a=1\n",
+        ] {
+            assert_eq!(
+                parse_nested_keyed::<crate::Device, u64>(case.as_bytes())
+                    .unwrap_err()
+                    .kind(),
+                ErrorKind::Parse
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_max64() -> Result<()> {
+        assert_eq!("max".parse::<Max64>()?, Max64::Max);
+        assert_eq!("42".parse::<Max64>()?, Max64::Limit(42));
+        assert_eq!(
+            "invalid".parse::<Max64>().unwrap_err().kind(),
+            ErrorKind::Parse
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+//! Operations on a pids controller in a v2 hierarchy.
+//!
+//! The operations are implemented as methods of [`Cgroup`], prefixed with `pids_`.
+//!
+//! For more information about this controller, see the kernel's documentation
+//! [Documentation/admin-guide/cgroup-v2.rst].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # fn main() -> controlgroup::Result<()> {
+//! use std::path::PathBuf;
+//! use controlgroup::{Max, v2::Cgroup};
+//!
+//! let mut cgroup = Cgroup::new(PathBuf::from("students/charlie"));
+//! cgroup.create()?;
+//!
+//! // Limit the maximum number of processes this cgroup can have.
+//! cgroup.set_pids_max(Max::Limit(42))?;
+//!
+//! // Do something ...
+//!
+//! println!("cgroup now has {} processes", cgroup.pids_current()?);
+//! println!("cgroup has hit the limit {} times", cgroup.pids_events()?);
+//!
+//! cgroup.delete()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Cgroup`]: ../struct.Cgroup.html
+//!
+//! [Documentation/admin-guide/cgroup-v2.rst]: https://www.kernel.org/doc/Documentation/admin-guide/cgroup-v2.rst
+
+use crate::{
+    parse::parse,
+    v2::{parse_flat_keyed, take_key, Cgroup},
+    Max, Result,
+};
+
+/// Resource limit on how many processes a cgroup can have.
+///
+/// See the kernel's documentation for more information about the fields.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Resources {
+    /// If [`Max::Max`], the system does not limit the number of processes this cgroup can have. If
+    /// [`Max::Limit(n)`], this cgroup can have `n` processes at most.
+    ///
+    /// [`Max::Max`]: ../../enum.Max.html#variant.Max
+    /// [`Max::Limit(n)`]: ../../enum.Max.html#variant.Limit
+    pub max: Option<Max>,
+}
+
+impl Cgroup {
+    pub(crate) fn apply_pids(&mut self, resources: &Resources) -> Result<()> {
+        if let Some(max) = resources.max {
+            self.set_pids_max(max)?;
+        }
+
+        Ok(())
+    }
+
+    gen_getter!(
+        "pids.max",
+        "the maximum number of processes this cgroup can have",
+        pids_max: pids.max,
+        Max,
+        parse
+    );
+
+    gen_setter!(
+        "pids.max",
+        "a maximum number of processes this cgroup can have",
+        set_pids_max: pids.max,
+        max: Max,
+        controlgroup::Max::Limit(2)
+    );
+
+    gen_getter!(
+        "pids.current",
+        "the number of processes this cgroup and its descendants currently have",
+        pids_current,
+        u32,
+        parse
+    );
+
+    gen_getter!(
+        "pids.events",
+        "the number of times fork failed due to the limit",
+        pids_events,
+        u64,
+        parse_events
+    );
+}
+
+impl From<Resources> for super::Resources {
+    fn from(resources: Resources) -> Self {
+        Self {
+            pids: resources,
+            ..Self::default()
+        }
+    }
+}
+
+fn parse_events(reader: impl std::io::Read) -> Result<u64> {
+    take_key(&mut parse_flat_keyed(reader)?, "max")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+
+    #[test]
+    fn test_parse_events() -> Result<()> {
+        assert_eq!(parse_events("max 7\n".as_bytes())?, 7);
+        assert_eq!(parse_events("max 0\nmax.imposed 0\n".as_bytes())?, 0);
+
+        for case in &["", "max\n", "max invalid\n", "imposed 0\n"] {
+            assert_eq!(
+                parse_events(case.as_bytes()).unwrap_err().kind(),
+                ErrorKind::Parse
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+//! Operations on an RDMA controller in a v2 hierarchy.
+//!
+//! The operations are implemented as methods of [`Cgroup`], prefixed with `rdma_`.
+//!
+//! For more information about this controller, see the kernel's documentation
+//! [Documentation/admin-guide/cgroup-v2.rst].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # fn main() -> controlgroup::Result<()> {
+//! use std::path::PathBuf;
+//! use controlgroup::{Max, v2::{Cgroup, RdmaLimit}};
+//!
+//! let mut cgroup = Cgroup::new(PathBuf::from("students/charlie"));
+//! cgroup.create()?;
+//!
+//! let max = RdmaLimit {
+//!     hca_handle: 2.into(),
+//!     hca_object: Max::Max,
+//! };
+//! cgroup.set_rdma_max([("mlx4_0", max)].iter())?;
+//!
+//! // Do something ...
+//!
+//! let current = cgroup.rdma_current()?;
+//! println!("{:?}", current.get("mlx4_0"));
+//!
+//! cgroup.delete()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Cgroup`]: ../struct.Cgroup.html
+//!
+//! [Documentation/admin-guide/cgroup-v2.rst]: https://www.kernel.org/doc/Documentation/admin-guide/cgroup-v2.rst
+
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    v1::rdma::{parse_limits, Limit},
+    v2::Cgroup,
+    Result,
+};
+
+/// Resource limit on how much a cgroup can use RDMA/IB devices.
+///
+/// See the kernel's documentation for more information about the fields.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Resources {
+    /// How much this cgroup can use each RDMA/IB device. The key is the device name, and the value
+    /// is limit for the device.
+    ///
+    /// No limits will be applied if this map is empty.
+    pub max: HashMap<String, Limit>,
+}
+
+impl Cgroup {
+    pub(crate) fn apply_rdma(&mut self, resources: &Resources) -> Result<()> {
+        let max = &resources.max;
+
+        if max.is_empty() {
+            Ok(())
+        } else {
+            self.set_rdma_max(max.iter())
+        }
+    }
+
+    gen_getter!(
+        "rdma.current",
+        "the current usage of RDMA/IB devices",
+        rdma_current,
+        HashMap<String, Limit>,
+        parse_limits
+    );
+
+    gen_getter!(
+        "rdma.max",
+        "the usage limits on RDMA/IB devices",
+        rdma_max: rdma.max,
+        HashMap<String, Limit>,
+        parse_limits
+    );
+
+    with_doc! { concat!(
+        gen_doc!(
+            sets; "rdma.max",
+            "usage limits on RDMA/IB devices"
+             : "The first element of the iterator item is device name,
+                and the second is limit for the device."
+        ),
+        gen_doc!(see; rdma.max),
+        gen_doc!(err_write; "rdma.max"),
+        gen_doc!(
+            eg_write;
+            set_rdma_max,
+            [(
+                "mlx4_0",
+                v2::RdmaLimit { hca_handle: 3.into(), hca_object: controlgroup::Max::Max }
+            )].iter()
+        )),
+        pub fn set_rdma_max<I, T, K>(&mut self, limits: I) -> Result<()>
+        where
+            I: Iterator<Item = T>,
+            T: crate::RefKv<K, Limit>,
+            K: fmt::Display,
+        {
+            use std::io::Write;
+
+            let mut file = self.open_file_write("rdma.max")?;
+            for lim in limits {
+                let (device, limit) = lim.ref_kv();
+                file.write_all(format!("{} {}", device, limit).as_bytes())?;
+            }
+
+            Ok(())
+        }
+    }
+}
+
+impl From<Resources> for super::Resources {
+    fn from(resources: Resources) -> Self {
+        Self {
+            rdma: resources,
+            ..Self::default()
+        }
+    }
+}